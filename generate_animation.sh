@@ -1,6 +1,8 @@
 #!/bin/bash
 
-PHERO_DIR="/home/matheus/STG/pherohormones"
+# usage: ./generate_animation.sh [pherohormones dir]
+# the dir is <results>/<graph>/thread_<i>/pherohormones, the colony writes frames/frame_NNNN.dot in it
+PHERO_DIR="${1:-/home/matheus/STG/pherohormones}"
 INPUT_DIR="$PHERO_DIR/frames"
 OUTPUT_DIR="$PHERO_DIR/rendered_frames"
 OUTPUT_GIF="$PHERO_DIR/animation.gif"
@@ -17,4 +19,4 @@ done
 # Create GIF (50ms delay between frames)
 convert -delay 50 -loop 0 "$OUTPUT_DIR"/*.png "$OUTPUT_GIF"
 
-echo "Animation created at: $OUTPUT_GIF"
\ No newline at end of file
+echo "Animation created at: $OUTPUT_GIF"
@@ -0,0 +1,154 @@
+use std::collections::VecDeque;
+
+// epochs between two adjustments, the improvement rate is measured over the same window
+const PERIOD: usize = 25;
+// share of improving epochs in the window above which the colony counts as progressing
+const PROGRESS_RATE: f64 = 0.08;
+// factors applied to the evaporation rate and the base chance, the deposit rate is divided by them
+const EXPLORE: f64 = 1.25;
+const EXPLOIT: f64 = 0.9;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Adjustment {
+    // no improvement in the window: lay weaker trails, forget them faster and add noise
+    Explore,
+    // steady improvement: lay stronger trails, keep them longer and follow them more closely
+    Exploit,
+}
+
+// Online control of the deposit rate, the evaporation rate and the base chance of one colony
+// from the rate at which its best makespan improved over the last epochs. The evaporation rate
+// and the base chance stay inside the ranges the fixed per-thread draw used, the deposit rate
+// within half and twice the one the colony started with. The values are logged by the
+// telemetry every epoch.
+#[derive(Clone, Debug)]
+pub(crate) struct Adaptation {
+    // strict improvements of the best makespan over the recent epochs
+    pub recent: VecDeque<bool>,
+    pub epochs: usize,
+    // the deposit rate the colony started with, the ranges below scale with it
+    pub deposit_base: f64,
+    deposit_range: (f64, f64),
+    evaporation_range: (f64, f64),
+    // known once the max weight of the first epochs is
+    pub base_chance_range: Option<(f64, f64)>,
+}
+
+impl Adaptation {
+    pub fn new(deposit_base: f64) -> Adaptation {
+        Adaptation {
+            recent: VecDeque::with_capacity(PERIOD),
+            epochs: 0,
+            deposit_base,
+            deposit_range: (deposit_base * 0.5, deposit_base * 2.0),
+            evaporation_range: (deposit_base * 0.1, deposit_base * 0.75),
+            base_chance_range: None,
+        }
+    }
+
+    // the base chance scales with the max weight seen after the first epochs
+    pub fn set_max_weight(&mut self, max_weight: f64) {
+        self.base_chance_range = Some((max_weight / 100.0, 2.0 * max_weight));
+    }
+
+    pub fn improvement_rate(&self) -> f64 {
+        if self.recent.is_empty() {
+            return 0.0;
+        }
+        self.recent.iter().filter(|&&i| i).count() as f64 / self.recent.len() as f64
+    }
+
+    // Records one epoch and adjusts the parameters at the end of every period
+    pub fn update(
+        &mut self,
+        improved: bool,
+        deposit_rate: &mut f64,
+        evaporation_rate: &mut f64,
+        base_chance: &mut f64,
+    ) -> Option<Adjustment> {
+        if self.recent.len() == PERIOD {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(improved);
+        self.epochs += 1;
+        if !self.epochs.is_multiple_of(PERIOD) {
+            return None;
+        }
+
+        let rate = self.improvement_rate();
+        let (adjustment, factor) = if rate == 0.0 {
+            (Adjustment::Explore, EXPLORE)
+        } else if rate >= PROGRESS_RATE {
+            (Adjustment::Exploit, EXPLOIT)
+        } else {
+            return None;
+        };
+        let (min, max) = self.deposit_range;
+        *deposit_rate = (*deposit_rate / factor).clamp(min, max);
+        let (min, max) = self.evaporation_range;
+        *evaporation_rate = (*evaporation_rate * factor).clamp(min, max);
+        if let Some((min, max)) = self.base_chance_range {
+            *base_chance = (*base_chance * factor).clamp(min, max);
+        }
+        Some(adjustment)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the parameters after `periods` windows with the given improvement pattern
+    fn run(periods: usize, improved: impl Fn(usize) -> bool) -> (f64, f64, f64) {
+        let mut adaptation = Adaptation::new(0.01);
+        adaptation.set_max_weight(0.5);
+        let (mut deposit, mut evaporation, mut base_chance) = (0.01, 0.004, 0.1);
+        for epoch in 0..periods * PERIOD {
+            let adjustment = adaptation.update(
+                improved(epoch),
+                &mut deposit,
+                &mut evaporation,
+                &mut base_chance,
+            );
+            // only the last epoch of a period adjusts
+            if (epoch + 1) % PERIOD != 0 {
+                assert!(adjustment.is_none());
+            }
+        }
+        (deposit, evaporation, base_chance)
+    }
+
+    #[test]
+    fn stagnation_explores() {
+        let (deposit, evaporation, base_chance) = run(1, |_| false);
+        assert!((deposit - 0.008).abs() < 1e-12);
+        assert!((evaporation - 0.005).abs() < 1e-12);
+        assert!((base_chance - 0.125).abs() < 1e-12);
+    }
+
+    #[test]
+    fn progress_exploits() {
+        let (deposit, evaporation, base_chance) = run(1, |epoch| epoch % 5 == 0);
+        assert!((deposit - 0.01 / 0.9).abs() < 1e-12);
+        assert!((evaporation - 0.0036).abs() < 1e-12);
+        assert!((base_chance - 0.09).abs() < 1e-12);
+    }
+
+    #[test]
+    fn slow_progress_keeps_the_parameters() {
+        // one improvement in 25 epochs is below the progress rate
+        assert_eq!(run(2, |epoch| epoch % PERIOD == 3), (0.01, 0.004, 0.1));
+    }
+
+    #[test]
+    fn parameters_stay_in_their_ranges() {
+        let (deposit, evaporation, base_chance) = run(40, |_| false);
+        assert_eq!(deposit, 0.005);
+        assert_eq!(evaporation, 0.0075);
+        assert_eq!(base_chance, 1.0);
+        let (deposit, evaporation, base_chance) = run(40, |_| true);
+        assert_eq!(deposit, 0.02);
+        assert_eq!(evaporation, 0.001);
+        assert_eq!(base_chance, 0.005);
+    }
+}
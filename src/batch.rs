@@ -0,0 +1,385 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+
+use crate::config::SolverConfig;
+use crate::export::create_parent;
+use crate::logging::{error, info};
+use crate::solver;
+use crate::stopping::CancelToken;
+
+// How the cores are shared out: several instances at once, or one instance at a time with
+// all its colonies in parallel
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Parallelism {
+    Instances,
+    Colonies,
+}
+
+pub(crate) struct BatchOptions {
+    pub instances_dir: String,
+    // file name pattern, '*' and '?' wildcards
+    pub pattern: String,
+    pub out_dir: String,
+    pub config: SolverConfig,
+    // known optimal makespans by instance name
+    pub optima: HashMap<String, i32>,
+    pub parallelism: Parallelism,
+}
+
+pub(crate) struct BatchSummary {
+    pub results_file: String,
+    pub solved: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    // interrupted or never started, solved again by the next batch
+    pub cancelled: usize,
+}
+
+const HEADER: &str = "instance,n_tasks,n_workers,makespan,lower_bound,optimum,gap_to_optimum,gap_to_lower_bound,seed,runtime_seconds,status";
+
+// Solves every matching instance and appends one row per instance to <out>/batch_results.csv.
+// Instances that already have an ok row are skipped, so an interrupted batch resumes where it stopped.
+// Once the rows of this batch are written, the failed and cancelled rows of an instance are
+// dropped in favour of its ok row or its newest one.
+pub(crate) fn run(options: &BatchOptions, cancel: &CancelToken) -> io::Result<BatchSummary> {
+    let mut instances = list_instances(&options.instances_dir, &options.pattern)?;
+
+    let results_file = format!("{}/batch_results.csv", options.out_dir);
+    let done = completed(&results_file)?;
+    let skipped = instances.iter().filter(|name| done.contains(*name)).count();
+    instances.retain(|name| !done.contains(name));
+    info!(
+        "{} instances to solve, {} already in {}",
+        instances.len(),
+        skipped,
+        results_file
+    );
+
+    create_parent(&results_file)?;
+    let is_new = fs::metadata(&results_file).map_or(true, |m| m.len() == 0);
+    let mut out = BufWriter::new(
+        OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(&results_file)?,
+    );
+    if is_new {
+        writeln!(out, "{}", HEADER)?;
+        out.flush()?;
+    }
+    let out = Mutex::new(out);
+    let failed = Mutex::new(0);
+    let solved = Mutex::new(0);
+
+    let instances_dir = format!("{}/", options.instances_dir.trim_end_matches('/'));
+    let solve_one = |name: &String| {
+        if cancel.is_cancelled() {
+            return;
+        }
+        let row = solve_instance(options, &instances_dir, name, cancel);
+        if row.ends_with(",failed") {
+            *failed.lock().unwrap() += 1;
+        } else if row.ends_with(",ok") {
+            *solved.lock().unwrap() += 1;
+        }
+        // a row is flushed as soon as it is known so an interrupted batch loses nothing
+        let mut out = out.lock().unwrap();
+        writeln!(out, "{}", row).expect("Failed to write batch results");
+        out.flush().expect("Failed to write batch results");
+    };
+    match options.parallelism {
+        Parallelism::Instances => instances.par_iter().for_each(solve_one),
+        Parallelism::Colonies => instances.iter().for_each(solve_one),
+    }
+
+    out.into_inner().unwrap().flush()?;
+    drop_superseded(&results_file)?;
+
+    let failed = failed.into_inner().unwrap();
+    let solved = solved.into_inner().unwrap();
+    Ok(BatchSummary {
+        results_file,
+        solved,
+        skipped,
+        failed,
+        cancelled: instances.len() - solved - failed,
+    })
+}
+
+// file names in dir matching the pattern, sorted
+pub(crate) fn list_instances(dir: &str, pattern: &str) -> io::Result<Vec<String>> {
+    let mut instances: Vec<String> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_file())
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .filter(|name| glob_match(pattern, name))
+        .collect();
+    instances.sort();
+    Ok(instances)
+}
+
+fn solve_instance(
+    options: &BatchOptions,
+    instances_dir: &str,
+    name: &str,
+    cancel: &CancelToken,
+) -> String {
+    let stem = Path::new(name)
+        .file_stem()
+        .map_or(name.to_string(), |s| s.to_string_lossy().to_string());
+    let results_path = format!("{}/{}/", options.out_dir, stem);
+    let start = Instant::now();
+    // one broken instance must not take the whole batch down
+    let outcome = panic::catch_unwind(AssertUnwindSafe(|| {
+        solver::solve_file(&options.config, instances_dir, name, &results_path, cancel)
+    }));
+    let runtime = start.elapsed().as_secs_f64();
+
+    let outcome = match outcome {
+        Ok(Ok(outcome)) => outcome,
+        Ok(Err(e)) => return failed_row(name, runtime, &e.to_string()),
+        Err(_) => return failed_row(name, runtime, "the solver panicked"),
+    };
+    // a branch-and-bound proof is as good as a published optimum
+    let optimum = optimum_of(&options.optima, name).or(outcome
+        .exact
+        .as_ref()
+        .filter(|exact| exact.proven_optimal)
+        .map(|exact| exact.makespan));
+    let gap_to_optimum = optimum
+        .filter(|&opt| opt > 0)
+        .map(|opt| format!("{:.6}", (outcome.makespan - opt) as f64 / opt as f64))
+        .unwrap_or_default();
+    info!(
+        "Instance {}: makespan {} lower bound {} in {:.1}s",
+        name, outcome.makespan, outcome.lower_bound, runtime
+    );
+    format!(
+        "{},{},{},{},{},{},{},{:.6},{},{:.3},{}",
+        name,
+        outcome.n_tasks,
+        outcome.n_workers,
+        outcome.makespan,
+        outcome.lower_bound,
+        optimum.map(|o| o.to_string()).unwrap_or_default(),
+        gap_to_optimum,
+        outcome.gap,
+        outcome.seed,
+        runtime,
+        // kept for the record, a later batch solves the instance again
+        if outcome.cancelled { "cancelled" } else { "ok" }
+    )
+}
+
+fn failed_row(name: &str, runtime: f64, reason: &str) -> String {
+    error!("Instance {} failed: {}", name, reason);
+    format!("{},,,,,,,,,{:.3},failed", name, runtime)
+}
+
+// instances with an ok row in a previous results file
+fn completed(results_file: &str) -> io::Result<HashSet<String>> {
+    let contents = match fs::read_to_string(results_file) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(HashSet::new()),
+        Err(e) => return Err(e),
+    };
+    Ok(contents
+        .lines()
+        .skip(1)
+        .filter(|line| line.ends_with(",ok"))
+        .filter_map(|line| line.split(',').next())
+        .map(|name| name.to_string())
+        .collect())
+}
+
+// Rewrites the results file with a single row per instance once the batch wrote its rows: the
+// ok row of a solved instance, the newest row of the others. A machine stopped mid-rewrite keeps
+// the old file, which only has rows to spare.
+fn drop_superseded(results_file: &str) -> io::Result<()> {
+    let contents = fs::read_to_string(results_file)?;
+    let lines: Vec<&str> = contents.lines().collect();
+    let name_of = |line: &str| line.split(',').next().unwrap_or_default().to_string();
+    let mut newest = HashMap::new();
+    let mut solved = HashSet::new();
+    for (index, line) in lines.iter().enumerate().skip(1) {
+        newest.insert(name_of(line), index);
+        if line.ends_with(",ok") {
+            solved.insert(name_of(line));
+        }
+    }
+    let kept: Vec<&str> = lines
+        .iter()
+        .enumerate()
+        .filter(|&(index, line)| {
+            let name = name_of(line);
+            index == 0
+                || line.ends_with(",ok")
+                || (!solved.contains(&name) && newest.get(&name) == Some(&index))
+        })
+        .map(|(_, line)| *line)
+        .collect();
+    if kept.len() == lines.len() {
+        return Ok(());
+    }
+    info!(
+        "Dropped {} superseded rows from {}",
+        lines.len() - kept.len(),
+        results_file
+    );
+    let temp = format!("{}.tmp", results_file);
+    let mut out = BufWriter::new(fs::File::create(&temp)?);
+    for line in kept {
+        writeln!(out, "{}", line)?;
+    }
+    out.flush()?;
+    fs::rename(&temp, results_file)
+}
+
+// Optimal makespans, one "<instance> <makespan>" pair per line (comma or whitespace separated).
+// The instance may be given with or without its extension.
+pub(crate) fn read_optima(path: &str) -> io::Result<HashMap<String, i32>> {
+    let mut optima = HashMap::new();
+    for (line_number, line) in fs::read_to_string(path)?.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|f| !f.is_empty())
+            .collect();
+        match (fields.first(), fields.get(1).and_then(|v| v.parse().ok())) {
+            (Some(name), Some(value)) => {
+                optima.insert(name.to_string(), value);
+            }
+            // a header line
+            _ if line_number == 0 => {}
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "{}:{}: expected <instance> <makespan>",
+                        path,
+                        line_number + 1
+                    ),
+                ))
+            }
+        }
+    }
+    Ok(optima)
+}
+
+pub(crate) fn optimum_of(optima: &HashMap<String, i32>, name: &str) -> Option<i32> {
+    optima.get(name).copied().or_else(|| {
+        let stem = Path::new(name).file_stem()?.to_string_lossy();
+        optima.get(stem.as_ref()).copied()
+    })
+}
+
+// shell style wildcard match, '*' any run of characters and '?' exactly one
+pub(crate) fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // where the last '*' was and how much of the name it had swallowed
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, n));
+            p += 1;
+        } else if let Some((star_p, star_n)) = star {
+            p = star_p + 1;
+            n = star_n + 1;
+            star = Some((star_p, star_n + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!(
+                "task_scheduling_batch_{}_{}",
+                std::process::id(),
+                name
+            ))
+            .to_string_lossy()
+            .to_string()
+    }
+
+    #[test]
+    fn glob_matches_wildcards() {
+        assert!(glob_match("*.stg", "rand0000.stg"));
+        assert!(glob_match("rand00??.stg", "rand0042.stg"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("a*b*c", "aXbYbZc"));
+        assert!(glob_match("**.stg", ".stg"));
+        assert!(!glob_match("*.stg", "rand0000.stg.bak"));
+        assert!(!glob_match("rand00??.stg", "rand001.stg"));
+        assert!(!glob_match("a*b*c", "aXbYcZ"));
+        assert!(!glob_match("", "a"));
+    }
+
+    #[test]
+    fn superseded_rows_give_way_to_ok_and_newest_rows() {
+        let path = path("results.csv");
+        let rows = [
+            HEADER,
+            "a.stg,,,,,,,,,0.1,failed",
+            "b.stg,,,,,,,,,0.1,failed",
+            "a.stg,10,2,30,28,,,0.071429,1,2.000,ok",
+            "b.stg,,,,,,,,,0.2,cancelled",
+            "c.stg,,,,,,,,,0.3,failed",
+        ];
+        fs::write(&path, rows.join("\n") + "\n").unwrap();
+        drop_superseded(&path).unwrap();
+        let kept = fs::read_to_string(&path).unwrap();
+        assert_eq!(
+            kept.lines().collect::<Vec<_>>(),
+            vec![HEADER, rows[3], rows[4], rows[5]]
+        );
+        assert_eq!(
+            completed(&path).unwrap(),
+            HashSet::from(["a.stg".to_string()])
+        );
+        let _ = fs::remove_file(&path);
+        assert!(completed(&path).unwrap().is_empty());
+    }
+
+    #[test]
+    fn optima_with_or_without_extension() {
+        let path = path("optima.txt");
+        fs::write(
+            &path,
+            "instance,optimum\n# comment\nrand0000 112\nrand0001.stg,98\n",
+        )
+        .unwrap();
+        let optima = read_optima(&path).unwrap();
+        assert_eq!(optimum_of(&optima, "rand0000.stg"), Some(112));
+        assert_eq!(optimum_of(&optima, "rand0001.stg"), Some(98));
+        assert_eq!(optimum_of(&optima, "rand0002.stg"), None);
+
+        fs::write(&path, "rand0000 112\nrand0001\n").unwrap();
+        let error = read_optima(&path).unwrap_err();
+        let _ = fs::remove_file(&path);
+        assert!(error
+            .to_string()
+            .ends_with(":2: expected <instance> <makespan>"));
+    }
+}
@@ -0,0 +1,233 @@
+use std::fmt;
+
+use petgraph::algo::toposort;
+use petgraph::stable_graph::NodeIndex;
+use petgraph::Direction;
+
+use crate::utils::Utils;
+
+// upper limit on the work spent on the Fernandez-Bussell interval search, the candidate
+// interval ends are thinned out evenly past this point (any subset of intervals still gives a valid bound)
+const FB_WORK_LIMIT: usize = 50_000_000;
+
+// Lower bounds on the makespan of an instance for a given number of workers
+#[derive(Clone, Debug)]
+pub(crate) struct LowerBounds {
+    pub critical_path: i32,
+    pub work: i32,
+    pub fernandez_bussell: i32,
+    pub n_workers: i32,
+}
+
+impl LowerBounds {
+    pub fn new(utils: &Utils, n_workers: i32) -> LowerBounds {
+        let n_workers = n_workers.max(1);
+        let (heads, tails) = heads_and_tails(utils);
+        let critical_path = heads
+            .iter()
+            .zip(&tails)
+            .map(|(h, t)| h + t)
+            .max()
+            .unwrap_or(0);
+        let total_work: i64 = utils.costs_vec.iter().map(|&c| c as i64).sum();
+        let work = ((total_work + n_workers as i64 - 1) / n_workers as i64) as i32;
+        let fernandez_bussell =
+            fernandez_bussell(&utils.costs_vec, &heads, &tails, critical_path, n_workers);
+
+        LowerBounds {
+            critical_path,
+            work,
+            fernandez_bussell,
+            n_workers,
+        }
+    }
+
+    // the tightest of all bounds
+    pub fn best(&self) -> i32 {
+        self.critical_path
+            .max(self.work)
+            .max(self.fernandez_bussell)
+    }
+
+    // relative distance of a makespan to the best bound, 0.0 means proven optimal
+    pub fn gap(&self, makespan: i32) -> f64 {
+        let lb = self.best();
+        if lb <= 0 {
+            return 0.0;
+        }
+        (makespan - lb) as f64 / lb as f64
+    }
+}
+
+impl fmt::Display for LowerBounds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Lower Bound: {} (critical path {}, work/{} {}, Fernandez-Bussell {})",
+            self.best(),
+            self.critical_path,
+            self.n_workers,
+            self.work,
+            self.fernandez_bussell
+        )
+    }
+}
+
+// heads are the earliest start of each task, tails the longest path from the task (cost included) to the end
+pub(crate) fn heads_and_tails(utils: &Utils) -> (Vec<i32>, Vec<i32>) {
+    let n = utils.n_tasks as usize;
+    let costs = &utils.costs_vec;
+    let order = toposort(&utils.di_graph, None).expect("task graph must be acyclic");
+
+    let mut heads = vec![0; n];
+    for node in &order {
+        let task = node.index();
+        for succ in utils
+            .di_graph
+            .neighbors_directed(*node, Direction::Outgoing)
+        {
+            let finish = heads[task] + costs[task];
+            if heads[succ.index()] < finish {
+                heads[succ.index()] = finish;
+            }
+        }
+    }
+
+    let mut tails = vec![0; n];
+    for node in order.iter().rev() {
+        let task = node.index();
+        let longest = utils
+            .di_graph
+            .neighbors_directed(NodeIndex::new(task), Direction::Outgoing)
+            .map(|succ| tails[succ.index()])
+            .max()
+            .unwrap_or(0);
+        tails[task] = costs[task] + longest;
+    }
+
+    (heads, tails)
+}
+
+// Fernandez and Bussell (1973): in any interval [a, b] the workers must at least process the part of
+// every task that cannot be moved out of it, which gives cp + ceil(work(a, b) / m - (b - a))
+fn fernandez_bussell(
+    costs: &[i32],
+    heads: &[i32],
+    tails: &[i32],
+    critical_path: i32,
+    n_workers: i32,
+) -> i32 {
+    let n = costs.len();
+    if n == 0 {
+        return 0;
+    }
+    // latest completion time of each task when the makespan is the critical path
+    let latest: Vec<i32> = tails
+        .iter()
+        .zip(costs)
+        .map(|(t, c)| critical_path - t + c)
+        .collect();
+
+    let mut starts: Vec<i32> = heads.to_vec();
+    starts.push(0);
+    starts.sort_unstable();
+    starts.dedup();
+    let mut ends: Vec<i32> = latest.clone();
+    ends.push(critical_path);
+    ends.sort_unstable();
+    ends.dedup();
+
+    let max_points = ((FB_WORK_LIMIT / n) as f64).sqrt().max(2.0) as usize;
+    let starts = thin_out(starts, max_points);
+    let ends = thin_out(ends, max_points);
+
+    let mut best = 0_i64;
+    for &a in &starts {
+        for &b in ends.iter().filter(|&&b| b > a) {
+            let mut work = 0_i64;
+            for i in 0..n {
+                let overlap = (b - a)
+                    .min(costs[i])
+                    .min(heads[i] + costs[i] - a)
+                    .min(b - (latest[i] - costs[i]));
+                if overlap > 0 {
+                    work += overlap as i64;
+                }
+            }
+            // ceil(work / m) - (b - a)
+            let m = n_workers as i64;
+            let excess = (work + m - 1) / m - (b - a) as i64;
+            best = best.max(excess);
+        }
+    }
+
+    critical_path + best as i32
+}
+
+// keeps at most max_points values spread evenly, always including the first and the last
+fn thin_out(points: Vec<i32>, max_points: usize) -> Vec<i32> {
+    if points.len() <= max_points {
+        return points;
+    }
+    let step = (points.len() - 1) as f64 / (max_points - 1) as f64;
+    (0..max_points)
+        .map(|k| points[((k as f64 * step).round() as usize).min(points.len() - 1)])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils;
+
+    // 1 -> 3 is the critical path, 4 waits for 1 and 2
+    const GRAPH: &str = "4\n0 0 0\n1 3 1 0\n2 2 1 0\n3 4 1 1\n4 1 2 1 2\n5 0 2 3 4\n";
+
+    #[test]
+    fn heads_and_tails_follow_the_longest_paths() {
+        let utils = utils::from_stg(GRAPH);
+        let (heads, tails) = heads_and_tails(&utils);
+        assert_eq!(heads, vec![0, 0, 0, 3, 3, 7]);
+        assert_eq!(tails, vec![7, 7, 3, 4, 1, 0]);
+    }
+
+    #[test]
+    fn critical_path_bounds_two_workers() {
+        let bounds = LowerBounds::new(&utils::from_stg(GRAPH), 2);
+        assert_eq!(bounds.critical_path, 7);
+        assert_eq!(bounds.work, 5);
+        assert_eq!(bounds.fernandez_bussell, 7);
+        assert_eq!(bounds.best(), 7);
+        assert_eq!(bounds.gap(7), 0.0);
+        assert!((bounds.gap(8) - 1.0 / 7.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn work_bounds_one_worker() {
+        let bounds = LowerBounds::new(&utils::from_stg(GRAPH), 1);
+        assert_eq!(bounds.work, 10);
+        assert_eq!(bounds.best(), 10);
+    }
+
+    #[test]
+    fn fernandez_bussell_sees_the_crowded_interval() {
+        // three tasks of 2 that must all run in [0, 2] next to a chain of 4 after them
+        let stg = "5\n0 0 0\n1 2 1 0\n2 2 1 0\n3 2 1 0\n4 2 3 1 2 3\n5 2 1 4\n6 0 1 5\n";
+        let bounds = LowerBounds::new(&utils::from_stg(stg), 2);
+        assert_eq!(bounds.critical_path, 6);
+        assert_eq!(bounds.work, 5);
+        // the three tasks need 3 units on 2 workers before the chain can start
+        assert_eq!(bounds.fernandez_bussell, 7);
+        assert_eq!(bounds.best(), 7);
+    }
+
+    #[test]
+    fn thin_out_keeps_both_ends() {
+        let points: Vec<i32> = (0..100).collect();
+        let thinned = thin_out(points, 5);
+        assert_eq!(thinned.len(), 5);
+        assert_eq!(thinned.first(), Some(&0));
+        assert_eq!(thinned.last(), Some(&99));
+        assert_eq!(thin_out(vec![1, 2], 5), vec![1, 2]);
+    }
+}
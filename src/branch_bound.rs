@@ -0,0 +1,454 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use petgraph::stable_graph::NodeIndex;
+use petgraph::Direction;
+
+use crate::bounds::{self, LowerBounds};
+use crate::schedule::{Schedule, ScheduledTask};
+use crate::utils::Utils;
+
+// stop storing new states in the dominance table once it holds this many signatures
+const MAX_STORED_STATES: usize = 2_000_000;
+
+// Outcome of a branch-and-bound search, when a limit is hit the best found schedule
+// is returned together with the gap that is still open
+#[derive(Clone, Debug)]
+pub struct BnbResult {
+    pub makespan: i32,
+    pub schedule: Schedule,
+    pub proven_optimal: bool,
+    pub lower_bound: i32,
+    pub nodes: u64,
+    pub elapsed: Duration,
+}
+
+impl BnbResult {
+    pub fn gap(&self) -> f64 {
+        if self.lower_bound <= 0 {
+            return 0.0;
+        }
+        (self.makespan - self.lower_bound) as f64 / self.lower_bound as f64
+    }
+}
+
+// Exact solver for small instances: depth first search over the order in which ready tasks are started,
+// every task goes to the worker that is free first. Only orders with non decreasing start times are
+// explored, which still contains an optimal schedule and removes most of the symmetric branches.
+pub(crate) struct BranchAndBound {
+    n_tasks: usize,
+    n_workers: usize,
+    costs: Vec<i32>,
+    preds: Vec<Vec<usize>>,
+    succs: Vec<Vec<usize>>,
+    heads: Vec<i32>,
+    tails: Vec<i32>,
+    root_bound: i32,
+    node_limit: u64,
+    time_limit: Duration,
+
+    // search state
+    start: Vec<i32>,
+    worker: Vec<usize>,
+    scheduled: Vec<bool>,
+    missing_preds: Vec<usize>,
+    free: Vec<i32>,
+    remaining_work: i64,
+    n_scheduled: usize,
+
+    // incumbent
+    best_makespan: i32,
+    best_start: Vec<i32>,
+    best_worker: Vec<usize>,
+    best_schedule: Schedule,
+
+    seen: HashMap<Vec<u64>, Vec<Vec<i32>>>,
+    n_seen: usize,
+    nodes: u64,
+    started_at: Instant,
+    aborted: bool,
+}
+
+impl BranchAndBound {
+    pub fn new(
+        utils: &Utils,
+        n_workers: i32,
+        node_limit: u64,
+        time_limit: Duration,
+    ) -> BranchAndBound {
+        let n_tasks = utils.n_tasks as usize;
+        let mut preds = vec![Vec::new(); n_tasks];
+        let mut succs = vec![Vec::new(); n_tasks];
+        for (task, task_succs) in succs.iter_mut().enumerate() {
+            for succ in utils
+                .di_graph
+                .neighbors_directed(NodeIndex::new(task), Direction::Outgoing)
+            {
+                task_succs.push(succ.index());
+                preds[succ.index()].push(task);
+            }
+        }
+        let (heads, tails) = bounds::heads_and_tails(utils);
+        let root_bound = LowerBounds::new(utils, n_workers).best();
+        let missing_preds = preds.iter().map(|p| p.len()).collect();
+
+        BranchAndBound {
+            n_tasks,
+            n_workers: n_workers.max(1) as usize,
+            costs: utils.costs_vec.clone(),
+            preds,
+            succs,
+            heads,
+            tails,
+            root_bound,
+            node_limit,
+            time_limit,
+            start: vec![-1; n_tasks],
+            worker: vec![0; n_tasks],
+            scheduled: vec![false; n_tasks],
+            missing_preds,
+            free: vec![0; n_workers.max(1) as usize],
+            remaining_work: utils.costs_vec.iter().map(|&c| c as i64).sum(),
+            n_scheduled: 0,
+            best_makespan: i32::MAX,
+            best_start: Vec::new(),
+            best_worker: Vec::new(),
+            best_schedule: Schedule::default(),
+            seen: HashMap::new(),
+            n_seen: 0,
+            nodes: 0,
+            started_at: Instant::now(),
+            aborted: false,
+        }
+    }
+
+    // the incumbent schedule (usually the best colony) is used as the first upper bound
+    pub fn solve(&mut self, incumbent: &Schedule) -> BnbResult {
+        self.started_at = Instant::now();
+        if !incumbent.is_empty() {
+            self.best_makespan = incumbent.makespan;
+            self.best_schedule = incumbent.clone();
+        }
+
+        if self.best_makespan > self.root_bound {
+            self.branch(0);
+        }
+
+        if !self.best_start.is_empty() {
+            self.best_schedule = self.build_schedule();
+        }
+        let proven_optimal = !self.aborted || self.best_makespan <= self.root_bound;
+        BnbResult {
+            makespan: self.best_makespan,
+            schedule: self.best_schedule.clone(),
+            proven_optimal,
+            lower_bound: if proven_optimal {
+                self.best_makespan
+            } else {
+                self.root_bound
+            },
+            nodes: self.nodes,
+            elapsed: self.started_at.elapsed(),
+        }
+    }
+
+    fn branch(&mut self, last_start: i32) {
+        self.nodes += 1;
+        if self.nodes >= self.node_limit
+            || (self.nodes.is_multiple_of(1024) && self.started_at.elapsed() >= self.time_limit)
+        {
+            self.aborted = true;
+        }
+        if self.aborted {
+            return;
+        }
+
+        if self.n_scheduled == self.n_tasks {
+            let makespan = (0..self.n_tasks)
+                .map(|t| self.start[t] + self.costs[t])
+                .max()
+                .unwrap_or(0);
+            if makespan < self.best_makespan {
+                self.best_makespan = makespan;
+                self.best_start = self.start.clone();
+                self.best_worker = self.worker.clone();
+            }
+            return;
+        }
+
+        let (free_worker, free_at) = self
+            .free
+            .iter()
+            .enumerate()
+            .min_by_key(|&(_, &f)| f)
+            .map(|(w, &f)| (w, f))
+            .unwrap();
+
+        // candidates are ready tasks that can start no earlier than the previous one
+        let mut children: Vec<(i32, i32, usize)> = Vec::new();
+        for task in 0..self.n_tasks {
+            if self.scheduled[task] || self.missing_preds[task] > 0 {
+                continue;
+            }
+            let start = free_at.max(self.pred_ready(task));
+            if start < last_start {
+                continue;
+            }
+            let bound = self.child_bound(task, free_worker, start);
+            if bound < self.best_makespan {
+                children.push((bound, -self.tails[task], task));
+            }
+        }
+        children.sort_unstable();
+
+        for (bound, _, task) in children {
+            if bound >= self.best_makespan || self.aborted {
+                break;
+            }
+            let start = free_at.max(self.pred_ready(task));
+            self.apply(task, free_worker, start);
+            if self.is_new_state(start) {
+                self.branch(start);
+            }
+            self.undo(task, free_worker, free_at);
+        }
+    }
+
+    fn pred_ready(&self, task: usize) -> i32 {
+        self.preds[task]
+            .iter()
+            .filter(|&&p| self.scheduled[p])
+            .map(|&p| self.start[p] + self.costs[p])
+            .max()
+            .unwrap_or(0)
+    }
+
+    // bound on the makespan of any completion after starting task on worker at start
+    fn child_bound(&self, task: usize, worker: usize, start: i32) -> i32 {
+        let finish = start + self.costs[task];
+
+        // every worker is busy or idle until its free time and the remaining work has to fit after it
+        let free_sum: i64 = self
+            .free
+            .iter()
+            .enumerate()
+            .map(|(w, &f)| if w == worker { finish as i64 } else { f as i64 })
+            .sum();
+        let work = free_sum + self.remaining_work - self.costs[task] as i64;
+        let m = self.n_workers as i64;
+        let mut bound = ((work + m - 1) / m) as i32;
+
+        // the task itself and everything still unscheduled starts no earlier than it
+        bound = bound.max(start + self.tails[task]);
+        for other in 0..self.n_tasks {
+            if self.scheduled[other] || other == task {
+                continue;
+            }
+            let earliest = start.max(self.heads[other]).max(self.pred_ready(other));
+            bound = bound.max(earliest + self.tails[other]);
+        }
+        bound.max(self.root_bound)
+    }
+
+    fn apply(&mut self, task: usize, worker: usize, start: i32) {
+        self.start[task] = start;
+        self.worker[task] = worker;
+        self.scheduled[task] = true;
+        self.free[worker] = start + self.costs[task];
+        self.remaining_work -= self.costs[task] as i64;
+        self.n_scheduled += 1;
+        for &succ in &self.succs[task] {
+            self.missing_preds[succ] -= 1;
+        }
+    }
+
+    fn undo(&mut self, task: usize, worker: usize, free_at: i32) {
+        self.start[task] = -1;
+        self.scheduled[task] = false;
+        self.free[worker] = free_at;
+        self.remaining_work += self.costs[task] as i64;
+        self.n_scheduled -= 1;
+        for &succ in &self.succs[task] {
+            self.missing_preds[succ] += 1;
+        }
+    }
+
+    // dominance pruning: two partial schedules with the same scheduled set are compared on the last start,
+    // the sorted worker free times and the finish of tasks that still have unscheduled successors.
+    // A state that is no better on all of them than one already explored is skipped.
+    fn is_new_state(&mut self, last_start: i32) -> bool {
+        let mut key = vec![0_u64; self.n_tasks.div_ceil(64)];
+        for task in 0..self.n_tasks {
+            if self.scheduled[task] {
+                key[task / 64] |= 1 << (task % 64);
+            }
+        }
+        let mut signature = vec![last_start];
+        let mut free = self.free.clone();
+        free.sort_unstable();
+        signature.extend(free);
+        for task in 0..self.n_tasks {
+            if self.scheduled[task] && self.succs[task].iter().any(|&s| !self.scheduled[s]) {
+                signature.push(self.start[task] + self.costs[task]);
+            }
+        }
+
+        let stored = self.seen.entry(key).or_default();
+        if stored
+            .iter()
+            .any(|old| old.iter().zip(&signature).all(|(o, s)| o <= s))
+        {
+            return false;
+        }
+        if self.n_seen < MAX_STORED_STATES {
+            stored.push(signature);
+            self.n_seen += 1;
+        }
+        true
+    }
+
+    fn build_schedule(&self) -> Schedule {
+        let entries: Vec<ScheduledTask> = (0..self.n_tasks)
+            .map(|task| ScheduledTask {
+                task: task as i32,
+                worker: self.best_worker[task] as i32,
+                start: self.best_start[task],
+                finish: self.best_start[task] + self.costs[task],
+            })
+            .collect();
+        Schedule::new(self.n_tasks as i32, self.n_workers as i32, &entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+
+    use super::*;
+    use crate::utils;
+    use crate::validator;
+
+    fn solve(utils: &Utils, n_workers: i32, node_limit: u64) -> BnbResult {
+        BranchAndBound::new(utils, n_workers, node_limit, Duration::from_secs(60))
+            .solve(&Schedule::default())
+    }
+
+    // best makespan over every topological order, each task started as early as possible on the
+    // worker free first
+    fn brute_force(utils: &Utils, n_workers: usize) -> i32 {
+        fn search(
+            utils: &Utils,
+            preds: &[Vec<i32>],
+            finish: &mut Vec<Option<i32>>,
+            free: &mut Vec<i32>,
+        ) -> i32 {
+            let ready: Vec<usize> = (0..finish.len())
+                .filter(|&task| finish[task].is_none())
+                .filter(|&task| preds[task].iter().all(|&p| finish[p as usize].is_some()))
+                .collect();
+            if ready.is_empty() {
+                return finish.iter().flatten().copied().max().unwrap_or(0);
+            }
+            let mut best = i32::MAX;
+            for task in ready {
+                let (worker, free_at) = free
+                    .iter()
+                    .copied()
+                    .enumerate()
+                    .min_by_key(|&(_, f)| f)
+                    .unwrap();
+                let pred_ready = preds[task]
+                    .iter()
+                    .map(|&p| finish[p as usize].unwrap())
+                    .max()
+                    .unwrap_or(0);
+                let end = free_at.max(pred_ready) + utils.costs_vec[task];
+                finish[task] = Some(end);
+                free[worker] = end;
+                best = best.min(search(utils, preds, finish, free));
+                free[worker] = free_at;
+                finish[task] = None;
+            }
+            best
+        }
+        let preds = utils.predecessors();
+        search(
+            utils,
+            &preds,
+            &mut vec![None; utils.n_tasks as usize],
+            &mut vec![0; n_workers],
+        )
+    }
+
+    // a random graph of n tasks between the entry and exit tasks
+    fn random_graph(rng: &mut ChaCha8Rng, n: i32) -> String {
+        let mut stg = format!("{}\n0 0 0\n", n);
+        for task in 1..=n {
+            let mut preds: Vec<i32> = (1..task).filter(|_| rng.random_bool(0.3)).collect();
+            if preds.is_empty() {
+                preds.push(0);
+            }
+            let preds: Vec<String> = preds.iter().map(|p| p.to_string()).collect();
+            stg.push_str(&format!(
+                "{} {} {} {}\n",
+                task,
+                rng.random_range(1..7),
+                preds.len(),
+                preds.join(" ")
+            ));
+        }
+        let all: Vec<String> = (1..=n).map(|t| t.to_string()).collect();
+        stg.push_str(&format!("{} 0 {} {}\n", n + 1, n, all.join(" ")));
+        stg
+    }
+
+    #[test]
+    fn proves_an_optimum_above_the_root_bound() {
+        // three tasks of 2 on two workers: the bounds say 3, any schedule needs 4
+        let utils = utils::from_stg("3\n0 0 0\n1 2 1 0\n2 2 1 0\n3 2 1 0\n4 0 3 1 2 3\n");
+        assert_eq!(LowerBounds::new(&utils, 2).best(), 3);
+        let result = solve(&utils, 2, u64::MAX);
+        assert_eq!(result.makespan, 4);
+        assert!(result.proven_optimal);
+        assert_eq!(result.lower_bound, 4);
+        assert_eq!(result.gap(), 0.0);
+        let entries = result.schedule.entries();
+        assert!(validator::validate(&utils, 2, &entries, 4).is_empty());
+    }
+
+    #[test]
+    fn matches_brute_force_on_small_graphs() {
+        let mut rng = ChaCha8Rng::seed_from_u64(7);
+        for _ in 0..12 {
+            let utils = utils::from_stg(&random_graph(&mut rng, 6));
+            for n_workers in 1..=3 {
+                let result = solve(&utils, n_workers, u64::MAX);
+                assert!(result.proven_optimal);
+                assert_eq!(result.makespan, brute_force(&utils, n_workers as usize));
+                let entries = result.schedule.entries();
+                assert!(
+                    validator::validate(&utils, n_workers, &entries, result.makespan).is_empty()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn keeps_an_optimal_incumbent() {
+        let utils = utils::from_stg("3\n0 0 0\n1 2 1 0\n2 2 1 0\n3 2 1 0\n4 0 3 1 2 3\n");
+        let incumbent = solve(&utils, 2, u64::MAX).schedule;
+        let result =
+            BranchAndBound::new(&utils, 2, u64::MAX, Duration::from_secs(60)).solve(&incumbent);
+        assert_eq!(result.makespan, 4);
+        assert!(result.proven_optimal);
+    }
+
+    #[test]
+    fn node_limit_leaves_the_root_bound() {
+        let utils = utils::from_stg("3\n0 0 0\n1 2 1 0\n2 2 1 0\n3 2 1 0\n4 0 3 1 2 3\n");
+        let result = solve(&utils, 2, 1);
+        assert!(!result.proven_optimal);
+        assert_eq!(result.lower_bound, 3);
+    }
+}
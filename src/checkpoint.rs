@@ -0,0 +1,365 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::adaptive::Adaptation;
+use crate::config::SolverConfig;
+use crate::export;
+use crate::schedule::ScheduledTask;
+use crate::stopping::StopReason;
+
+// Everything a colony carries from one epoch to the next, written every `checkpoint_every`
+// epochs so a seeded run resumed from it makes the same choices as one never interrupted.
+// Stored as `key = value` lines, `edge` and `task` repeat once per trail and scheduled task.
+#[derive(Clone, Debug)]
+pub(crate) struct ColonyCheckpoint {
+    pub thread_id: i32,
+    pub seed: u64,
+    // the next epoch to run
+    pub epoch: i32,
+    // the colony already stopped for good, a cancelled one is resumed
+    pub stopped: Option<StopReason>,
+    // time since the colonies started
+    pub elapsed: Duration,
+    pub best_cycle: i32,
+    pub best_schedule: Vec<ScheduledTask>,
+    pub stagnant_epochs: i32,
+    // max weight of the first epochs, the base chance is drawn from it
+    pub first_max_weight: f64,
+    pub max_weight: f64,
+    pub deposit_rate: f64,
+    pub evaporation_rate: f64,
+    pub base_chance: f64,
+    pub alfa: f64,
+    pub beta: f64,
+    pub adaptation: Option<Adaptation>,
+    // the last epoch as it ran, None before the first one
+    pub last_epoch: Option<LastEpoch>,
+    // position of the colony rng in its stream, the stream itself comes from the seed
+    pub rng_word_pos: u128,
+    // bytes of the telemetry log up to this epoch
+    pub telemetry_len: u64,
+    // the trails as `Pherohormones::edges` gives them
+    pub pheromones_sum: f64,
+    pub edges: Vec<(i32, i32, f64)>,
+}
+
+// What the telemetry record of the last epoch needs besides the rest of the checkpoint, a colony
+// resumed after it stopped reports it as its last epoch. The parameters are the ones the epoch
+// ran with, the adaptation may have changed them for the next one.
+#[derive(Clone, Debug)]
+pub(crate) struct LastEpoch {
+    pub current: i32,
+    pub deposit_rate: f64,
+    pub evaporation_rate: f64,
+    pub base_chance: f64,
+    pub max_weight: f64,
+    pub construction_ms: f64,
+}
+
+impl ColonyCheckpoint {
+    // written next to the file and renamed over it, a machine stopped mid-write keeps the last one
+    pub fn save(&self, path: &str) -> io::Result<()> {
+        let temp = format!("{}.tmp", path);
+        export::write_file(&temp, &self.to_string())?;
+        fs::rename(&temp, path)
+    }
+
+    pub fn load(path: &str) -> io::Result<ColonyCheckpoint> {
+        let contents = fs::read_to_string(path)?;
+        let mut checkpoint = ColonyCheckpoint {
+            thread_id: 0,
+            seed: 0,
+            epoch: 0,
+            stopped: None,
+            elapsed: Duration::ZERO,
+            best_cycle: i32::MAX,
+            best_schedule: Vec::new(),
+            stagnant_epochs: 0,
+            first_max_weight: 0.0,
+            max_weight: 0.0,
+            deposit_rate: 0.0,
+            evaporation_rate: 0.0,
+            base_chance: 0.0,
+            alfa: 0.0,
+            beta: 0.0,
+            adaptation: None,
+            last_epoch: None,
+            rng_word_pos: 0,
+            telemetry_len: 0,
+            pheromones_sum: 0.0,
+            edges: Vec::new(),
+        };
+        // the adaptation needs the deposit rate, which may come after it
+        let mut adaptation_epochs: Option<usize> = None;
+        let mut adaptation_deposit_base = None;
+        let mut adaptation_recent = String::new();
+        let mut adaptation_range = None;
+
+        for (line_number, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid = |message: String| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{}:{}: {}", path, line_number + 1, message),
+                )
+            };
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(invalid(format!("expected key = value, got '{}'", line)));
+            };
+            let (key, value) = (key.trim(), value.trim());
+            let bad_value = || invalid(format!("invalid value '{}' for {}", value, key));
+            let fields: Vec<&str> = value.split_whitespace().collect();
+            match key {
+                "thread_id" => checkpoint.thread_id = parse(value).ok_or_else(bad_value)?,
+                "seed" => checkpoint.seed = parse(value).ok_or_else(bad_value)?,
+                "epoch" => checkpoint.epoch = parse(value).ok_or_else(bad_value)?,
+                "stopped" => {
+                    checkpoint.stopped = match value {
+                        "none" => None,
+                        _ => Some(value.parse().map_err(invalid)?),
+                    }
+                }
+                "elapsed" => {
+                    checkpoint.elapsed = parse(value)
+                        .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+                        .ok_or_else(bad_value)?;
+                }
+                "best_cycle" => checkpoint.best_cycle = parse(value).ok_or_else(bad_value)?,
+                "stagnant_epochs" => {
+                    checkpoint.stagnant_epochs = parse(value).ok_or_else(bad_value)?;
+                }
+                "first_max_weight" => {
+                    checkpoint.first_max_weight = parse(value).ok_or_else(bad_value)?;
+                }
+                "max_weight" => checkpoint.max_weight = parse(value).ok_or_else(bad_value)?,
+                "deposit_rate" => checkpoint.deposit_rate = parse(value).ok_or_else(bad_value)?,
+                "evaporation_rate" => {
+                    checkpoint.evaporation_rate = parse(value).ok_or_else(bad_value)?;
+                }
+                "base_chance" => checkpoint.base_chance = parse(value).ok_or_else(bad_value)?,
+                "alfa" => checkpoint.alfa = parse(value).ok_or_else(bad_value)?,
+                "beta" => checkpoint.beta = parse(value).ok_or_else(bad_value)?,
+                "adaptation_epochs" => {
+                    adaptation_epochs = Some(parse(value).ok_or_else(bad_value)?);
+                }
+                "adaptation_deposit_base" => {
+                    adaptation_deposit_base = Some(parse(value).ok_or_else(bad_value)?);
+                }
+                "adaptation_recent" => {
+                    if value.chars().any(|c| c != '0' && c != '1') {
+                        return Err(bad_value());
+                    }
+                    adaptation_recent = value.to_string();
+                }
+                "adaptation_base_chance_range" => {
+                    adaptation_range = match fields.as_slice() {
+                        ["none"] => None,
+                        [min, max] => Some((
+                            parse(min).ok_or_else(bad_value)?,
+                            parse(max).ok_or_else(bad_value)?,
+                        )),
+                        _ => return Err(bad_value()),
+                    }
+                }
+                "last_epoch" => match fields.as_slice() {
+                    [current, deposit_rate, evaporation_rate, base_chance, max_weight, construction_ms] => {
+                        checkpoint.last_epoch = Some(LastEpoch {
+                            current: parse(current).ok_or_else(bad_value)?,
+                            deposit_rate: parse(deposit_rate).ok_or_else(bad_value)?,
+                            evaporation_rate: parse(evaporation_rate).ok_or_else(bad_value)?,
+                            base_chance: parse(base_chance).ok_or_else(bad_value)?,
+                            max_weight: parse(max_weight).ok_or_else(bad_value)?,
+                            construction_ms: parse(construction_ms).ok_or_else(bad_value)?,
+                        })
+                    }
+                    _ => return Err(bad_value()),
+                },
+                "rng_word_pos" => checkpoint.rng_word_pos = parse(value).ok_or_else(bad_value)?,
+                "telemetry_len" => {
+                    checkpoint.telemetry_len = parse(value).ok_or_else(bad_value)?;
+                }
+                "pheromones_sum" => {
+                    checkpoint.pheromones_sum = parse(value).ok_or_else(bad_value)?;
+                }
+                "edge" => match fields.as_slice() {
+                    [source, target, weight] => checkpoint.edges.push((
+                        parse(source).ok_or_else(bad_value)?,
+                        parse(target).ok_or_else(bad_value)?,
+                        parse(weight).ok_or_else(bad_value)?,
+                    )),
+                    _ => return Err(bad_value()),
+                },
+                "task" => match fields.as_slice() {
+                    [task, worker, start, finish] => checkpoint.best_schedule.push(ScheduledTask {
+                        task: parse(task).ok_or_else(bad_value)?,
+                        worker: parse(worker).ok_or_else(bad_value)?,
+                        start: parse(start).ok_or_else(bad_value)?,
+                        finish: parse(finish).ok_or_else(bad_value)?,
+                    }),
+                    _ => return Err(bad_value()),
+                },
+                _ => return Err(invalid(format!("unknown key '{}'", key))),
+            }
+        }
+
+        if let Some(epochs) = adaptation_epochs {
+            // the deposit rate of the checkpoint is the adapted one, the ranges come from the first
+            let deposit_base = adaptation_deposit_base.unwrap_or(checkpoint.deposit_rate);
+            let mut adaptation = Adaptation::new(deposit_base);
+            adaptation.epochs = epochs;
+            adaptation.recent = adaptation_recent.chars().map(|c| c == '1').collect();
+            adaptation.base_chance_range = adaptation_range;
+            checkpoint.adaptation = Some(adaptation);
+        }
+        Ok(checkpoint)
+    }
+}
+
+fn parse<T: std::str::FromStr>(value: &str) -> Option<T> {
+    value.parse().ok()
+}
+
+// the file format read by `load`, floats are written in full so they read back to the same bits
+impl fmt::Display for ColonyCheckpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "thread_id = {}", self.thread_id)?;
+        writeln!(f, "seed = {}", self.seed)?;
+        writeln!(f, "epoch = {}", self.epoch)?;
+        match self.stopped {
+            Some(reason) => writeln!(f, "stopped = {}", reason)?,
+            None => writeln!(f, "stopped = none")?,
+        }
+        writeln!(f, "elapsed = {}", self.elapsed.as_secs_f64())?;
+        writeln!(f, "best_cycle = {}", self.best_cycle)?;
+        writeln!(f, "stagnant_epochs = {}", self.stagnant_epochs)?;
+        writeln!(f, "first_max_weight = {}", self.first_max_weight)?;
+        writeln!(f, "max_weight = {}", self.max_weight)?;
+        writeln!(f, "deposit_rate = {}", self.deposit_rate)?;
+        writeln!(f, "evaporation_rate = {}", self.evaporation_rate)?;
+        writeln!(f, "base_chance = {}", self.base_chance)?;
+        writeln!(f, "alfa = {}", self.alfa)?;
+        writeln!(f, "beta = {}", self.beta)?;
+        if let Some(adaptation) = &self.adaptation {
+            writeln!(f, "adaptation_epochs = {}", adaptation.epochs)?;
+            writeln!(f, "adaptation_deposit_base = {}", adaptation.deposit_base)?;
+            let recent: String = adaptation
+                .recent
+                .iter()
+                .map(|&improved| if improved { '1' } else { '0' })
+                .collect();
+            writeln!(f, "adaptation_recent = {}", recent)?;
+            match adaptation.base_chance_range {
+                Some((min, max)) => writeln!(f, "adaptation_base_chance_range = {} {}", min, max)?,
+                None => writeln!(f, "adaptation_base_chance_range = none")?,
+            }
+        }
+        if let Some(last) = &self.last_epoch {
+            writeln!(
+                f,
+                "last_epoch = {} {} {} {} {} {}",
+                last.current,
+                last.deposit_rate,
+                last.evaporation_rate,
+                last.base_chance,
+                last.max_weight,
+                last.construction_ms
+            )?;
+        }
+        writeln!(f, "rng_word_pos = {}", self.rng_word_pos)?;
+        writeln!(f, "telemetry_len = {}", self.telemetry_len)?;
+        writeln!(f, "pheromones_sum = {}", self.pheromones_sum)?;
+        for &(source, target, weight) in &self.edges {
+            writeln!(f, "edge = {} {} {}", source, target, weight)?;
+        }
+        for entry in &self.best_schedule {
+            writeln!(
+                f,
+                "task = {} {} {} {}",
+                entry.task, entry.worker, entry.start, entry.finish
+            )?;
+        }
+        Ok(())
+    }
+}
+
+// A run that can be resumed: the config with its seed, where the instance and the results are
+// and the last checkpoint of every colony that wrote one
+pub(crate) struct RunCheckpoint {
+    pub config: SolverConfig,
+    pub file_path: String,
+    pub graph_name: String,
+    pub results_path: String,
+    pub colonies: Vec<ColonyCheckpoint>,
+}
+
+impl RunCheckpoint {
+    pub fn dir(results_path: &str) -> String {
+        format!("{}/checkpoints", results_path.trim_end_matches('/'))
+    }
+
+    pub fn colony_path(dir: &str, thread_id: i32) -> String {
+        format!("{}/colony_{}.txt", dir, thread_id)
+    }
+
+    // what `load` needs besides the colonies, written before they start
+    pub fn start(
+        config: &SolverConfig,
+        file_path: &str,
+        graph_name: &str,
+        results_path: &str,
+    ) -> io::Result<()> {
+        let dir = RunCheckpoint::dir(results_path);
+        // colonies of an earlier run in the same place must not be mistaken for this one's
+        if Path::new(&dir).exists() {
+            fs::remove_dir_all(&dir)?;
+        }
+        config.save(&format!("{}/config.txt", dir))?;
+        export::write_file(
+            &format!("{}/run.txt", dir),
+            &format!(
+                "file_path = {}\ngraph_name = {}\nresults_path = {}\n",
+                file_path, graph_name, results_path
+            ),
+        )
+    }
+
+    pub fn load(dir: &str) -> io::Result<RunCheckpoint> {
+        let dir = dir.trim_end_matches('/');
+        let config = SolverConfig::load(&format!("{}/config.txt", dir))?;
+        let run_path = format!("{}/run.txt", dir);
+        let contents = fs::read_to_string(&run_path)?;
+        let value = |key: &str| {
+            contents
+                .lines()
+                .filter_map(|line| line.split_once('='))
+                .find(|(k, _)| k.trim() == key)
+                .map(|(_, v)| v.trim().to_string())
+                .ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("{}: missing {}", run_path, key),
+                    )
+                })
+        };
+        let mut colonies = Vec::new();
+        for thread_id in 0..config.colonies {
+            let path = RunCheckpoint::colony_path(dir, thread_id);
+            if Path::new(&path).exists() {
+                colonies.push(ColonyCheckpoint::load(&path)?);
+            }
+        }
+        Ok(RunCheckpoint {
+            file_path: value("file_path")?,
+            graph_name: value("graph_name")?,
+            results_path: value("results_path")?,
+            config,
+            colonies,
+        })
+    }
+}
@@ -0,0 +1,399 @@
+// The command line of the binary, kept in the library so main.rs only calls `cli::main`
+
+use std::str::FromStr;
+
+use crate::batch::{BatchOptions, Parallelism};
+use crate::compare::CompareOptions;
+use crate::config::SolverConfig;
+use crate::logging::{self, set_log_format, set_log_level, LogFormat, LogLevel};
+use crate::stopping::CancelToken;
+use crate::tuning::TuningOptions;
+use crate::utils::Utils;
+use crate::{batch, compare, report, solver, tuning, validator};
+
+pub fn main() {
+    let args = logging_options(std::env::args().collect());
+    let cancel = interrupt_token();
+    match args.get(1).map(String::as_str) {
+        Some("validate") => validate_command(&args[2..]),
+        Some("report") => report_command(&args[2..]),
+        Some("batch") => batch_command(&args[2..], &cancel),
+        Some("compare") => compare_command(&args[2..], &cancel),
+        Some("tune") => tune_command(&args[2..], &cancel),
+        Some("resume") => resume_command(&args[2..], &cancel),
+        None | Some("run") => run(&args, &cancel),
+        Some(other) => exit_usage(&format!(
+            "unknown command '{}'\nusage: [run [config file] | validate | report | batch | compare | tune | resume] ...",
+            other
+        )),
+    }
+}
+
+fn exit_usage(usage: &str) -> ! {
+    eprintln!("{}", usage);
+    std::process::exit(2);
+}
+
+// the value after the flag `name`, None without the flag. A missing or unreadable value ends the
+// command with its usage.
+fn option_value<T: FromStr>(args: &[String], name: &str, usage: &str) -> Option<T> {
+    let i = args.iter().position(|a| a == name)?;
+    let value = args.get(i + 1).unwrap_or_else(|| exit_usage(usage));
+    Some(value.parse().unwrap_or_else(|_| exit_usage(usage)))
+}
+
+// --quiet, --verbose, --log-level LEVEL, --log-json and --no-progress go with every command and
+// anywhere on the line, they are taken out before the command reads its own arguments
+fn logging_options(args: Vec<String>) -> Vec<String> {
+    let usage = "logging options: [--quiet | --verbose | --log-level error|warn|info|debug|trace] [--log-json] [--no-progress]";
+    let mut rest = Vec::with_capacity(args.len());
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            // only errors, and no live progress line
+            "--quiet" | "-q" => set_log_level(LogLevel::Error),
+            "--verbose" | "-v" => set_log_level(LogLevel::Debug),
+            "--log-level" => {
+                let level = args.next().and_then(|level| level.parse().ok());
+                set_log_level(level.unwrap_or_else(|| {
+                    eprintln!("{}", usage);
+                    std::process::exit(2);
+                }));
+            }
+            "--log-json" => set_log_format(LogFormat::Json),
+            "--no-progress" => logging::set_progress_line(false),
+            _ => rest.push(arg),
+        }
+    }
+    rest
+}
+
+// The first Ctrl-C stops every colony after its current epoch and lets the run write its
+// results, a second one exits right away
+fn interrupt_token() -> CancelToken {
+    let cancel = CancelToken::new();
+    let handler_token = cancel.clone();
+    let installed = ctrlc::set_handler(move || {
+        if handler_token.is_cancelled() {
+            eprintln!("\nInterrupted again, exiting without saving");
+            std::process::exit(130);
+        }
+        eprintln!("\nInterrupted, stopping the colonies and saving the best so far (Ctrl-C again to exit now)");
+        handler_token.cancel();
+    });
+    if let Err(e) = installed {
+        eprintln!("Ctrl-C handler not installed: {}", e);
+    }
+    cancel
+}
+
+// validate <graph.stg> <schedule.csv> [--proto] [--workers N] [--makespan N]
+// checks an external schedule file, the worker count defaults to the highest worker id in the file
+// (or the one in the graph for prototypes) and the makespan to the last finish
+fn validate_command(args: &[String]) {
+    let usage = "usage: validate <graph.stg> <schedule.csv> [--proto] [--workers N] [--makespan N]";
+    if args.len() < 2 {
+        eprintln!("{}", usage);
+        std::process::exit(2);
+    }
+    let option = |name: &str| option_value::<i32>(args, name, usage);
+
+    let schedule = validator::read_schedule_csv(&args[1]).unwrap_or_else(|e| {
+        eprintln!("Failed to read schedule: {}", e);
+        std::process::exit(2);
+    });
+    let mut n_workers = schedule
+        .iter()
+        .map(|entry| entry.worker + 1)
+        .max()
+        .unwrap_or(1);
+    let mut utils = Utils::new();
+    let graph = if args.iter().any(|a| a == "--proto") {
+        utils.initialize_graph_prototype("", &args[0], &mut n_workers)
+    } else {
+        utils.initialize_graph("", &args[0])
+    };
+    if let Err(e) = graph {
+        eprintln!("Failed to read graph: {}", e);
+        std::process::exit(2);
+    }
+    utils.n_tasks = utils.di_graph.node_count() as i32;
+    let n_workers = option("--workers").unwrap_or(n_workers);
+    let makespan = option("--makespan")
+        .unwrap_or_else(|| schedule.iter().map(|entry| entry.finish).max().unwrap_or(0));
+
+    let violations = validator::validate(&utils, n_workers, &schedule, makespan);
+    if violations.is_empty() {
+        println!(
+            "Schedule is valid: {} tasks on {} workers, makespan {}",
+            schedule.len(),
+            n_workers,
+            makespan
+        );
+    } else {
+        for violation in &violations {
+            println!("{}", violation);
+        }
+        println!("{} violations", violations.len());
+        std::process::exit(1);
+    }
+}
+
+// report <results dir> [--out <dir>]
+// plots and tables from every colony telemetry log under the results directory, written to
+// <results dir>/report unless --out is given
+fn report_command(args: &[String]) {
+    let usage = "usage: report <results dir> [--out <dir>]";
+    let Some(results_dir) = args.first() else {
+        eprintln!("{}", usage);
+        std::process::exit(2);
+    };
+    let out_dir = option_value(args, "--out", usage)
+        .unwrap_or_else(|| format!("{}/report", results_dir.trim_end_matches('/')));
+
+    let report = report::generate(results_dir, &out_dir).unwrap_or_else(|e| {
+        eprintln!("Failed to write report: {}", e);
+        std::process::exit(1);
+    });
+    println!("{} colony logs found", report.colonies.len());
+    if let Some(colony) = report.colonies.first() {
+        println!(
+            "Best colony: {} with makespan {} at epoch {}",
+            colony.label,
+            colony.last().best,
+            colony.best_epoch()
+        );
+    }
+    if let Some((label, schedule)) = &report.best_schedule {
+        println!(
+            "Best schedule: {} with makespan {}",
+            label, schedule.makespan
+        );
+    }
+    for file in &report.files {
+        println!("Wrote {}", file);
+    }
+}
+
+// batch <instances dir> [--glob PATTERN] [--config FILE] [--out DIR] [--optima FILE]
+//       [--parallel instances|colonies]
+// solves every instance of a benchmark directory, resuming from <out>/batch_results.csv
+fn batch_command(args: &[String], cancel: &CancelToken) {
+    let usage = "usage: batch <instances dir> [--glob PATTERN] [--config FILE] [--out DIR] [--optima FILE] [--parallel instances|colonies]";
+    let Some(instances_dir) = args.first() else {
+        eprintln!("{}", usage);
+        std::process::exit(2);
+    };
+    let option = |name: &str| option_value::<String>(args, name, usage);
+    let fail = |message: String| -> ! {
+        eprintln!("{}", message);
+        std::process::exit(2);
+    };
+
+    let config = option("--config")
+        .map(|path| SolverConfig::load(&path).unwrap_or_else(|e| fail(e.to_string())))
+        .unwrap_or_default();
+    let optima = option("--optima")
+        .map(|path| batch::read_optima(&path).unwrap_or_else(|e| fail(e.to_string())))
+        .unwrap_or_default();
+    let parallelism = match option("--parallel").as_deref() {
+        None | Some("colonies") => Parallelism::Colonies,
+        Some("instances") => Parallelism::Instances,
+        Some(other) => fail(format!("unknown parallel mode '{}'\n{}", other, usage)),
+    };
+    let options = BatchOptions {
+        instances_dir: instances_dir.clone(),
+        pattern: option("--glob").unwrap_or_else(|| "*.stg".to_string()),
+        out_dir: option("--out")
+            .unwrap_or_else(|| format!("{}/results", instances_dir.trim_end_matches('/'))),
+        config,
+        optima,
+        parallelism,
+    };
+
+    let summary =
+        batch::run(&options, cancel).unwrap_or_else(|e| fail(format!("Batch failed: {}", e)));
+    println!(
+        "Solved {} instances ({} failed, {} skipped, {} cancelled), results in {}",
+        summary.solved, summary.failed, summary.skipped, summary.cancelled, summary.results_file
+    );
+}
+
+// compare <instances dir> --config FILE [--config FILE ...] [--runs N] [--glob PATTERN]
+//         [--out DIR] [--optima FILE]
+// repeated runs of every config on every instance and the statistics to tell them apart,
+// written to <instances dir>/comparison unless --out is given
+fn compare_command(args: &[String], cancel: &CancelToken) {
+    let usage = "usage: compare <instances dir> --config FILE [--config FILE ...] [--runs N] [--glob PATTERN] [--out DIR] [--optima FILE]";
+    let Some(instances_dir) = args.first() else {
+        eprintln!("{}", usage);
+        std::process::exit(2);
+    };
+    let option = |name: &str| option_value::<String>(args, name, usage);
+    let fail = |message: String| -> ! {
+        eprintln!("{}", message);
+        std::process::exit(2);
+    };
+
+    let configs: Vec<(String, SolverConfig)> = args
+        .iter()
+        .enumerate()
+        .filter(|(_, a)| *a == "--config")
+        .map(|(i, _)| {
+            let path = args.get(i + 1).unwrap_or_else(|| exit_usage(usage));
+            let label = std::path::Path::new(path)
+                .file_stem()
+                .map_or(path.clone(), |s| s.to_string_lossy().to_string());
+            let config = SolverConfig::load(path).unwrap_or_else(|e| fail(e.to_string()));
+            (label, config)
+        })
+        .collect();
+    if configs.is_empty() {
+        fail(usage.to_string());
+    }
+    let options = CompareOptions {
+        instances_dir: instances_dir.clone(),
+        pattern: option("--glob").unwrap_or_else(|| "*.stg".to_string()),
+        out_dir: option("--out")
+            .unwrap_or_else(|| format!("{}/comparison", instances_dir.trim_end_matches('/'))),
+        configs,
+        runs: option_value(args, "--runs", usage).unwrap_or(10),
+        optima: option("--optima")
+            .map(|path| batch::read_optima(&path).unwrap_or_else(|e| fail(e.to_string())))
+            .unwrap_or_default(),
+    };
+
+    let comparison = compare::run(&options, cancel)
+        .unwrap_or_else(|e| fail(format!("Comparison failed: {}", e)));
+    println!("{} runs compared", comparison.records.len());
+    if let Some(friedman) = &comparison.friedman {
+        println!(
+            "Friedman chi2 = {:.3}, p = {:.4}",
+            friedman.statistic, friedman.p_value
+        );
+    }
+    for file in &comparison.files {
+        println!("Wrote {}", file);
+    }
+}
+
+// tune <instances dir> [--glob PATTERN] [--config FILE] [--params FILE] [--budget N]
+//      [--out DIR] [--seed N]
+// races colony parameters over the training instances and writes the elite configs to
+// <instances dir>/tuning unless --out is given, see tuning.rs for the parameter file
+fn tune_command(args: &[String], cancel: &CancelToken) {
+    let usage = "usage: tune <instances dir> [--glob PATTERN] [--config FILE] [--params FILE] [--budget N] [--out DIR] [--seed N]";
+    let Some(instances_dir) = args.first() else {
+        eprintln!("{}", usage);
+        std::process::exit(2);
+    };
+    let option = |name: &str| option_value::<String>(args, name, usage);
+    let fail = |message: String| -> ! {
+        eprintln!("{}", message);
+        std::process::exit(2);
+    };
+
+    let base = option("--config")
+        .map(|path| SolverConfig::load(&path).unwrap_or_else(|e| fail(e.to_string())))
+        .unwrap_or_default();
+    let options = TuningOptions {
+        instances_dir: instances_dir.clone(),
+        pattern: option("--glob").unwrap_or_else(|| "*.stg".to_string()),
+        out_dir: option("--out")
+            .unwrap_or_else(|| format!("{}/tuning", instances_dir.trim_end_matches('/'))),
+        space: option("--params")
+            .map(|path| tuning::read_space(&path).unwrap_or_else(|e| fail(e.to_string())))
+            .unwrap_or_else(tuning::default_space),
+        budget: option_value(args, "--budget", usage).unwrap_or(500),
+        seed: option_value(args, "--seed", usage)
+            .or(base.seed)
+            .unwrap_or_else(rand::random),
+        base,
+    };
+
+    let tuning =
+        tuning::tune(&options, cancel).unwrap_or_else(|e| fail(format!("Tuning failed: {}", e)));
+    println!(
+        "{} runs over {} iterations (seed {})",
+        tuning.evaluations, tuning.iterations, options.seed
+    );
+    if let Some(best) = tuning.elites.first() {
+        println!("Best candidate {}:\n{}", best.id, best.config);
+    }
+    for file in &tuning.files {
+        println!("Wrote {}", file);
+    }
+}
+
+// resume <checkpoint dir>
+// continues a run that had `checkpoint_every` set from <results>/checkpoints, with the config,
+// instance and results directory it was started with
+fn resume_command(args: &[String], cancel: &CancelToken) {
+    let Some(checkpoint_dir) = args.first() else {
+        eprintln!("usage: resume <checkpoint dir>");
+        std::process::exit(2);
+    };
+    let outcome = solver::resume(checkpoint_dir, cancel).unwrap_or_else(|e| {
+        eprintln!("Failed to resume from {}: {}", checkpoint_dir, e);
+        std::process::exit(2);
+    });
+    println!(
+        "Makespan {} (lower bound {}) in {:.1}s",
+        outcome.makespan,
+        outcome.lower_bound,
+        outcome.elapsed.as_secs_f64()
+    );
+}
+
+fn run(args: &[String], cancel: &CancelToken) {
+    /*##### READ FILE ###### */
+    let file_path: String;
+    let graph_name: String;
+    let resuts_path: String;
+
+    /*##### PARAMETERS ###### */
+    // see config.rs for every parameter, `run <config file>` replaces the defaults
+    let config = match args.get(2) {
+        Some(path) => SolverConfig::load(path).unwrap_or_else(|e| {
+            eprintln!("Failed to read config {}: {}", path, e);
+            std::process::exit(2);
+        }),
+        None => SolverConfig {
+            benchmark: 8244,
+            ..SolverConfig::default()
+        },
+    };
+
+    if config.prototype {
+        let number = 100;
+        file_path = "/home/matheus/STG/protostg/".to_owned();
+        resuts_path = format!("/home/matheus/STG/results/protostg/{}/", number).to_string();
+        // graph_name = "atest2.stg";
+        graph_name = format!("proto{}.stg", number).to_string();
+    } else {
+        let number = 3000;
+        file_path = format!("/home/matheus/STG/{}/", number);
+        resuts_path = format!("/home/matheus/STG/results/{}/", number).to_string();
+        // graph_name = "atest2.stg";
+        graph_name = "rand0000.stg".to_owned();
+    }
+
+    /*##### CALL AND MEASURE ###### */
+    let outcome = solver::solve_file(&config, &file_path, &graph_name, &resuts_path, cancel)
+        .unwrap_or_else(|e| {
+            eprintln!("Failed to solve {}{}: {}", file_path, graph_name, e);
+            std::process::exit(2);
+        });
+
+    let elapsed_time = outcome.elapsed;
+    let elapsed_seconds = elapsed_time.as_secs();
+    let elapsed_millis = elapsed_time.as_millis();
+    let elapsed_micros = elapsed_time.as_micros();
+
+    /*##### PRINT RESULTS ###### */
+
+    println!(
+        "Real Time Spent: {}s {}ms {}us",
+        elapsed_seconds, elapsed_millis, elapsed_micros
+    );
+}
@@ -1,10 +1,21 @@
-use rand::Rng;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 
-use crate::manager_ant::{self, ManagerAnt};
-use crate::pherohormones::{self, Pherohormones};
+use crate::adaptive::Adaptation;
+use crate::checkpoint::{ColonyCheckpoint, LastEpoch, RunCheckpoint};
+use crate::logging::{debug, info, warn};
+use crate::manager_ant::ManagerAnt;
+use crate::pherohormones::{Pherohormones, TrailFormat};
+use crate::schedule::Schedule;
+use crate::scheduler::{RunState, Scheduler};
+use crate::snapshot::{SnapshotPolicy, SnapshotWriter};
+use crate::stopping::StopReason;
+use crate::telemetry::EpochRecord;
 use crate::utils::Utils;
-use crate::worker_ant::WorkerAnt;
+use crate::validator;
+use std::io;
 use std::sync::{Arc, Mutex};
+use std::time::Instant;
 
 pub struct Colony {
     pub utils: Utils,
@@ -13,15 +24,36 @@ pub struct Colony {
     pub evaporation_rate: f64,
     pub n_ants: i32,
     pub file_path: String,
-    pub graph_name: String,
     pub thread_id: i32,
     pub base_chance: f64,
+    // base chance as a multiple of the max weight of the first epochs, drawn when None
+    pub base_chance_factor: Option<f64>,
     pub alfa: f64,
     pub beta: f64,
     pub max_weight: f64,
+    // every random choice of the colony comes from here so a seed reproduces the run
+    pub seed: u64,
+    pub rng: ChaCha8Rng,
+    pub snapshot_policy: SnapshotPolicy,
+    // evaporation rate and base chance follow the improvement rate instead of staying fixed
+    pub adaptive: bool,
+    // where `checkpoint` writes the colony state
+    checkpoint_path: String,
+    // format of the trails written at the end, None writes none
+    pub trail_format: Option<TrailFormat>,
+    pub lower_bound: i32,
+    // trails of an earlier run to start from
+    pub warm_start: Option<Pherohormones>,
+    // the state to continue from instead of starting with empty trails
+    pub resume_from: Option<ColonyCheckpoint>,
+    progress: Progress,
+    // the last epoch as it ran
+    record: EpochRecord,
+    snapshots: Option<SnapshotWriter>,
 }
 
 impl Colony {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         utils: &Utils,
         n_ants: i32,
@@ -32,9 +64,14 @@ impl Colony {
         graph_name: &str,
         thread_id: i32,
         base_chance: f64,
+        base_chance_factor: Option<f64>,
         alfa: f64,
         beta: f64,
         max_weight: f64,
+        seed: u64,
+        snapshot_policy: SnapshotPolicy,
+        adaptive: bool,
+        trail_format: Option<TrailFormat>,
     ) -> Colony {
         let pherohormones = Arc::new(Mutex::new(Pherohormones::new(
             utils.n_tasks,
@@ -50,129 +87,361 @@ impl Colony {
             deposit_rate,
             evaporation_rate,
             file_path: file_path.to_string(),
-            graph_name: graph_name.to_string(),
             thread_id,
             base_chance,
+            base_chance_factor,
             alfa,
             beta,
             max_weight,
+            seed,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+            snapshot_policy,
+            adaptive,
+            checkpoint_path: RunCheckpoint::colony_path(
+                &RunCheckpoint::dir(pherohormones_output_dir),
+                thread_id,
+            ),
+            trail_format,
+            lower_bound: 0,
+            warm_start: None,
+            resume_from: None,
+            progress: Progress {
+                best_cycle: i32::MAX,
+                best_schedule: Schedule::default(),
+                //use to get the true base chance after the first cilce where the magic number comes in
+                first_max_weight: 0.0,
+                adaptation: None,
+            },
+            record: EpochRecord::default(),
+            snapshots: None,
+        }
+    }
+
+    // Restores the trails, parameters and rng of a checkpoint, the rest goes back to the loop
+    fn restore(&mut self, checkpoint: ColonyCheckpoint) -> RunState {
+        let output_dir = self.pherohormones.lock().unwrap().output_dir.clone();
+        *self.pherohormones.lock().unwrap() = Pherohormones::from_edges(
+            self.utils.n_tasks,
+            &output_dir,
+            &checkpoint.edges,
+            checkpoint.pheromones_sum,
+        );
+        self.deposit_rate = checkpoint.deposit_rate;
+        self.evaporation_rate = checkpoint.evaporation_rate;
+        self.base_chance = checkpoint.base_chance;
+        self.alfa = checkpoint.alfa;
+        self.beta = checkpoint.beta;
+        self.max_weight = checkpoint.max_weight;
+        self.rng = ChaCha8Rng::seed_from_u64(self.seed);
+        self.rng.set_word_pos(checkpoint.rng_word_pos);
+        // a colony that stopped before the interruption runs no epoch, its last record is the
+        // one it stopped with. Checkpoints without it fall back to the restored parameters.
+        let last = checkpoint.last_epoch.unwrap_or(LastEpoch {
+            current: checkpoint.best_cycle,
+            deposit_rate: checkpoint.deposit_rate,
+            evaporation_rate: checkpoint.evaporation_rate,
+            base_chance: checkpoint.base_chance,
+            max_weight: checkpoint.max_weight,
+            construction_ms: 0.0,
+        });
+        self.record = EpochRecord {
+            colony_id: self.thread_id,
+            epoch: checkpoint.epoch - 1,
+            current: last.current,
+            best: checkpoint.best_cycle,
+            lower_bound: self.lower_bound,
+            alfa: checkpoint.alfa,
+            beta: checkpoint.beta,
+            base_chance: last.base_chance,
+            deposit_rate: last.deposit_rate,
+            evaporation_rate: last.evaporation_rate,
+            max_weight: last.max_weight,
+            pheromones: self.pherohormones.lock().unwrap().stats(),
+            construction_ms: last.construction_ms,
+        };
+        self.progress = Progress {
+            best_cycle: checkpoint.best_cycle,
+            best_schedule: if checkpoint.best_schedule.is_empty() {
+                Schedule::default()
+            } else {
+                Schedule::new(self.utils.n_tasks, self.n_ants, &checkpoint.best_schedule)
+            },
+            first_max_weight: checkpoint.first_max_weight,
+            adaptation: checkpoint.adaptation,
+        };
+        RunState {
+            step: checkpoint.epoch,
+            stagnant_steps: checkpoint.stagnant_epochs,
+            elapsed: checkpoint.elapsed,
+            stopped: checkpoint.stopped,
+            telemetry_len: checkpoint.telemetry_len,
         }
     }
+}
 
-    pub fn ACO(&mut self, epochs: i32, benchmark: i32) -> (i32, Vec<WorkerAnt>) {
-        let file_name = format!("thread_{}_{}.csv", self.thread_id, self.graph_name);
-
-        Utils::delete_file(&self.file_path, &file_name);
-        let mut best_cycle = i32::MAX;
-        let mut best_work_history: Vec<WorkerAnt> = vec![];
-        //use to get the true base chance after the first cilce where the magic number comes in
-        let mut max_weight = 0.0;
-
-        // Initialize pheromones once with lock
-        self.pherohormones.lock().unwrap().initialize();
-
-        for epoch in 0..epochs {
-            if epoch == 2 {
-                //after the very firtst epoch the value of the max_wight will start to increadse in n* log(n) rate , so to make thnings fair
-                //  and favor the ants exploration the  base chance will be proportional to this number
-
-                let mut rng = rand::rng();
-                let min_value = max_weight / 100.0;
-                let max_value = 2.0 * max_weight;
-                self.base_chance = rng.random_range(min_value..max_value);
-                println!(
-                    "[Thread {}] Base chance updated to: {}",
-                    self.thread_id, self.base_chance
+impl Scheduler for Colony {
+    fn name(&self) -> &str {
+        "aco"
+    }
+
+    fn initialise(&mut self) -> RunState {
+        let state = match self.resume_from.take() {
+            Some(checkpoint) => {
+                let state = self.restore(checkpoint);
+                info!(
+                    colony: self.thread_id,
+                    "Resumed at epoch {}, Best = {}",
+                    state.step,
+                    self.progress.best_cycle
                 );
+                state
+            }
+            None => {
+                // Initialize pheromones once with lock
+                let mut ph = self.pherohormones.lock().unwrap();
+                ph.initialize();
+                if let Some(trails) = &self.warm_start {
+                    let kept = ph.warm_start(trails);
+                    info!(
+                        colony: self.thread_id,
+                        "Warm start with {} of {} trails",
+                        kept,
+                        trails.di_graph.edge_count()
+                    );
+                }
+                drop(ph);
+                self.progress.adaptation =
+                    self.adaptive.then(|| Adaptation::new(self.deposit_rate));
+                RunState::default()
             }
-            let mut rng = rand::rng();
-            //let alfa = rng.random_range(0.0..2.0);
-            self.alfa = 0.0;
-            // self.beta = rng.random_range(0.1..3.0);
-            self.beta = 1.0;
-            let mut manager = ManagerAnt::new(
-                &self.utils,
-                self.n_ants,
-                Arc::clone(&self.pherohormones),
-                self.evaporation_rate,
-                self.deposit_rate,
-                self.utils.n_tasks,
-                self.alfa,
-                self.beta,
-                self.base_chance,
+        };
+        // trails are copied to a background writer as the snapshot policy asks
+        let output_dir = self.pherohormones.lock().unwrap().output_dir.clone();
+        self.snapshots = Some(SnapshotWriter::new(self.snapshot_policy, &output_dir));
+        state
+    }
+
+    fn step(&mut self, epoch: i32) -> i32 {
+        let progress = &mut self.progress;
+        if epoch == 2 {
+            //after the very firtst epoch the value of the max_wight will start to increadse in n* log(n) rate , so to make thnings fair
+            //  and favor the ants exploration the  base chance will be proportional to this number
+
+            let max_weight = progress.first_max_weight;
+            self.base_chance = match self.base_chance_factor {
+                Some(factor) => factor * max_weight,
+                None => {
+                    let min_value = max_weight / 100.0;
+                    let max_value = 2.0 * max_weight;
+                    self.rng.random_range(min_value..max_value)
+                }
+            };
+            debug!(
+                colony: self.thread_id,
+                "Base chance updated to: {}",
+                self.base_chance
             );
+            if let Some(adaptation) = &mut progress.adaptation {
+                adaptation.set_max_weight(max_weight);
+            }
+        }
+        let mut manager = ManagerAnt::new(
+            &self.utils,
+            self.n_ants,
+            Arc::clone(&self.pherohormones),
+            self.deposit_rate,
+            self.alfa,
+            self.beta,
+            self.base_chance,
+        );
 
-            let cycles = manager.work(epoch);
-            // Check if the cycle is valid o update the main pherohormones (deposit and evaporate)
-            // Only update main pheromones if solution improves or stay the same
-            if cycles <= best_cycle {
-                // Get lock on main pheromones
-                let mut main_ph = self.pherohormones.lock().unwrap();
-
-                // Replace main pheromones with successful local version
-                *main_ph = manager.local_pherohormones.clone();
-
-                // Apply evaporation AFTER merging
-                main_ph.evaporate_pherohormones(self.evaporation_rate);
-
-                // Save state with lock
-                main_ph.save_gephi(epoch).expect("Failed to save frame");
-
-                // Print and save results
-                // println!(
-                //     "[Thread {}] Epoch {}: Cycles: {}, Max weight: {}",
-                //     self.thread_id, epoch, cycles, manager.max_weight
-                // );
-
-                // Update best results
-                best_cycle = cycles;
-                best_work_history = manager.ants.clone();
-                // Save pheromones to CSV
-                let _ = Utils::append_to_csv(
-                    epoch,
-                    manager.max_weight,
-                    cycles,
-                    &self.file_path,
-                    &file_name,
-                );
+        let construction = Instant::now();
+        let cycles = manager.work(epoch, &mut self.rng);
+        let construction_ms = construction.elapsed().as_secs_f64() * 1000.0;
+        // Check if the cycle is valid o update the main pherohormones (deposit and evaporate)
+        // Only update main pheromones if solution improves or stay the same
+        let improved = cycles <= progress.best_cycle;
+        let strictly_improved = cycles < progress.best_cycle;
+        if improved {
+            // Get lock on main pheromones
+            let mut main_ph = self.pherohormones.lock().unwrap();
 
-                //  write the respective graphs for hte best
-                main_ph.to_gexf(cycles);
-                let _ = main_ph.save_gephi(cycles);
-                self.max_weight = manager.max_weight;
+            // Replace main pheromones with successful local version
+            *main_ph = manager.local_pherohormones.clone();
 
-                // Save the pherohormones state to view with gephi
-                main_ph.save_gephi(epoch).expect("Failed to save frame");
+            // Apply evaporation AFTER merging
+            main_ph.evaporate_pherohormones(self.evaporation_rate);
 
-                // Some debuggin
-                println!(
-                    "New best found [Thread {}] Epoch {}: Cycles: {}, Max weight: {}",
-                    self.thread_id, epoch, cycles, manager.max_weight
-                );
-            }
-            // Initialize max weight after first epoch
-            if epoch == 1 {
-                max_weight = manager.max_weight;
-                println!(" [Thread {}] max Weight {}", self.thread_id, max_weight);
-            }
+            // Print and save results
+            // println!(
+            //     "[Thread {}] Epoch {}: Cycles: {}, Max weight: {}",
+            //     self.thread_id, epoch, cycles, manager.max_weight
+            // );
 
-            // Periodic logging
-            if epoch % 100 == 0 {
-                println!(
-                    "[Thread {}] Progress - Epoch {}/{}: Best = {}, Current = {}",
-                    self.thread_id, epoch, epochs, best_cycle, cycles
+            // every new best is checked independently in debug builds
+            let schedule = manager.schedule();
+            if cfg!(debug_assertions) {
+                let violations =
+                    validator::validate(&self.utils, self.n_ants, &schedule.entries(), cycles);
+                assert!(
+                    violations.is_empty(),
+                    "[Thread {}] Epoch {}: invalid schedule: {:?}",
+                    self.thread_id,
+                    epoch,
+                    violations.iter().map(|v| v.to_string()).collect::<Vec<_>>()
                 );
             }
-            // Early stopping condition
-            if cycles <= benchmark {
-                println!(
-                    "[Thread {}] Benchmark achieved at epoch {}! Cycles: {} (Target: {})",
-                    self.thread_id, epoch, cycles, benchmark
+
+            // Update best results
+            progress.best_cycle = cycles;
+            progress.best_schedule = schedule;
+            self.max_weight = manager.max_weight;
+
+            // Some debuggin
+            info!(
+                colony: self.thread_id,
+                "New best found at epoch {}: Cycles: {}, Max weight: {}",
+                epoch,
+                cycles,
+                manager.max_weight
+            );
+        }
+        let main_ph = self.pherohormones.lock().unwrap();
+        let record = EpochRecord {
+            colony_id: self.thread_id,
+            epoch,
+            current: cycles,
+            best: progress.best_cycle,
+            lower_bound: self.lower_bound,
+            alfa: self.alfa,
+            beta: self.beta,
+            base_chance: self.base_chance,
+            deposit_rate: self.deposit_rate,
+            evaporation_rate: self.evaporation_rate,
+            max_weight: manager.max_weight,
+            pheromones: main_ph.stats(),
+            construction_ms,
+        };
+        if let Some(snapshots) = &mut self.snapshots {
+            snapshots.submit(
+                &main_ph,
+                epoch,
+                cycles,
+                improved,
+                format!(
+                    "Thread {} epoch {} cycles {}",
+                    self.thread_id, epoch, cycles
+                ),
+            );
+        }
+        drop(main_ph);
+        self.record = record;
+        // the next epoch runs with the adapted parameters, this one is logged as it ran
+        if let Some(adaptation) = &mut progress.adaptation {
+            if let Some(adjustment) = adaptation.update(
+                strictly_improved,
+                &mut self.deposit_rate,
+                &mut self.evaporation_rate,
+                &mut self.base_chance,
+            ) {
+                debug!(
+                    colony: self.thread_id,
+                    "Epoch {}: {:?} at improvement rate {:.2}, deposit rate {:.6} evaporation rate {:.6} base chance {:.6}",
+                    epoch,
+                    adjustment,
+                    adaptation.improvement_rate(),
+                    self.deposit_rate,
+                    self.evaporation_rate,
+                    self.base_chance
                 );
-                break;
             }
         }
+        // Initialize max weight after first epoch
+        if epoch == 1 {
+            progress.first_max_weight = manager.max_weight;
+            debug!(
+                colony: self.thread_id,
+                "max Weight {}",
+                progress.first_max_weight
+            );
+        }
+        cycles
+    }
+
+    fn best(&self) -> Option<&Schedule> {
+        (self.progress.best_cycle != i32::MAX).then_some(&self.progress.best_schedule)
+    }
 
-        (best_cycle, best_work_history)
+    fn telemetry(&self) -> EpochRecord {
+        self.record.clone()
     }
+
+    fn checkpoint(&mut self, state: &RunState) -> io::Result<()> {
+        let progress = &self.progress;
+        let ph = self.pherohormones.lock().unwrap();
+        let checkpoint = ColonyCheckpoint {
+            thread_id: self.thread_id,
+            seed: self.seed,
+            epoch: state.step,
+            stopped: state.stopped,
+            elapsed: state.elapsed,
+            best_cycle: progress.best_cycle,
+            best_schedule: progress.best_schedule.entries(),
+            stagnant_epochs: state.stagnant_steps,
+            first_max_weight: progress.first_max_weight,
+            max_weight: self.max_weight,
+            deposit_rate: self.deposit_rate,
+            evaporation_rate: self.evaporation_rate,
+            base_chance: self.base_chance,
+            alfa: self.alfa,
+            beta: self.beta,
+            adaptation: progress.adaptation.clone(),
+            last_epoch: (state.step > 0).then_some(LastEpoch {
+                current: self.record.current,
+                deposit_rate: self.record.deposit_rate,
+                evaporation_rate: self.record.evaporation_rate,
+                base_chance: self.record.base_chance,
+                max_weight: self.record.max_weight,
+                construction_ms: self.record.construction_ms,
+            }),
+            rng_word_pos: self.rng.get_word_pos(),
+            telemetry_len: state.telemetry_len,
+            pheromones_sum: ph.pheromones_sum,
+            edges: ph.edges(),
+        };
+        checkpoint.save(&self.checkpoint_path)
+    }
+
+    fn finish(&mut self, _reason: StopReason) -> io::Result<()> {
+        let dropped = match self.snapshots.take() {
+            Some(mut snapshots) => {
+                snapshots.finish()?;
+                snapshots.dropped
+            }
+            None => 0,
+        };
+        if let Some(format) = self.trail_format {
+            self.pherohormones.lock().unwrap().save_trails(
+                &format!("{}/trails.{}", self.file_path, format.extension()),
+                format,
+            )?;
+        }
+        if dropped > 0 {
+            warn!(
+                colony: self.thread_id,
+                "{} snapshots skipped, the writer could not keep up",
+                dropped
+            );
+        }
+        Ok(())
+    }
+}
+
+// What the epoch loop carries from one epoch to the next
+struct Progress {
+    best_cycle: i32,
+    best_schedule: Schedule,
+    // max weight of the first epochs, the base chance is drawn from it
+    first_max_weight: f64,
+    adaptation: Option<Adaptation>,
 }
@@ -0,0 +1,483 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::time::Instant;
+
+use crate::batch;
+use crate::config::SolverConfig;
+use crate::export::{self, create_parent};
+use crate::logging::{error, info};
+use crate::report;
+use crate::solver;
+use crate::stats::{self, Friedman};
+use crate::stopping::CancelToken;
+
+pub(crate) struct CompareOptions {
+    pub instances_dir: String,
+    // file name pattern, '*' and '?' wildcards
+    pub pattern: String,
+    pub out_dir: String,
+    // every candidate with its label, the config file name
+    pub configs: Vec<(String, SolverConfig)>,
+    // independent runs per configuration and instance, seeded 1..=runs so every
+    // configuration sees the same seeds
+    pub runs: u64,
+    // known optimal makespans by instance name
+    pub optima: HashMap<String, i32>,
+}
+
+// One run of one configuration on one instance
+#[derive(Clone, Debug)]
+pub(crate) struct RunRecord {
+    pub config: String,
+    pub instance: String,
+    pub seed: u64,
+    pub makespan: i32,
+    pub lower_bound: i32,
+    // mean of the best makespans of the colonies of the run
+    pub colony_mean: f64,
+    pub runtime: f64,
+}
+
+pub(crate) struct Comparison {
+    pub records: Vec<RunRecord>,
+    // None with fewer than two configurations or no instance solved by all of them
+    pub friedman: Option<Friedman>,
+    pub files: Vec<String>,
+}
+
+const HEADER: &str = "config,instance,seed,makespan,lower_bound,colony_mean,runtime_seconds,status";
+
+// Runs every configuration on every instance with every seed, appending to <out>/runs.csv
+// (runs already in the file are not repeated), then writes the statistics of all of them.
+// A cancelled comparison keeps the runs it finished and analyses them.
+pub(crate) fn run(options: &CompareOptions, cancel: &CancelToken) -> io::Result<Comparison> {
+    let instances = batch::list_instances(&options.instances_dir, &options.pattern)?;
+    let runs_file = format!("{}/runs.csv", options.out_dir);
+    let mut records = read_runs(&runs_file)?;
+    let done: HashSet<(String, String, u64)> = records
+        .iter()
+        .map(|r| (r.config.clone(), r.instance.clone(), r.seed))
+        .collect();
+    let total = instances.len() * options.configs.len() * options.runs as usize;
+    info!(
+        "{} runs ({} instances, {} configs, {} seeds), {} already in {}",
+        total,
+        instances.len(),
+        options.configs.len(),
+        options.runs,
+        done.len(),
+        runs_file
+    );
+
+    create_parent(&runs_file)?;
+    let is_new = fs::metadata(&runs_file).map_or(true, |m| m.len() == 0);
+    let mut out = BufWriter::new(
+        OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(&runs_file)?,
+    );
+    if is_new {
+        writeln!(out, "{}", HEADER)?;
+        out.flush()?;
+    }
+
+    let instances_dir = format!("{}/", options.instances_dir.trim_end_matches('/'));
+    for instance in &instances {
+        let stem = stem(instance);
+        for (label, config) in &options.configs {
+            for seed in 1..=options.runs {
+                if cancel.is_cancelled() {
+                    break;
+                }
+                if done.contains(&(label.clone(), instance.clone(), seed)) {
+                    continue;
+                }
+                let config = SolverConfig {
+                    seed: Some(seed),
+                    ..config.clone()
+                };
+                let results_path =
+                    format!("{}/runs/{}/{}/seed_{}/", options.out_dir, label, stem, seed);
+                let start = Instant::now();
+                let outcome = panic::catch_unwind(AssertUnwindSafe(|| {
+                    solver::solve_file(&config, &instances_dir, instance, &results_path, cancel)
+                }));
+                let runtime = start.elapsed().as_secs_f64();
+
+                let outcome = match outcome {
+                    Ok(Ok(outcome)) => outcome,
+                    failure => {
+                        let reason = match failure {
+                            Ok(Err(e)) => e.to_string(),
+                            _ => "the solver panicked".to_string(),
+                        };
+                        error!(
+                            "{} on {} with seed {} failed: {}",
+                            label, instance, seed, reason
+                        );
+                        writeln!(
+                            out,
+                            "{},{},{},,,,{:.3},failed",
+                            label, instance, seed, runtime
+                        )?;
+                        out.flush()?;
+                        continue;
+                    }
+                };
+                // only the best so far, not a run of the configuration
+                if outcome.cancelled {
+                    break;
+                }
+                let colony_mean = stats::mean(
+                    &outcome
+                        .colonies
+                        .iter()
+                        .map(|c| c.best_cycle as f64)
+                        .collect::<Vec<_>>(),
+                );
+                let record = RunRecord {
+                    config: label.clone(),
+                    instance: instance.clone(),
+                    seed,
+                    makespan: outcome.makespan,
+                    lower_bound: outcome.lower_bound,
+                    colony_mean,
+                    runtime,
+                };
+                info!(
+                    "{} on {} with seed {}: makespan {}",
+                    label, instance, seed, record.makespan
+                );
+                writeln!(
+                    out,
+                    "{},{},{},{},{},{:.3},{:.3},ok",
+                    record.config,
+                    record.instance,
+                    record.seed,
+                    record.makespan,
+                    record.lower_bound,
+                    record.colony_mean,
+                    record.runtime
+                )?;
+                out.flush()?;
+                records.push(record);
+            }
+        }
+    }
+
+    // rows of configurations, instances or seeds outside this comparison stay in the file
+    let labels: Vec<&str> = options.configs.iter().map(|(l, _)| l.as_str()).collect();
+    records.retain(|r| {
+        labels.contains(&r.config.as_str())
+            && instances.contains(&r.instance)
+            && r.seed <= options.runs
+    });
+    let (friedman, files) = analyse(&records, &labels, &instances, options)?;
+    Ok(Comparison {
+        records,
+        friedman,
+        files,
+    })
+}
+
+// ok rows of a previous runs file
+fn read_runs(path: &str) -> io::Result<Vec<RunRecord>> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let invalid = |line_number: usize| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}:{}: malformed run record", path, line_number + 1),
+        )
+    };
+    let mut records = Vec::new();
+    for (line_number, line) in contents.lines().enumerate().skip(1) {
+        let fields: Vec<&str> = line.trim().split(',').collect();
+        if fields.last() != Some(&"ok") {
+            continue;
+        }
+        if fields.len() != HEADER.split(',').count() {
+            return Err(invalid(line_number));
+        }
+        records.push(RunRecord {
+            config: fields[0].to_string(),
+            instance: fields[1].to_string(),
+            seed: fields[2].parse().map_err(|_| invalid(line_number))?,
+            makespan: fields[3].parse().map_err(|_| invalid(line_number))?,
+            lower_bound: fields[4].parse().map_err(|_| invalid(line_number))?,
+            colony_mean: fields[5].parse().map_err(|_| invalid(line_number))?,
+            runtime: fields[6].parse().map_err(|_| invalid(line_number))?,
+        });
+    }
+    Ok(records)
+}
+
+fn stem(name: &str) -> String {
+    Path::new(name)
+        .file_stem()
+        .map_or(name.to_string(), |s| s.to_string_lossy().to_string())
+}
+
+// the runs of one configuration on one instance
+struct Cell {
+    makespans: Vec<f64>,
+    colony_means: Vec<f64>,
+    runtimes: Vec<f64>,
+    lower_bound: i32,
+}
+
+impl Cell {
+    fn mean(&self) -> f64 {
+        stats::mean(&self.makespans)
+    }
+
+    fn best(&self) -> f64 {
+        self.makespans.iter().copied().fold(f64::INFINITY, f64::min)
+    }
+
+    fn worst(&self) -> f64 {
+        self.makespans
+            .iter()
+            .copied()
+            .fold(f64::NEG_INFINITY, f64::max)
+    }
+}
+
+fn analyse(
+    records: &[RunRecord],
+    labels: &[&str],
+    instances: &[String],
+    options: &CompareOptions,
+) -> io::Result<(Option<Friedman>, Vec<String>)> {
+    let mut cells: HashMap<(&str, &str), Cell> = HashMap::new();
+    for record in records {
+        let cell = cells
+            .entry((record.config.as_str(), record.instance.as_str()))
+            .or_insert(Cell {
+                makespans: Vec::new(),
+                colony_means: Vec::new(),
+                runtimes: Vec::new(),
+                lower_bound: record.lower_bound,
+            });
+        cell.makespans.push(record.makespan as f64);
+        cell.colony_means.push(record.colony_mean);
+        cell.runtimes.push(record.runtime);
+    }
+    // the tests pair configurations by instance, so only instances every configuration ran on count
+    let complete: Vec<&str> = instances
+        .iter()
+        .map(String::as_str)
+        .filter(|instance| labels.iter().all(|l| cells.contains_key(&(*l, *instance))))
+        .collect();
+    // mean makespan, blocks[instance][config]
+    let blocks: Vec<Vec<f64>> = complete
+        .iter()
+        .map(|instance| {
+            labels
+                .iter()
+                .map(|l| cells[&(*l, *instance)].mean())
+                .collect()
+        })
+        .collect();
+    // the gap is measured against the optimum when known, otherwise the lower bound
+    let reference = |instance: &str, cell: &Cell| {
+        batch::optimum_of(&options.optima, instance).unwrap_or(cell.lower_bound)
+    };
+
+    let mut files = Vec::new();
+    let mut save = |name: &str, contents: &str| -> io::Result<()> {
+        let path = format!("{}/{}", options.out_dir, name);
+        export::write_file(&path, contents)?;
+        files.push(path);
+        Ok(())
+    };
+
+    let mut csv = String::from("config,instance,runs,mean,median,std,best,worst,ci95_low,ci95_high,colony_mean,mean_runtime_seconds,reference,mean_gap\n");
+    for instance in instances {
+        for label in labels {
+            let Some(cell) = cells.get(&(*label, instance.as_str())) else {
+                continue;
+            };
+            let (low, high) = stats::confidence_interval(&cell.makespans);
+            let reference = reference(instance, cell);
+            let _ = writeln!(
+                csv,
+                "{},{},{},{:.3},{:.1},{:.3},{},{},{:.3},{:.3},{:.3},{:.3},{},{:.6}",
+                label,
+                instance,
+                cell.makespans.len(),
+                cell.mean(),
+                stats::median(&cell.makespans),
+                stats::std_dev(&cell.makespans),
+                cell.best(),
+                cell.worst(),
+                low,
+                high,
+                stats::mean(&cell.colony_means),
+                stats::mean(&cell.runtimes),
+                reference,
+                gap(cell.mean(), reference)
+            );
+        }
+    }
+    save("stats.csv", &csv)?;
+
+    let friedman = (labels.len() >= 2 && !blocks.is_empty()).then(|| stats::friedman(&blocks));
+    let profiles = stats::performance_profile(&blocks);
+    let mut profile_csv = String::from("config,tau,fraction\n");
+    for (label, points) in labels.iter().zip(&profiles) {
+        for (tau, fraction) in points {
+            let _ = writeln!(profile_csv, "{},{:.6},{:.6}", label, tau, fraction);
+        }
+    }
+    save("performance_profile.csv", &profile_csv)?;
+    save("performance_profile.svg", &profile_svg(labels, &profiles))?;
+
+    let mut md = String::from("# Configuration comparison\n\n");
+    let _ = writeln!(
+        md,
+        "{} configurations, {} instances ({} run by every configuration), {} seeds each.\n",
+        labels.len(),
+        instances.len(),
+        complete.len(),
+        options.runs
+    );
+    md.push_str("## Overall\n\n");
+    md.push_str("Mean over the instances run by every configuration, gaps are to the optimum when known and to the lower bound otherwise.\n\n");
+    md.push_str("| Config | Mean rank | Mean gap % | Best mean on |\n|---|---:|---:|---:|\n");
+    for (c, label) in labels.iter().enumerate() {
+        let gaps: Vec<f64> = complete
+            .iter()
+            .map(|instance| {
+                let cell = &cells[&(*label, *instance)];
+                gap(cell.mean(), reference(instance, cell))
+            })
+            .collect();
+        let wins = blocks
+            .iter()
+            .filter(|block| block[c] <= block.iter().copied().fold(f64::INFINITY, f64::min))
+            .count();
+        let _ = writeln!(
+            md,
+            "| {} | {} | {:.2} | {} |",
+            label,
+            friedman
+                .as_ref()
+                .map_or("-".to_string(), |f| format!("{:.2}", f.mean_ranks[c])),
+            stats::mean(&gaps) * 100.0,
+            wins
+        );
+    }
+
+    md.push_str("\n## Tests\n\n");
+    match &friedman {
+        Some(f) => {
+            let _ = writeln!(
+                md,
+                "Friedman over {} instances: chi2 = {:.3}, df = {}, p = {:.4}\n",
+                blocks.len(),
+                f.statistic,
+                f.df,
+                f.p_value
+            );
+        }
+        None => md.push_str(
+            "Friedman test needs two configurations and one instance run by all of them.\n\n",
+        ),
+    }
+    if labels.len() >= 2 {
+        md.push_str("Wilcoxon signed-rank on the mean makespan per instance, two sided, normal approximation (rough below 10 instances).\n\n");
+        md.push_str("| A | B | Pairs | W+ (A worse) | W- (A better) | z | p |\n|---|---|---:|---:|---:|---:|---:|\n");
+        for a in 0..labels.len() {
+            for b in a + 1..labels.len() {
+                let x: Vec<f64> = blocks.iter().map(|block| block[a]).collect();
+                let y: Vec<f64> = blocks.iter().map(|block| block[b]).collect();
+                let w = stats::wilcoxon_signed_rank(&x, &y);
+                let _ = writeln!(
+                    md,
+                    "| {} | {} | {} | {:.1} | {:.1} | {:.3} | {:.4} |",
+                    labels[a], labels[b], w.n, w.w_plus, w.w_minus, w.z, w.p_value
+                );
+            }
+        }
+    }
+
+    md.push_str("\n## Per instance\n\n");
+    md.push_str(
+        "| Instance | Config | Runs | Mean | Std | Median | Best | 95% CI | Reference | Gap % |\n",
+    );
+    md.push_str("|---|---|---:|---:|---:|---:|---:|---|---:|---:|\n");
+    for instance in instances {
+        for label in labels {
+            let Some(cell) = cells.get(&(*label, instance.as_str())) else {
+                continue;
+            };
+            let (low, high) = stats::confidence_interval(&cell.makespans);
+            let reference = reference(instance, cell);
+            let _ = writeln!(
+                md,
+                "| {} | {} | {} | {:.2} | {:.2} | {:.1} | {} | [{:.2}, {:.2}] | {} | {:.2} |",
+                instance,
+                label,
+                cell.makespans.len(),
+                cell.mean(),
+                stats::std_dev(&cell.makespans),
+                stats::median(&cell.makespans),
+                cell.best(),
+                low,
+                high,
+                reference,
+                gap(cell.mean(), reference) * 100.0
+            );
+        }
+    }
+    md.push_str("\n![performance profile](performance_profile.svg)\n");
+    save("comparison.md", &md)?;
+
+    Ok((friedman, files))
+}
+
+fn gap(makespan: f64, reference: i32) -> f64 {
+    if reference <= 0 {
+        return 0.0;
+    }
+    (makespan - reference as f64) / reference as f64
+}
+
+// fraction of instances within a factor tau of the best configuration, as steps
+fn profile_svg(labels: &[&str], profiles: &[Vec<(f64, f64)>]) -> String {
+    let tau_max = profiles
+        .iter()
+        .flatten()
+        .map(|&(tau, _)| tau)
+        .fold(1.0, f64::max);
+    let series: Vec<(String, Vec<(f64, f64)>)> = labels
+        .iter()
+        .zip(profiles)
+        .map(|(label, points)| {
+            let mut steps = vec![(1.0, 0.0)];
+            for &(tau, fraction) in points {
+                let previous = steps.last().map_or(0.0, |&(_, f)| f);
+                steps.push((tau, previous));
+                steps.push((tau, fraction));
+            }
+            let last = steps.last().map_or(0.0, |&(_, f)| f);
+            steps.push((tau_max, last));
+            (label.to_string(), steps)
+        })
+        .collect();
+    report::lines_svg(
+        "Performance profile on the mean makespan",
+        "tau (ratio to the best configuration)",
+        "fraction of instances",
+        &series,
+    )
+}
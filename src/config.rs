@@ -0,0 +1,511 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::time::Duration;
+
+use crate::pherohormones::TrailFormat;
+use crate::scheduler::SchedulerKind;
+use crate::snapshot::SnapshotPolicy;
+
+/// Everything that drives one solver run, the parameters that used to be hardcoded in main.
+///
+/// Built from the defaults with the chained setters below, or read from a `key = value` file
+/// with [`SolverConfig::load`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct SolverConfig {
+    // what builds the schedules, the ant colonies or the list scheduling baseline
+    pub(crate) scheduler: SchedulerKind,
+    // the graph is in the prototype format ("n m" header), which also gives the worker count
+    pub(crate) prototype: bool,
+    pub(crate) workers: i32,
+    pub(crate) epochs: i32,
+    // a colony stops once it reaches this makespan, 0 never stops early
+    pub(crate) benchmark: i32,
+    // stop every colony on a makespan equal to the lower bound, which is optimal
+    pub(crate) stop_at_lower_bound: bool,
+    // a colony stops after this many epochs without a better makespan
+    pub(crate) stagnation: Option<i32>,
+    // the whole run stops once this much time passed or this many schedules were built
+    pub(crate) time_limit: Option<Duration>,
+    pub(crate) max_evaluations: Option<u64>,
+    // number of colonies run in parallel
+    pub(crate) colonies: i32,
+    pub(crate) deposit_base: f64,
+    // evaporation rate as a fraction of the deposit rate, drawn per colony in [0.1, 0.75) when None
+    pub(crate) evaporation_ratio: Option<f64>,
+    // exponents of the visibility and the pheromone in the transition rule
+    pub(crate) alfa: f64,
+    pub(crate) beta: f64,
+    // exploration noise as a multiple of the max weight of the first epochs, drawn per colony
+    // in [0.01, 2) when None
+    pub(crate) base_chance: Option<f64>,
+    // colonies adapt their deposit rate, evaporation rate and base chance to their improvement
+    // rate, the values above are where they start
+    pub(crate) adaptive: bool,
+    // fixes every random choice of the run, a fresh seed is drawn when None
+    pub(crate) seed: Option<u64>,
+    pub(crate) snapshots: SnapshotPolicy,
+    // every colony writes its state to <results>/checkpoints every this many epochs, and when
+    // the run is cancelled, so `resume` can continue it
+    pub(crate) checkpoint_every: Option<i32>,
+    // every colony writes its final trails to <results>/thread_N/trails.bin (or .json)
+    pub(crate) trails: Option<TrailFormat>,
+    // trails of an earlier run every colony starts from instead of empty ones, task ids
+    // above this instance's are left out
+    pub(crate) warm_start: Option<String>,
+    // limits of the exact solver that runs after the colonies on prototype graphs
+    pub(crate) bnb_node_limit: u64,
+    pub(crate) bnb_time_limit: Duration,
+    // write the instance as LP and MiniZinc models to cross check with external solvers
+    pub(crate) export_models: bool,
+    // solution of an external solver to read back and compare with the colonies
+    pub(crate) solver_solution: Option<String>,
+}
+
+impl Default for SolverConfig {
+    fn default() -> SolverConfig {
+        SolverConfig {
+            scheduler: SchedulerKind::Aco,
+            prototype: false,
+            workers: 2,
+            epochs: 10000,
+            benchmark: 0,
+            stop_at_lower_bound: true,
+            stagnation: None,
+            time_limit: None,
+            max_evaluations: None,
+            colonies: 12,
+            deposit_base: 0.01,
+            evaporation_ratio: None,
+            alfa: 0.0,
+            beta: 1.0,
+            base_chance: None,
+            adaptive: true,
+            seed: None,
+            snapshots: SnapshotPolicy::LastK(20),
+            checkpoint_every: None,
+            trails: Some(TrailFormat::Binary),
+            warm_start: None,
+            bnb_node_limit: 50_000_000,
+            bnb_time_limit: Duration::from_secs(60),
+            export_models: false,
+            solver_solution: None,
+        }
+    }
+}
+
+impl SolverConfig {
+    pub fn new() -> SolverConfig {
+        SolverConfig::default()
+    }
+
+    /// What builds the schedules, the ant colonies by default.
+    pub fn scheduler(mut self, scheduler: SchedulerKind) -> SolverConfig {
+        self.scheduler = scheduler;
+        self
+    }
+
+    /// The graph is in the prototype format, see [`Instance::load`](crate::Instance::load).
+    pub fn prototype(mut self, prototype: bool) -> SolverConfig {
+        self.prototype = prototype;
+        self
+    }
+
+    /// Number of workers, a prototype instance brings its own.
+    pub fn workers(mut self, workers: i32) -> SolverConfig {
+        self.workers = workers;
+        self
+    }
+
+    /// Epochs of every colony.
+    pub fn epochs(mut self, epochs: i32) -> SolverConfig {
+        self.epochs = epochs;
+        self
+    }
+
+    /// A colony stops once it reaches this makespan.
+    pub fn benchmark(mut self, makespan: i32) -> SolverConfig {
+        self.benchmark = makespan;
+        self
+    }
+
+    /// Stop every colony on a makespan equal to the lower bound, on by default.
+    pub fn stop_at_lower_bound(mut self, stop: bool) -> SolverConfig {
+        self.stop_at_lower_bound = stop;
+        self
+    }
+
+    /// A colony stops after this many epochs without a better makespan.
+    pub fn stagnation(mut self, epochs: i32) -> SolverConfig {
+        self.stagnation = Some(epochs);
+        self
+    }
+
+    /// The whole run stops once this much time passed.
+    pub fn time_limit(mut self, limit: Duration) -> SolverConfig {
+        self.time_limit = Some(limit);
+        self
+    }
+
+    /// The whole run stops once this many epochs ran over all colonies.
+    pub fn max_evaluations(mut self, evaluations: u64) -> SolverConfig {
+        self.max_evaluations = Some(evaluations);
+        self
+    }
+
+    /// Number of colonies run in parallel, or of schedulers for the other ones.
+    pub fn colonies(mut self, colonies: i32) -> SolverConfig {
+        self.colonies = colonies;
+        self
+    }
+
+    pub fn deposit_base(mut self, deposit_base: f64) -> SolverConfig {
+        self.deposit_base = deposit_base;
+        self
+    }
+
+    /// Evaporation rate as a fraction of the deposit rate, drawn per colony when not set.
+    pub fn evaporation_ratio(mut self, ratio: f64) -> SolverConfig {
+        self.evaporation_ratio = Some(ratio);
+        self
+    }
+
+    /// Exponent of the visibility in the transition rule.
+    pub fn alfa(mut self, alfa: f64) -> SolverConfig {
+        self.alfa = alfa;
+        self
+    }
+
+    /// Exponent of the pheromone in the transition rule.
+    pub fn beta(mut self, beta: f64) -> SolverConfig {
+        self.beta = beta;
+        self
+    }
+
+    /// Exploration noise as a multiple of the max weight of the first epochs, drawn per colony
+    /// when not set.
+    pub fn base_chance(mut self, factor: f64) -> SolverConfig {
+        self.base_chance = Some(factor);
+        self
+    }
+
+    /// Colonies adapt their deposit rate, evaporation rate and base chance to their improvement
+    /// rate. On by default, when off the parameters of the config hold for the whole run.
+    pub fn adaptive(mut self, adaptive: bool) -> SolverConfig {
+        self.adaptive = adaptive;
+        self
+    }
+
+    /// Fixes every random choice of the run.
+    pub fn seed(mut self, seed: u64) -> SolverConfig {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Pheromone snapshots, only written with a results directory.
+    pub fn snapshots(mut self, policy: SnapshotPolicy) -> SolverConfig {
+        self.snapshots = policy;
+        self
+    }
+
+    /// Checkpoint every colony this often, only written with a results directory.
+    pub fn checkpoint_every(mut self, epochs: i32) -> SolverConfig {
+        self.checkpoint_every = Some(epochs);
+        self
+    }
+
+    /// Format of the final trails of every colony, `None` writes none.
+    pub fn trails(mut self, format: Option<TrailFormat>) -> SolverConfig {
+        self.trails = format;
+        self
+    }
+
+    /// Trails of an earlier run every colony starts from.
+    pub fn warm_start(mut self, path: &str) -> SolverConfig {
+        self.warm_start = Some(path.to_string());
+        self
+    }
+
+    /// Limits of the exact solver that runs after the colonies on prototype graphs.
+    pub fn bnb_limits(mut self, nodes: u64, time: Duration) -> SolverConfig {
+        self.bnb_node_limit = nodes;
+        self.bnb_time_limit = time;
+        self
+    }
+
+    /// Reads a config file, keys that are not in the file keep their default value.
+    /// Blank lines and lines starting with '#' are ignored.
+    pub fn load(path: &str) -> io::Result<SolverConfig> {
+        let contents = fs::read_to_string(path)?;
+        let mut config = SolverConfig::default();
+        for (line_number, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid = |message: String| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{}:{}: {}", path, line_number + 1, message),
+                )
+            };
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(invalid(format!("expected key = value, got '{}'", line)));
+            };
+            config.set(key.trim(), value.trim()).map_err(invalid)?;
+        }
+        config
+            .validate()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path, e)))?;
+        Ok(config)
+    }
+
+    /// Checks the values the solver cannot run with, which the setters take as they come.
+    /// [`solve`](crate::solve) and [`SolverConfig::load`] call it first.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.workers < 1 {
+            return Err(format!("workers must be at least 1, got {}", self.workers));
+        }
+        if self.colonies < 1 {
+            return Err(format!(
+                "colonies must be at least 1, got {}",
+                self.colonies
+            ));
+        }
+        if self.epochs < 1 {
+            return Err(format!("epochs must be at least 1, got {}", self.epochs));
+        }
+        // the evaporation rates are drawn below the deposit rate
+        if !(self.deposit_base > 0.0 && self.deposit_base.is_finite()) {
+            return Err(format!(
+                "deposit_base must be a positive number, got {}",
+                self.deposit_base
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn save(&self, path: &str) -> io::Result<()> {
+        crate::export::write_file(path, &self.to_string())
+    }
+
+    /// Sets one parameter from its text form, the error says what was expected.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        fn parse<T: std::str::FromStr>(key: &str, value: &str) -> Result<T, String> {
+            value
+                .parse()
+                .map_err(|_| format!("invalid value '{}' for {}", value, key))
+        }
+        // None for limits that are not set
+        fn parse_none<T: std::str::FromStr>(key: &str, value: &str) -> Result<Option<T>, String> {
+            match value {
+                "" | "none" => Ok(None),
+                _ => parse(key, value).map(Some),
+            }
+        }
+        // seconds, negative and not a number are no duration
+        fn duration(key: &str, value: &str, seconds: f64) -> Result<Duration, String> {
+            Duration::try_from_secs_f64(seconds)
+                .map_err(|_| format!("invalid value '{}' for {}", value, key))
+        }
+        // None for values left to chance
+        fn parse_random<T: std::str::FromStr>(key: &str, value: &str) -> Result<Option<T>, String> {
+            match value {
+                "" | "random" => Ok(None),
+                _ => parse(key, value).map(Some),
+            }
+        }
+        match key {
+            "scheduler" => self.scheduler = parse(key, value)?,
+            "prototype" => self.prototype = parse(key, value)?,
+            "workers" => self.workers = parse(key, value)?,
+            "epochs" => self.epochs = parse(key, value)?,
+            "benchmark" => self.benchmark = parse(key, value)?,
+            "stop_at_lower_bound" => self.stop_at_lower_bound = parse(key, value)?,
+            "stagnation" => self.stagnation = parse_none(key, value)?,
+            "time_limit" => {
+                self.time_limit = parse_none(key, value)?
+                    .map(|seconds| duration(key, value, seconds))
+                    .transpose()?;
+            }
+            "max_evaluations" => self.max_evaluations = parse_none(key, value)?,
+            "colonies" => self.colonies = parse(key, value)?,
+            "deposit_base" => self.deposit_base = parse(key, value)?,
+            "evaporation_ratio" => self.evaporation_ratio = parse_random(key, value)?,
+            "alfa" => self.alfa = parse(key, value)?,
+            "beta" => self.beta = parse(key, value)?,
+            "base_chance" => self.base_chance = parse_random(key, value)?,
+            "adaptive" => self.adaptive = parse(key, value)?,
+            "seed" => self.seed = parse_random(key, value)?,
+            "snapshots" => self.snapshots = parse(key, value)?,
+            "checkpoint_every" => self.checkpoint_every = parse_none(key, value)?,
+            "trails" => self.trails = parse_none(key, value)?,
+            "warm_start" => {
+                self.warm_start = (!value.is_empty()).then(|| value.to_string());
+            }
+            "bnb_node_limit" => self.bnb_node_limit = parse(key, value)?,
+            "bnb_time_limit" => {
+                self.bnb_time_limit = duration(key, value, parse(key, value)?)?;
+            }
+            "export_models" => self.export_models = parse(key, value)?,
+            "solver_solution" => {
+                self.solver_solution = (!value.is_empty()).then(|| value.to_string());
+            }
+            _ => return Err(format!("unknown key '{}'", key)),
+        }
+        Ok(())
+    }
+}
+
+// the file format read by `load`
+impl fmt::Display for SolverConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "scheduler = {}", self.scheduler)?;
+        writeln!(f, "prototype = {}", self.prototype)?;
+        writeln!(f, "workers = {}", self.workers)?;
+        writeln!(f, "epochs = {}", self.epochs)?;
+        writeln!(f, "benchmark = {}", self.benchmark)?;
+        writeln!(f, "stop_at_lower_bound = {}", self.stop_at_lower_bound)?;
+        writeln!(f, "stagnation = {}", or_none(self.stagnation))?;
+        writeln!(
+            f,
+            "time_limit = {}",
+            or_none(self.time_limit.map(|t| t.as_secs_f64()))
+        )?;
+        writeln!(f, "max_evaluations = {}", or_none(self.max_evaluations))?;
+        writeln!(f, "colonies = {}", self.colonies)?;
+        writeln!(f, "deposit_base = {}", self.deposit_base)?;
+        writeln!(
+            f,
+            "evaporation_ratio = {}",
+            or_random(self.evaporation_ratio)
+        )?;
+        writeln!(f, "alfa = {}", self.alfa)?;
+        writeln!(f, "beta = {}", self.beta)?;
+        writeln!(f, "base_chance = {}", or_random(self.base_chance))?;
+        writeln!(f, "adaptive = {}", self.adaptive)?;
+        writeln!(f, "seed = {}", or_random(self.seed))?;
+        writeln!(f, "snapshots = {}", self.snapshots)?;
+        writeln!(f, "checkpoint_every = {}", or_none(self.checkpoint_every))?;
+        writeln!(f, "trails = {}", or_none(self.trails))?;
+        writeln!(
+            f,
+            "warm_start = {}",
+            self.warm_start.as_deref().unwrap_or("")
+        )?;
+        writeln!(f, "bnb_node_limit = {}", self.bnb_node_limit)?;
+        writeln!(f, "bnb_time_limit = {}", self.bnb_time_limit.as_secs_f64())?;
+        writeln!(f, "export_models = {}", self.export_models)?;
+        writeln!(
+            f,
+            "solver_solution = {}",
+            self.solver_solution.as_deref().unwrap_or("")
+        )
+    }
+}
+
+fn or_random<T: fmt::Display>(value: Option<T>) -> String {
+    value.map_or("random".to_string(), |v| v.to_string())
+}
+
+fn or_none<T: fmt::Display>(value: Option<T>) -> String {
+    value.map_or("none".to_string(), |v| v.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!(
+                "task_scheduling_config_{}_{}",
+                std::process::id(),
+                name
+            ))
+            .to_string_lossy()
+            .to_string()
+    }
+
+    // every key away from its default
+    fn changed() -> SolverConfig {
+        SolverConfig {
+            scheduler: SchedulerKind::List,
+            prototype: true,
+            workers: 5,
+            epochs: 321,
+            benchmark: 99,
+            stop_at_lower_bound: false,
+            stagnation: Some(40),
+            time_limit: Some(Duration::from_millis(2500)),
+            max_evaluations: Some(123_456),
+            colonies: 3,
+            deposit_base: 0.02,
+            evaporation_ratio: Some(0.3),
+            alfa: 1.5,
+            beta: 2.25,
+            base_chance: Some(0.125),
+            adaptive: false,
+            seed: Some(42),
+            snapshots: SnapshotPolicy::EveryNEpochs(50),
+            checkpoint_every: Some(25),
+            trails: Some(TrailFormat::Json),
+            warm_start: Some("old/trails.bin".to_string()),
+            bnb_node_limit: 1000,
+            bnb_time_limit: Duration::from_secs(5),
+            export_models: true,
+            solver_solution: Some("model.sol".to_string()),
+        }
+    }
+
+    #[test]
+    fn load_reads_back_what_save_wrote() {
+        for config in [SolverConfig::default(), changed()] {
+            let path = path("round_trip.txt");
+            config.save(&path).unwrap();
+            let loaded = SolverConfig::load(&path).unwrap();
+            let _ = fs::remove_file(&path);
+            assert_eq!(loaded, config);
+        }
+    }
+
+    #[test]
+    fn load_keeps_the_defaults_of_missing_keys() {
+        let path = path("partial.txt");
+        fs::write(&path, "# a comment\n\nworkers = 7\n  seed=3  \n").unwrap();
+        let loaded = SolverConfig::load(&path).unwrap();
+        let _ = fs::remove_file(&path);
+        assert_eq!(loaded, SolverConfig::new().workers(7).seed(3));
+    }
+
+    #[test]
+    fn load_names_the_bad_line() {
+        for (contents, message) in [
+            (
+                "workers = 2\nepochs\n",
+                ":2: expected key = value, got 'epochs'",
+            ),
+            ("colour = red\n", ":1: unknown key 'colour'"),
+            ("workers = two\n", ":1: invalid value 'two' for workers"),
+            ("time_limit = -1\n", ":1: invalid value '-1' for time_limit"),
+            ("workers = 0\n", ": workers must be at least 1, got 0"),
+        ] {
+            let path = path("bad.txt");
+            fs::write(&path, contents).unwrap();
+            let error = SolverConfig::load(&path).unwrap_err();
+            let _ = fs::remove_file(&path);
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+            assert!(error.to_string().ends_with(message), "{}", error);
+        }
+    }
+
+    #[test]
+    fn validate_rejects_what_the_solver_cannot_run() {
+        assert!(SolverConfig::default().validate().is_ok());
+        assert!(SolverConfig::new().colonies(0).validate().is_err());
+        assert!(SolverConfig::new().epochs(0).validate().is_err());
+        let mut config = SolverConfig::new();
+        config.deposit_base = f64::NAN;
+        assert!(config.validate().is_err());
+    }
+}
@@ -0,0 +1,157 @@
+use std::fmt;
+use std::fs;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::schedule::Schedule;
+use crate::utils::Utils;
+
+// Small JSON writer, enough for the flat result files without pulling in a serialisation crate
+#[derive(Default)]
+pub(crate) struct JsonObject {
+    fields: Vec<(String, String)>,
+}
+
+impl JsonObject {
+    pub fn new() -> JsonObject {
+        JsonObject::default()
+    }
+
+    // integers, booleans and anything else whose Display is already valid JSON
+    pub fn field(mut self, key: &str, value: impl fmt::Display) -> JsonObject {
+        self.fields.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    // non finite floats have no JSON representation and are written as null
+    pub fn float(mut self, key: &str, value: f64) -> JsonObject {
+        let value = if value.is_finite() {
+            format!("{}", value)
+        } else {
+            "null".to_string()
+        };
+        self.fields.push((key.to_string(), value));
+        self
+    }
+
+    pub fn string(mut self, key: &str, value: &str) -> JsonObject {
+        self.fields.push((key.to_string(), json_string(value)));
+        self
+    }
+
+    // value that is already JSON (an object or an array)
+    pub fn raw(mut self, key: &str, json: String) -> JsonObject {
+        self.fields.push((key.to_string(), json));
+        self
+    }
+}
+
+impl fmt::Display for JsonObject {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{{")?;
+        for (i, (key, value)) in self.fields.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{}:{}", json_string(key), value)?;
+        }
+        write!(f, "}}")
+    }
+}
+
+pub(crate) fn json_array<T: fmt::Display>(items: impl IntoIterator<Item = T>) -> String {
+    let items: Vec<String> = items.into_iter().map(|item| item.to_string()).collect();
+    format!("[{}]", items.join(","))
+}
+
+pub(crate) fn json_string(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+pub(crate) fn schedule_json(schedule: &Schedule, utils: &Utils) -> String {
+    let predecessors = utils.predecessors();
+    let tasks = schedule.tasks.iter().flatten().map(|entry| {
+        JsonObject::new()
+            .field("task", entry.task)
+            .field("worker", entry.worker)
+            .field("start", entry.start)
+            .field("finish", entry.finish)
+            .field("cost", utils.costs_vec[entry.task as usize])
+            .raw(
+                "predecessors",
+                json_array(&predecessors[entry.task as usize]),
+            )
+    });
+    let workers = schedule
+        .workers
+        .iter()
+        .map(|list| json_array(list.iter().map(|entry| entry.task)));
+
+    JsonObject::new()
+        .field("makespan", schedule.makespan)
+        .field("n_workers", schedule.n_workers)
+        .field("idle_time", schedule.idle_time())
+        .float("utilisation", schedule.utilisation())
+        .raw("tasks", json_array(tasks))
+        .raw("workers", json_array(workers))
+        .to_string()
+}
+
+pub(crate) fn write_schedule_json(
+    schedule: &Schedule,
+    utils: &Utils,
+    path: &str,
+) -> io::Result<()> {
+    write_file(path, &schedule_json(schedule, utils))
+}
+
+// one row per task ordered by id, the predecessors column is space separated.
+// The first four columns are the format the validate command reads back.
+pub(crate) fn write_schedule_csv(schedule: &Schedule, utils: &Utils, path: &str) -> io::Result<()> {
+    create_parent(path)?;
+    let predecessors = utils.predecessors();
+    let mut out = BufWriter::new(fs::File::create(path)?);
+    writeln!(out, "task,worker,start,finish,predecessors")?;
+    for entry in schedule.tasks.iter().flatten() {
+        let preds: Vec<String> = predecessors[entry.task as usize]
+            .iter()
+            .map(|p| p.to_string())
+            .collect();
+        writeln!(
+            out,
+            "{},{},{},{},{}",
+            entry.task,
+            entry.worker,
+            entry.start,
+            entry.finish,
+            preds.join(" ")
+        )?;
+    }
+    out.flush()
+}
+
+pub(crate) fn write_file(path: &str, contents: &str) -> io::Result<()> {
+    create_parent(path)?;
+    fs::write(path, contents)
+}
+
+pub(crate) fn create_parent(path: &str) -> io::Result<()> {
+    if let Some(parent) = Path::new(path).parent() {
+        fs::create_dir_all(parent)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,281 @@
+use std::fmt::Write as _;
+use std::io;
+
+use crate::export::{self, json_string};
+use crate::schedule::Schedule;
+use crate::utils::Utils;
+
+// layout of the chart in pixels
+const CHART_WIDTH: f64 = 1600.0;
+const LABEL_WIDTH: f64 = 80.0;
+const ROW_HEIGHT: f64 = 26.0;
+const BAR_HEIGHT: f64 = 18.0;
+const AXIS_HEIGHT: f64 = 30.0;
+const MARGIN: f64 = 10.0;
+
+const TASK_COLOR: &str = "#4e79a7";
+const CRITICAL_COLOR: &str = "#e15759";
+const IDLE_COLOR: &str = "#f4d58d";
+
+// Tasks on a critical chain of the schedule: walking back from the tasks that end at the makespan,
+// a task is critical when it starts exactly when a predecessor or the previous task of its worker finishes
+pub(crate) fn critical_tasks(schedule: &Schedule, utils: &Utils) -> Vec<bool> {
+    let predecessors = utils.predecessors();
+    let mut critical = vec![false; schedule.tasks.len()];
+    let mut stack: Vec<usize> = schedule
+        .tasks
+        .iter()
+        .flatten()
+        .filter(|entry| entry.finish == schedule.makespan)
+        .map(|entry| entry.task as usize)
+        .collect();
+
+    while let Some(task) = stack.pop() {
+        if critical[task] {
+            continue;
+        }
+        critical[task] = true;
+        let entry = schedule.tasks[task].unwrap();
+        if entry.start == 0 {
+            continue;
+        }
+        for &pred in &predecessors[task] {
+            if let Some(&Some(pred_entry)) = schedule.tasks.get(pred as usize) {
+                if pred_entry.finish == entry.start {
+                    stack.push(pred as usize);
+                }
+            }
+        }
+        let on_worker = &schedule.workers[entry.worker as usize];
+        if let Some(previous) = on_worker
+            .iter()
+            .rfind(|other| other.task != entry.task && other.finish == entry.start)
+        {
+            stack.push(previous.task as usize);
+        }
+    }
+    critical
+}
+
+// time between two labelled ticks, 1, 2 or 5 times a power of ten giving about ten ticks
+fn tick_step(makespan: i32) -> i32 {
+    let raw = (makespan.max(1) as f64 / 10.0).max(1.0);
+    let power = 10_f64.powf(raw.log10().floor());
+    let step = [1.0, 2.0, 5.0, 10.0]
+        .iter()
+        .map(|m| m * power)
+        .find(|&s| s >= raw)
+        .unwrap_or(10.0 * power);
+    step as i32
+}
+
+// Scalable Gantt chart with one row per worker, idle gaps highlighted and the critical path coloured.
+// Every bar carries a <title> (hover tooltip in viewers) and data attributes used by the HTML page.
+pub(crate) fn to_svg(schedule: &Schedule, utils: &Utils) -> String {
+    let critical = critical_tasks(schedule, utils);
+    let predecessors = utils.predecessors();
+    let makespan = schedule.makespan.max(1);
+    let scale = CHART_WIDTH / makespan as f64;
+    let n_rows = schedule.workers.len();
+    let width = LABEL_WIDTH + CHART_WIDTH + 2.0 * MARGIN;
+    let height = n_rows as f64 * ROW_HEIGHT + AXIS_HEIGHT + 2.0 * MARGIN;
+    let x_of = |cycle: i32| LABEL_WIDTH + MARGIN + cycle as f64 * scale;
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w:.0}" height="{h:.0}" viewBox="0 0 {w:.0} {h:.0}" font-family="sans-serif" font-size="11">"#,
+        w = width,
+        h = height
+    );
+    let _ = writeln!(
+        svg,
+        r#"<rect x="0" y="0" width="{:.0}" height="{:.0}" fill="white"/>"#,
+        width, height
+    );
+
+    for (worker, tasks) in schedule.workers.iter().enumerate() {
+        let y = MARGIN + worker as f64 * ROW_HEIGHT;
+        let bar_y = y + (ROW_HEIGHT - BAR_HEIGHT) / 2.0;
+        let _ = writeln!(
+            svg,
+            r#"<text x="{:.1}" y="{:.1}" dominant-baseline="middle">Worker {}</text>"#,
+            MARGIN,
+            y + ROW_HEIGHT / 2.0,
+            worker
+        );
+
+        // idle gaps between tasks and after the last one up to the makespan
+        let mut free_from = 0;
+        let mut gaps = Vec::new();
+        for entry in tasks {
+            if entry.start > free_from {
+                gaps.push((free_from, entry.start));
+            }
+            free_from = free_from.max(entry.finish);
+        }
+        if free_from < schedule.makespan {
+            gaps.push((free_from, schedule.makespan));
+        }
+        for (from, to) in gaps {
+            let _ = writeln!(
+                svg,
+                r#"<rect class="idle" x="{:.2}" y="{:.1}" width="{:.2}" height="{:.1}" fill="{}"><title>Worker {} idle {}-{}</title></rect>"#,
+                x_of(from),
+                bar_y,
+                (to - from) as f64 * scale,
+                BAR_HEIGHT,
+                IDLE_COLOR,
+                worker,
+                from,
+                to
+            );
+        }
+
+        for entry in tasks {
+            let task = entry.task as usize;
+            let color = if critical[task] {
+                CRITICAL_COLOR
+            } else {
+                TASK_COLOR
+            };
+            let preds: Vec<String> = predecessors[task].iter().map(|p| p.to_string()).collect();
+            // zero cost tasks are drawn as a thin marker so they stay visible
+            let bar_width = ((entry.finish - entry.start) as f64 * scale).max(1.0);
+            let _ = writeln!(
+                svg,
+                r#"<rect class="task" x="{:.2}" y="{:.1}" width="{:.2}" height="{:.1}" fill="{}" stroke="white" stroke-width="0.5" data-task="{}" data-worker="{}" data-start="{}" data-finish="{}" data-cost="{}" data-preds="{}" data-critical="{}"><title>Task {} on worker {}: {}-{} (cost {}){}</title></rect>"#,
+                x_of(entry.start),
+                bar_y,
+                bar_width,
+                BAR_HEIGHT,
+                color,
+                entry.task,
+                entry.worker,
+                entry.start,
+                entry.finish,
+                utils.costs_vec[task],
+                preds.join(" "),
+                critical[task],
+                entry.task,
+                entry.worker,
+                entry.start,
+                entry.finish,
+                utils.costs_vec[task],
+                if critical[task] { ", critical" } else { "" }
+            );
+            // label only bars wide enough to hold it
+            let label = entry.task.to_string();
+            if bar_width > 7.0 * label.len() as f64 + 4.0 {
+                let _ = writeln!(
+                    svg,
+                    r#"<text x="{:.2}" y="{:.1}" fill="white" text-anchor="middle" dominant-baseline="middle" pointer-events="none">{}</text>"#,
+                    x_of(entry.start) + bar_width / 2.0,
+                    bar_y + BAR_HEIGHT / 2.0,
+                    label
+                );
+            }
+        }
+    }
+
+    // time axis
+    let axis_y = MARGIN + n_rows as f64 * ROW_HEIGHT + 4.0;
+    let _ = writeln!(
+        svg,
+        r#"<line x1="{:.2}" y1="{:.1}" x2="{:.2}" y2="{:.1}" stroke="black"/>"#,
+        x_of(0),
+        axis_y,
+        x_of(makespan),
+        axis_y
+    );
+    let step = tick_step(makespan);
+    let mut tick = 0;
+    while tick <= makespan {
+        let _ = writeln!(
+            svg,
+            r#"<line x1="{x:.2}" y1="{y1:.1}" x2="{x:.2}" y2="{y2:.1}" stroke="black"/><text x="{x:.2}" y="{ty:.1}" text-anchor="middle">{t}</text>"#,
+            x = x_of(tick),
+            y1 = axis_y,
+            y2 = axis_y + 5.0,
+            ty = axis_y + 17.0,
+            t = tick
+        );
+        tick += step;
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+// Self contained page around the SVG with a tooltip that follows the mouse
+pub(crate) fn to_html(schedule: &Schedule, utils: &Utils, title: &str) -> String {
+    let mut html = String::new();
+    let _ = writeln!(html, "<!DOCTYPE html>");
+    let _ = writeln!(html, "<html><head><meta charset=\"utf-8\">");
+    let _ = writeln!(html, "<title>{}</title>", escape(title));
+    html.push_str(
+        r#"<style>
+body { font-family: sans-serif; margin: 16px; }
+#chart { overflow-x: auto; }
+rect.task:hover { opacity: 0.7; cursor: pointer; }
+#tooltip { position: fixed; display: none; background: #222; color: #fff; padding: 6px 8px;
+           border-radius: 4px; font-size: 12px; pointer-events: none; white-space: pre; }
+</style></head><body>
+"#,
+    );
+    let _ = writeln!(html, "<h2>{}</h2>", escape(title));
+    let _ = writeln!(
+        html,
+        "<p>Makespan {} on {} workers, idle time {}, utilisation {:.2}%. Critical path in red, idle gaps in yellow.</p>",
+        schedule.makespan,
+        schedule.n_workers,
+        schedule.idle_time(),
+        schedule.utilisation() * 100.0
+    );
+    let _ = writeln!(html, "<div id=\"chart\">");
+    html.push_str(&to_svg(schedule, utils));
+    let _ = writeln!(html, "</div><div id=\"tooltip\"></div>");
+    html.push_str(
+        r#"<script>
+const tooltip = document.getElementById("tooltip");
+document.querySelectorAll("rect.task").forEach(function (bar) {
+  const title = bar.querySelector("title");
+  if (title) title.remove();
+  bar.addEventListener("mousemove", function (event) {
+    const d = bar.dataset;
+    tooltip.textContent = "Task " + d.task + (d.critical === "true" ? " (critical)" : "") +
+      "\nWorker " + d.worker + "\nStart " + d.start + "  Finish " + d.finish +
+      "\nCost " + d.cost + "\nPredecessors " + (d.preds || "-");
+    tooltip.style.left = (event.clientX + 12) + "px";
+    tooltip.style.top = (event.clientY + 12) + "px";
+    tooltip.style.display = "block";
+  });
+  bar.addEventListener("mouseleave", function () { tooltip.style.display = "none"; });
+});
+</script>
+</body></html>
+"#,
+    );
+    html
+}
+
+pub(crate) fn write_svg(schedule: &Schedule, utils: &Utils, path: &str) -> io::Result<()> {
+    export::write_file(path, &to_svg(schedule, utils))
+}
+
+pub(crate) fn write_html(
+    schedule: &Schedule,
+    utils: &Utils,
+    title: &str,
+    path: &str,
+) -> io::Result<()> {
+    export::write_file(path, &to_html(schedule, utils, title))
+}
+
+fn escape(text: &str) -> String {
+    // reuse the JSON escaping for quotes and control characters, then the HTML specials
+    let quoted = json_string(text);
+    quoted[1..quoted.len() - 1]
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
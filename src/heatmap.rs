@@ -0,0 +1,108 @@
+use std::fs::File;
+use std::io::{self, BufWriter};
+
+use crate::export::create_parent;
+use crate::pherohormones::{self, Pherohormones};
+
+// largest side of a heatmap, bigger graphs are downsampled into blocks of tasks
+const MAX_SIDE: usize = 512;
+// palette index 0 is a cell without trail, 1..=255 the weight from low to high
+const PALETTE_LEVELS: f64 = 254.0;
+
+// Task x task picture of the pheromone trails, row = task the ant leaves, column = the next task.
+// Pixels are indices into the shared palette so the same frame goes to PNG and GIF unchanged.
+pub(crate) struct Heatmap {
+    pub side: usize,
+    pub pixels: Vec<u8>,
+}
+
+impl Heatmap {
+    pub fn from_pherohormones(pherohormones: &Pherohormones) -> Heatmap {
+        let n = pherohormones.n_tasks.max(1) as usize;
+        // tasks per cell and cells per side after downsampling
+        let block = n.div_ceil(MAX_SIDE);
+        let cells = n.div_ceil(block);
+        // small graphs get several pixels per cell
+        let scale = (MAX_SIDE / cells).max(1);
+        let side = cells * scale;
+
+        // a block keeps its strongest trail so sparse trails survive the downsampling
+        let mut grid = vec![0.0_f64; cells * cells];
+        for edge in pherohormones.di_graph.edge_indices() {
+            let (source, target) = pherohormones.di_graph.edge_endpoints(edge).unwrap();
+            let cell = (source.index() / block) * cells + target.index() / block;
+            grid[cell] = grid[cell].max(pherohormones.di_graph[edge]);
+        }
+        let max_weight = grid.iter().cloned().fold(0.0_f64, f64::max);
+
+        let mut pixels = vec![0u8; side * side];
+        for row in 0..side {
+            for col in 0..side {
+                let weight = grid[(row / scale) * cells + col / scale];
+                if weight > 0.0 && max_weight > 0.0 {
+                    pixels[row * side + col] = 1 + (weight / max_weight * PALETTE_LEVELS) as u8;
+                }
+            }
+        }
+        Heatmap { side, pixels }
+    }
+
+    pub fn write_png(&self, path: &str) -> io::Result<()> {
+        create_parent(path)?;
+        let file = BufWriter::new(File::create(path)?);
+        let mut encoder = png::Encoder::new(file, self.side as u32, self.side as u32);
+        encoder.set_color(png::ColorType::Indexed);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.set_palette(palette());
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.pixels)?;
+        writer.finish()?;
+        Ok(())
+    }
+}
+
+// Animated GIF written frame by frame, nothing is kept in memory between improvements
+pub(crate) struct HeatmapAnimation {
+    encoder: gif::Encoder<BufWriter<File>>,
+    side: usize,
+}
+
+impl HeatmapAnimation {
+    pub fn create(path: &str, side: usize) -> io::Result<HeatmapAnimation> {
+        create_parent(path)?;
+        let file = BufWriter::new(File::create(path)?);
+        let mut encoder = gif::Encoder::new(file, side as u16, side as u16, &palette())
+            .map_err(io::Error::other)?;
+        encoder
+            .set_repeat(gif::Repeat::Infinite)
+            .map_err(io::Error::other)?;
+        Ok(HeatmapAnimation { encoder, side })
+    }
+
+    // delay in hundredths of a second
+    pub fn add_frame(&mut self, heatmap: &Heatmap, delay: u16) -> io::Result<()> {
+        assert_eq!(
+            heatmap.side, self.side,
+            "all frames must have the same size"
+        );
+        let mut frame = gif::Frame::from_indexed_pixels(
+            self.side as u16,
+            self.side as u16,
+            heatmap.pixels.clone(),
+            None,
+        );
+        frame.delay = delay;
+        self.encoder.write_frame(&frame).map_err(io::Error::other)?;
+        Ok(())
+    }
+}
+
+// white for no trail then the blue to red ramp of the GEXF and DOT exports
+fn palette() -> Vec<u8> {
+    let mut palette = vec![255, 255, 255];
+    for level in 0..=PALETTE_LEVELS as usize {
+        let (r, g, b) = pherohormones::weight_color(level as f64 / PALETTE_LEVELS);
+        palette.extend_from_slice(&[r, g, b]);
+    }
+    palette
+}
@@ -0,0 +1,89 @@
+use std::io;
+
+use crate::utils::Utils;
+
+/// A task graph to schedule, read from a Standard Task Graph (STG) file.
+///
+/// Prototype files start with an `n m` header that also gives the number of workers, see
+/// [`Instance::workers`].
+#[derive(Clone)]
+pub struct Instance {
+    pub(crate) utils: Utils,
+    pub(crate) workers: Option<i32>,
+    pub(crate) prototype: bool,
+    // the directory with its trailing separator and the file name, how the rest of the crate
+    // names an instance
+    pub(crate) file_path: String,
+    pub(crate) name: String,
+}
+
+impl Instance {
+    /// Reads an STG file, or a prototype file when `prototype` is set.
+    pub fn load(path: &str, prototype: bool) -> io::Result<Instance> {
+        let (file_path, name) = match path.rfind('/') {
+            Some(i) => path.split_at(i + 1),
+            None => ("", path),
+        };
+        Instance::open(file_path, name, prototype)
+    }
+
+    pub(crate) fn open(file_path: &str, name: &str, prototype: bool) -> io::Result<Instance> {
+        let path = format!("{}{}", file_path, name);
+        let mut utils = Utils::new();
+        let mut workers = 0;
+        if prototype {
+            utils.initialize_graph_prototype(file_path, name, &mut workers)?;
+        } else {
+            utils.initialize_graph(file_path, name)?;
+        }
+        utils.init_arrays();
+        if utils.n_tasks == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: no tasks", path),
+            ));
+        }
+        if prototype && workers < 1 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: no workers", path),
+            ));
+        }
+        Ok(Instance {
+            utils,
+            workers: prototype.then_some(workers),
+            prototype,
+            file_path: file_path.to_string(),
+            name: name.to_string(),
+        })
+    }
+
+    /// The file name the instance was read from.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn path(&self) -> String {
+        format!("{}{}", self.file_path, self.name)
+    }
+
+    /// Number of tasks, including the entry and exit tasks of the STG format.
+    pub fn n_tasks(&self) -> i32 {
+        self.utils.n_tasks
+    }
+
+    /// The worker count of a prototype file, which replaces the one of the config.
+    pub fn workers(&self) -> Option<i32> {
+        self.workers
+    }
+
+    /// Processing time of every task, indexed by task id.
+    pub fn costs(&self) -> &[i32] {
+        &self.utils.costs_vec
+    }
+
+    /// Sorted predecessors of every task, indexed by task id.
+    pub fn predecessors(&self) -> Vec<Vec<i32>> {
+        self.utils.predecessors()
+    }
+}
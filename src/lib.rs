@@ -0,0 +1,68 @@
+//! Task scheduling with parallel ant colonies: every colony builds schedules of a task graph on
+//! a fixed number of workers, guided by pheromone trails on the precedence edges.
+//!
+//! ```no_run
+//! use task_scheduling::{solve, Instance, SolverConfig};
+//!
+//! let instance = Instance::load("graphs/rand0000.stg", false).unwrap();
+//! let result = solve(&instance, &SolverConfig::new().workers(16).epochs(1000)).unwrap();
+//! println!("makespan {} ({:.2}% over the lower bound)", result.makespan, result.gap * 100.0);
+//! for task in result.schedule.entries() {
+//!     println!("task {} on worker {} at {}", task.task, task.worker, task.start);
+//! }
+//! ```
+//!
+//! Runs log to stderr, at [`LogLevel::Info`] unless [`set_log_level`] says otherwise, and
+//! [`ProgressDisplay`] keeps a single live progress line for all the colonies.
+//!
+//! The binary is a thin wrapper over [`cli`].
+
+mod adaptive;
+mod batch;
+mod bounds;
+mod branch_bound;
+mod checkpoint;
+#[doc(hidden)]
+pub mod cli;
+mod colony;
+mod compare;
+mod config;
+mod export;
+mod gantt;
+mod heatmap;
+mod instance;
+mod list_scheduler;
+mod logging;
+mod manager_ant;
+mod models;
+mod observer;
+mod pherohormones;
+mod report;
+mod schedule;
+mod scheduler;
+mod snapshot;
+mod solver;
+mod stats;
+mod stopping;
+mod telemetry;
+mod trace;
+mod tuning;
+mod worker_ant;
+
+mod utils;
+mod validator;
+
+pub use branch_bound::BnbResult;
+pub use config::SolverConfig;
+pub use instance::Instance;
+pub use logging::{
+    progress_supported, set_log_format, set_log_level, LogFormat, LogLevel, ProgressDisplay,
+};
+pub use observer::{Event, Observer, STAGNATION_EVENT_EPOCHS};
+pub use pherohormones::TrailFormat;
+pub use schedule::{Schedule, ScheduledTask};
+pub use scheduler::{RunState, Scheduler, SchedulerContext, SchedulerKind};
+pub use snapshot::SnapshotPolicy;
+pub use solver::{solve, solve_with, BuildScheduler, ColonyResult, SolveOptions, SolveResult};
+pub use stopping::{CancelToken, StopReason};
+pub use telemetry::{EpochRecord, PheromoneStats};
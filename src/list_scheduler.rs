@@ -0,0 +1,154 @@
+use std::time::Instant;
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::bounds;
+use crate::schedule::{Schedule, ScheduledTask};
+use crate::scheduler::{RunState, Scheduler};
+use crate::telemetry::EpochRecord;
+use crate::utils::Utils;
+
+// how much the tails are perturbed after the first step, as a fraction of their length
+const NOISE: f64 = 0.25;
+
+// Critical path list scheduling, the baseline for the colonies: every step starts the ready task
+// with the longest tail at its earliest start on any worker. The first step follows the tails
+// exactly, the later ones perturb them so every restart tries another order.
+pub(crate) struct ListScheduler {
+    id: i32,
+    n_workers: i32,
+    lower_bound: i32,
+    costs: Vec<i32>,
+    successors: Vec<Vec<usize>>,
+    n_predecessors: Vec<i32>,
+    // longest path from every task to the end, its cost included
+    tails: Vec<i32>,
+    rng: ChaCha8Rng,
+    best: Option<Schedule>,
+    record: EpochRecord,
+}
+
+impl ListScheduler {
+    pub fn new(
+        utils: &Utils,
+        n_workers: i32,
+        id: i32,
+        seed: u64,
+        lower_bound: i32,
+    ) -> ListScheduler {
+        let n = utils.n_tasks as usize;
+        let mut successors = vec![Vec::new(); n];
+        let mut n_predecessors = vec![0; n];
+        for edge in utils.di_graph.edge_indices() {
+            let (source, target) = utils.di_graph.edge_endpoints(edge).unwrap();
+            successors[source.index()].push(target.index());
+            n_predecessors[target.index()] += 1;
+        }
+        let (_, tails) = bounds::heads_and_tails(utils);
+        ListScheduler {
+            id,
+            n_workers: n_workers.max(1),
+            lower_bound,
+            costs: utils.costs_vec.clone(),
+            successors,
+            n_predecessors,
+            tails,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+            best: None,
+            record: EpochRecord::default(),
+        }
+    }
+
+    // one schedule, every tail stretched by up to `noise` of its length
+    fn build(&mut self, noise: f64) -> Schedule {
+        let n = self.costs.len();
+        let priority: Vec<f64> = self
+            .tails
+            .iter()
+            .map(|&tail| tail as f64 * (1.0 + noise * self.rng.random::<f64>()))
+            .collect();
+        let mut remaining = self.n_predecessors.clone();
+        // when the last predecessor of every task finishes
+        let mut ready_at = vec![0; n];
+        let mut ready: Vec<usize> = (0..n).filter(|&task| remaining[task] == 0).collect();
+        let mut free_at = vec![0; self.n_workers as usize];
+        let mut entries = Vec::with_capacity(n);
+        while !ready.is_empty() {
+            // highest priority first, ties to the lowest task id
+            let index = (0..ready.len())
+                .max_by(|&a, &b| {
+                    priority[ready[a]]
+                        .total_cmp(&priority[ready[b]])
+                        .then(ready[b].cmp(&ready[a]))
+                })
+                .unwrap();
+            let task = ready.swap_remove(index);
+            // the worker that can start it first
+            let (start, worker) = free_at
+                .iter()
+                .enumerate()
+                .map(|(worker, &free)| (free.max(ready_at[task]), worker))
+                .min()
+                .unwrap();
+            let finish = start + self.costs[task];
+            free_at[worker] = finish;
+            entries.push(ScheduledTask {
+                task: task as i32,
+                worker: worker as i32,
+                start,
+                finish,
+            });
+            for &succ in &self.successors[task] {
+                ready_at[succ] = ready_at[succ].max(finish);
+                remaining[succ] -= 1;
+                if remaining[succ] == 0 {
+                    ready.push(succ);
+                }
+            }
+        }
+        Schedule::new(n as i32, self.n_workers, &entries)
+    }
+}
+
+impl Scheduler for ListScheduler {
+    fn name(&self) -> &str {
+        "list"
+    }
+
+    fn initialise(&mut self) -> RunState {
+        RunState::default()
+    }
+
+    fn step(&mut self, step: i32) -> i32 {
+        let construction = Instant::now();
+        let schedule = self.build(if step == 0 { 0.0 } else { NOISE });
+        let construction_ms = construction.elapsed().as_secs_f64() * 1000.0;
+        let makespan = schedule.makespan;
+        if self
+            .best
+            .as_ref()
+            .is_none_or(|best| makespan < best.makespan)
+        {
+            self.best = Some(schedule);
+        }
+        self.record = EpochRecord {
+            colony_id: self.id,
+            epoch: step,
+            current: makespan,
+            best: self.best.as_ref().map_or(makespan, |best| best.makespan),
+            lower_bound: self.lower_bound,
+            construction_ms,
+            ..EpochRecord::default()
+        };
+        makespan
+    }
+
+    fn best(&self) -> Option<&Schedule> {
+        self.best.as_ref()
+    }
+
+    fn telemetry(&self) -> EpochRecord {
+        self.record.clone()
+    }
+}
@@ -0,0 +1,295 @@
+use std::fmt;
+use std::io::{self, IsTerminal, Write};
+use std::ops::ControlFlow;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use crate::export::JsonObject;
+use crate::observer::{Event, Observer};
+
+// Leveled log lines on stderr, shared by every colony thread. A line is written whole under
+// one lock, and the live progress line, when one is shown, is cleared before it and drawn
+// again after it so the two never interleave.
+
+/// How much the solver writes to stderr, set with [`set_log_level`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    /// The default: bounds, new best schedules, stopped colonies and the results.
+    Info,
+    /// Also the periodic progress of every colony and its parameter changes.
+    Debug,
+    Trace,
+}
+
+/// How log lines are written, set with [`set_log_format`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogFormat {
+    /// `INFO  [colony 3] message`
+    Text,
+    /// One JSON object per line with the level, the seconds since the start, the colony when
+    /// there is one and the message.
+    Json,
+}
+
+static LEVEL: AtomicU8 = AtomicU8::new(LogLevel::Info as u8);
+static JSON: AtomicBool = AtomicBool::new(false);
+// the progress line currently on the terminal, redrawn under every log line
+static STATUS: Mutex<Option<String>> = Mutex::new(None);
+static START: OnceLock<Instant> = OnceLock::new();
+// off with `--no-progress`, the command line runs draw the live line when this is set
+static PROGRESS_LINE: AtomicBool = AtomicBool::new(true);
+
+/// Messages above this level are dropped, [`LogLevel::Error`] keeps the solver quiet.
+pub fn set_log_level(level: LogLevel) {
+    LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+pub fn set_log_format(format: LogFormat) {
+    JSON.store(format == LogFormat::Json, Ordering::Relaxed);
+    START.get_or_init(Instant::now);
+}
+
+pub(crate) fn enabled(level: LogLevel) -> bool {
+    level as u8 <= LEVEL.load(Ordering::Relaxed)
+}
+
+pub(crate) fn json() -> bool {
+    JSON.load(Ordering::Relaxed)
+}
+
+pub(crate) fn write(level: LogLevel, colony: Option<i32>, args: fmt::Arguments) {
+    if !enabled(level) {
+        return;
+    }
+    let line = if json() {
+        let elapsed = START.get_or_init(Instant::now).elapsed();
+        let mut object = JsonObject::new()
+            .string("level", &level.to_string())
+            .float("time", elapsed.as_secs_f64());
+        if let Some(colony) = colony {
+            object = object.field("colony", colony);
+        }
+        object.string("message", &args.to_string()).to_string()
+    } else {
+        match colony {
+            Some(colony) => format!("{:<5} [colony {}] {}", level.as_str(), colony, args),
+            None => format!("{:<5} {}", level.as_str(), args),
+        }
+    };
+    let status = STATUS.lock().unwrap();
+    let mut stderr = io::stderr().lock();
+    // nothing sensible to do when stderr is gone
+    let _ = match &*status {
+        Some(status) => write!(stderr, "\r\x1b[2K{}\n{}", line, status),
+        None => writeln!(stderr, "{}", line),
+    };
+    let _ = stderr.flush();
+}
+
+// replaces the progress line, None clears it
+fn set_status(status: Option<String>) {
+    let mut current = STATUS.lock().unwrap();
+    let mut stderr = io::stderr().lock();
+    let _ = match &status {
+        Some(status) => write!(stderr, "\r\x1b[2K{}", status),
+        None if current.is_some() => write!(stderr, "\r\x1b[2K"),
+        None => Ok(()),
+    };
+    let _ = stderr.flush();
+    *current = status;
+}
+
+/// Whether a live progress line makes sense: stderr is a terminal, the lines are text and the
+/// level shows the progress at all.
+pub fn progress_supported() -> bool {
+    io::stderr().is_terminal() && !json() && enabled(LogLevel::Info)
+}
+
+pub(crate) fn set_progress_line(enabled: bool) {
+    PROGRESS_LINE.store(enabled, Ordering::Relaxed);
+}
+
+pub(crate) fn progress_line() -> bool {
+    PROGRESS_LINE.load(Ordering::Relaxed) && progress_supported()
+}
+
+impl LogLevel {
+    fn as_str(&self) -> &'static str {
+        match self {
+            LogLevel::Error => "ERROR",
+            LogLevel::Warn => "WARN",
+            LogLevel::Info => "INFO",
+            LogLevel::Debug => "DEBUG",
+            LogLevel::Trace => "TRACE",
+        }
+    }
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str().to_lowercase())
+    }
+}
+
+impl FromStr for LogLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<LogLevel, String> {
+        match s.to_lowercase().as_str() {
+            "error" => Ok(LogLevel::Error),
+            "warn" => Ok(LogLevel::Warn),
+            "info" => Ok(LogLevel::Info),
+            "debug" => Ok(LogLevel::Debug),
+            "trace" => Ok(LogLevel::Trace),
+            _ => Err(format!("unknown log level '{}'", s)),
+        }
+    }
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<LogFormat, String> {
+        match s {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(format!("unknown log format '{}'", s)),
+        }
+    }
+}
+
+// `info!(colony: id, "...", args)` tags the line with the colony, `info!("...", args)` does not
+macro_rules! log {
+    ($level:expr, colony: $colony:expr, $($arg:tt)+) => {
+        $crate::logging::write($level, Some($colony), format_args!($($arg)+))
+    };
+    ($level:expr, $($arg:tt)+) => {
+        $crate::logging::write($level, None, format_args!($($arg)+))
+    };
+}
+
+macro_rules! error {
+    ($($arg:tt)+) => { $crate::logging::log!($crate::logging::LogLevel::Error, $($arg)+) };
+}
+
+// `warn` alone would clash with the lint attribute of the same name, it is exported as `warn`
+macro_rules! warning {
+    ($($arg:tt)+) => { $crate::logging::log!($crate::logging::LogLevel::Warn, $($arg)+) };
+}
+
+macro_rules! info {
+    ($($arg:tt)+) => { $crate::logging::log!($crate::logging::LogLevel::Info, $($arg)+) };
+}
+
+macro_rules! debug {
+    ($($arg:tt)+) => { $crate::logging::log!($crate::logging::LogLevel::Debug, $($arg)+) };
+}
+
+#[allow(unused_macros)]
+macro_rules! trace {
+    ($($arg:tt)+) => { $crate::logging::log!($crate::logging::LogLevel::Trace, $($arg)+) };
+}
+
+#[allow(unused_imports)]
+pub(crate) use trace;
+pub(crate) use {debug, error, info, log, warning as warn};
+
+// how often the progress line is drawn at most
+const REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+
+/// One live line for the whole run, in place of a progress line per colony: the best makespan
+/// over all colonies, the colony that found it, the epochs the colonies are at and how many
+/// of them finished.
+///
+/// It is an [`Observer`], add it to the run with
+/// [`SolveOptions::observe`](crate::SolveOptions::observe). The line is drawn on stderr and
+/// cleared when the display is dropped.
+pub struct ProgressDisplay {
+    label: String,
+    epochs: i32,
+    state: Mutex<ProgressState>,
+}
+
+#[derive(Default)]
+struct ProgressState {
+    // the epoch every colony is at, by colony id
+    epochs: Vec<i32>,
+    finished: usize,
+    best: Option<(i32, i32)>,
+    lower_bound: i32,
+    drawn: Option<Instant>,
+}
+
+impl ProgressDisplay {
+    /// A display for a run of `colonies` colonies of `epochs` epochs, `label` starts the line.
+    pub fn new(label: &str, colonies: i32, epochs: i32) -> ProgressDisplay {
+        ProgressDisplay {
+            label: label.to_string(),
+            epochs,
+            state: Mutex::new(ProgressState {
+                epochs: vec![0; colonies.max(0) as usize],
+                ..ProgressState::default()
+            }),
+        }
+    }
+
+    fn line(&self, state: &ProgressState) -> String {
+        let min = state.epochs.iter().min().copied().unwrap_or(0);
+        let max = state.epochs.iter().max().copied().unwrap_or(0);
+        let best = match state.best {
+            Some((best, colony)) => format!("best {} (colony {})", best, colony),
+            None => "best -".to_string(),
+        };
+        format!(
+            "{} | {} | lower bound {} | epoch {}-{}/{} | {}/{} colonies done",
+            self.label,
+            best,
+            state.lower_bound,
+            min,
+            max,
+            self.epochs,
+            state.finished,
+            state.epochs.len()
+        )
+    }
+}
+
+impl Observer for ProgressDisplay {
+    fn on_event(&self, event: &Event<'_>) -> ControlFlow<()> {
+        let record = event.record();
+        let mut state = self.state.lock().unwrap();
+        let colony = record.colony_id.max(0) as usize;
+        if colony >= state.epochs.len() {
+            state.epochs.resize(colony + 1, 0);
+        }
+        state.epochs[colony] = state.epochs[colony].max(record.epoch + 1);
+        state.lower_bound = record.lower_bound;
+        if state.best.is_none_or(|(best, _)| record.best < best) {
+            state.best = Some((record.best, record.colony_id));
+        }
+        let finished = matches!(event, Event::ColonyFinished { .. });
+        if finished {
+            state.finished += 1;
+        }
+        // a finished colony is always shown, the epochs in between at most every interval
+        if finished
+            || state
+                .drawn
+                .is_none_or(|drawn| drawn.elapsed() >= REDRAW_INTERVAL)
+        {
+            state.drawn = Some(Instant::now());
+            set_status(Some(self.line(&state)));
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+impl Drop for ProgressDisplay {
+    fn drop(&mut self) {
+        set_status(None);
+    }
+}
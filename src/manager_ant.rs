@@ -1,17 +1,13 @@
-use std::cell::RefCell;
-use std::fs;
-use std::fs::OpenOptions;
-use std::io::{BufRead, BufReader, Error, Write};
-use std::rc::Rc;
 use std::sync::{Arc, Mutex};
 
-use petgraph::stable_graph::{EdgeIndex, NodeIndex, StableDiGraph};
+use petgraph::stable_graph::{NodeIndex, StableDiGraph};
 use petgraph::Direction;
 
-use rand::{thread_rng, Rng};
-use random_choice::random_choice;
+use rand::Rng;
+use rand_chacha::ChaCha8Rng;
 
 use crate::pherohormones::{self, Pherohormones};
+use crate::schedule::Schedule;
 use crate::utils::Utils;
 use crate::worker_ant::WorkerAnt;
 
@@ -29,7 +25,6 @@ pub(crate) struct ManagerAnt {
     current_cycle: i32,
     pherohormones: Arc<Mutex<Pherohormones>>,
     pub local_pherohormones: pherohormones::Pherohormones,
-    evaporation_rate: f64,
     deposit_rate: f64,
     alfa: f64,
     beta: f64,
@@ -39,13 +34,12 @@ pub(crate) struct ManagerAnt {
 }
 
 impl ManagerAnt {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         utils: &Utils,
         n_ants: i32,
         pherohormones: Arc<Mutex<Pherohormones>>,
-        evaporation_rate: f64,
         deposit_rate: f64,
-        n_tasks: i32,
         alfa: f64,
         beta: f64,
         base_chance: f64,
@@ -53,8 +47,8 @@ impl ManagerAnt {
         let local_pherohormones = pherohormones.lock().unwrap().clone();
 
         ManagerAnt {
-            n_ants: n_ants,
-            ants: vec![WorkerAnt::new(n_tasks); n_ants as usize],
+            n_ants,
+            ants: vec![WorkerAnt::new(); n_ants as usize],
             // the utils struct is passed by value and we just need to clone it because threre wuill be a lot of colonies and we dont want to share the same utils struct
             //utils: utils.clone(),
             di_graph: utils.di_graph.clone(),
@@ -67,7 +61,6 @@ impl ManagerAnt {
             current_cycle: 0,
             pherohormones,
             local_pherohormones,
-            evaporation_rate,
             deposit_rate,
             alfa,
             beta,
@@ -77,12 +70,10 @@ impl ManagerAnt {
     }
     // this is the main fucntion for the colony to work
     // it will be called by the main function in a loop that stops when the graph is totally destroyed meaning that all tasks are completed
-    pub fn work(&mut self, frame_counter: i32) -> i32 {
-        let mut cycles_spent = 0;
-
+    pub fn work(&mut self, _frame_counter: i32, rng: &mut ChaCha8Rng) -> i32 {
         self.init_available_tasks();
 
-        self.check_available_tasks();
+        self.check_available_tasks(rng);
 
         //self.print_ants();
         //println!("Starting work session...");
@@ -92,12 +83,17 @@ impl ManagerAnt {
             //self.print_available_tasks();
 
             self.check_tasks_completion();
-            self.check_available_tasks();
+            self.check_available_tasks(rng);
             self.current_cycle += 1;
         }
 
-        cycles_spent = self.current_cycle - 2;
-        cycles_spent
+        // the makespan is the last finish, not the cycle the simulation stopped at
+        self.ants
+            .iter()
+            .flat_map(|ant| ant.task_history.iter())
+            .map(|&(_, _, finish)| finish)
+            .max()
+            .unwrap_or(0)
     }
 
     fn check_tasks_completion(&mut self) {
@@ -121,7 +117,7 @@ impl ManagerAnt {
 
             if self.ants[i as usize].free_at <= self.current_cycle {
                 // Set the worker as free.
-                if (self.ants[i as usize].current_task != -1) {
+                if self.ants[i as usize].current_task != -1 {
                     let finished_task = self.ants[i as usize].current_task;
 
                     // there is a task to mark as completed so:
@@ -129,7 +125,8 @@ impl ManagerAnt {
                     // Remove the task node from the graph as well as its edges.
 
                     self.reduce_and_destroy(finished_task);
-                    self.ants[i as usize].complete_task(finished_task, self.current_cycle);
+                    let finish = self.ants[i as usize].free_at;
+                    self.ants[i as usize].complete_task(finished_task, finish);
 
                     //println!("Completed Task {} !", finished_task + 1);
                 }
@@ -140,11 +137,7 @@ impl ManagerAnt {
     // this fucntion will allow simple setup to initiate the algorithm for both nbormal and prototype graphs, since prototype may have multiple opossible starting points
     fn init_available_tasks(&mut self) {
         for i in 0..self.n_tasks {
-            if self.remaining_vec[i as usize] == 0 {
-                self.available_tasks[i as usize] = true;
-            } else {
-                self.available_tasks[i as usize] = false;
-            }
+            self.available_tasks[i as usize] = self.remaining_vec[i as usize] == 0;
         }
     }
 
@@ -153,15 +146,12 @@ impl ManagerAnt {
             return;
         }
 
-        let mut neighboors = Vec::new();
         // get the neighboors of the task , they are all the outgoing edges targets
-        let mut neighboors_iter = self
+        let neighboors: Vec<i32> = self
             .di_graph
-            .neighbors_directed(NodeIndex::new(task as usize), Direction::Outgoing);
-
-        while let Some(neighboor) = neighboors_iter.next() {
-            neighboors.push(neighboor.index() as i32);
-        }
+            .neighbors_directed(NodeIndex::new(task as usize), Direction::Outgoing)
+            .map(|neighboor| neighboor.index() as i32)
+            .collect();
 
         //reduce the remaining vec for the neighboors
         for &nb in &neighboors {
@@ -176,18 +166,18 @@ impl ManagerAnt {
         // once a node is removed all incoming and outgoing edges are removed as well
     }
 
-    fn check_available_tasks(&mut self) {
+    fn check_available_tasks(&mut self, rng: &mut ChaCha8Rng) {
         for i in 0..self.n_ants {
             if self.ants[i as usize].current_task == -1 {
-                // choose a taskthread_rng
-                self.choose_task_randomly_weighted(i);
+                // choose a task
+                self.choose_task_randomly_weighted(i, rng);
             }
         }
     }
 
     // This function chooses a task randomly among available tasks,
     // using the visibility vector as the weight (plus an optional base chance if desired).
-    fn choose_task_randomly_weighted(&mut self, free_ant: i32) {
+    fn choose_task_randomly_weighted(&mut self, free_ant: i32, rng: &mut ChaCha8Rng) {
         // Collect available tasks and their corresponding visibility weights
         let mut candidate_tasks = Vec::new();
         let mut weights = Vec::new();
@@ -213,8 +203,7 @@ impl ManagerAnt {
                 // the fucntion is visibility^alfa * pheromone^beta/ visibility_sum^alfa * pheromone_sum^beta
                 let mut weight: f64 = 0.0;
                 if pherohormones_sum > 0.0 {
-                    weight = ((self.visibility_vec[i] as f64).powf(self.alfa)
-                        * (pheromone as f64).powf(self.beta))
+                    weight = (self.visibility_vec[i].powf(self.alfa) * pheromone.powf(self.beta))
                         / (self.visibility_sum.powf(self.alfa) * pherohormones_sum.powf(self.beta));
                 }
 
@@ -232,7 +221,6 @@ impl ManagerAnt {
                 //     self.base_chance
                 // );]
 
-                let mut rng = rand::rng();
                 // Generate a random number between 0.0 and max value wich wil decrease as epochs advance
                 // let max_value =
                 let base_chance = rng.random_range(0.0..1.0) * self.base_chance;
@@ -249,57 +237,49 @@ impl ManagerAnt {
             return;
         }
 
-        // Determine the number of choices to make; in this case, we choose one task
-        let number_of_choices: usize = 1;
-
-        // Use random_choice to select tasks based on weights
-        let chosen_tasks =
-            random_choice().random_choice_f64(&candidate_tasks, &weights, number_of_choices);
+        // Roulette wheel over the weights, drawn from the colony rng so a seeded run is reproducible
+        let chosen_task = candidate_tasks[roulette(&weights, rng)];
 
         // Assign the chosen task to an available ant
-        if let Some(&chosen_task) = chosen_tasks.first() {
-            // free ant already checked passed as parameter
-
-            // Mark the task as no longer available
-            self.available_tasks[*chosen_task as usize] = false;
-
-            let free_at = self.current_cycle + self.costs_vec[*chosen_task as usize];
-            let last_task = self.ants[free_ant as usize].last_task;
-            // Only add pherohormones to the local pherohormones
-            self.ants[free_ant as usize].start_task(
-                last_task as i32,
-                *chosen_task as i32,
-                free_at,
-                &mut self.local_pherohormones,
-                self.deposit_rate,
-                self.current_cycle,
-            );
-
-            //println!("Ant {} has Started task {} ", freeAnt, chosen_task + 1);
-        }
+        // free ant already checked passed as parameter
+
+        // Mark the task as no longer available
+        self.available_tasks[chosen_task as usize] = false;
+
+        let free_at = self.current_cycle + self.costs_vec[chosen_task as usize];
+        let last_task = self.ants[free_ant as usize].last_task;
+        // Only add pherohormones to the local pherohormones
+        self.ants[free_ant as usize].start_task(
+            last_task,
+            chosen_task,
+            free_at,
+            &mut self.local_pherohormones,
+            self.deposit_rate,
+            self.current_cycle,
+        );
+
+        //println!("Ant {} has Started task {} ", freeAnt, chosen_task + 1);
     }
 
     /*########## UTILS ########## */
 
-    pub fn current_c(&self) -> i32 {
-        self.current_cycle
+    pub fn schedule(&self) -> Schedule {
+        Schedule::from_ants(self.n_tasks, &self.ants)
     }
+}
 
-    pub fn print_ants(&self) {
-        for i in 0..self.n_ants {
-            println!(
-                "Ant {} is working on task {} and will be free at cycle {}",
-                i, self.ants[i as usize].current_task, self.ants[i as usize].free_at
-            );
-        }
+// index picked with probability proportional to its weight, uniform when all weights are zero
+fn roulette(weights: &[f64], rng: &mut ChaCha8Rng) -> usize {
+    let total: f64 = weights.iter().sum();
+    if total <= 0.0 {
+        return rng.random_range(0..weights.len());
     }
-    pub fn print_available_tasks(&self) {
-        println!("Available tasks:");
-        for i in 0..self.n_tasks {
-            if self.available_tasks[i as usize] {
-                println!(" {}", i);
-            }
+    let mut target = rng.random_range(0.0..total);
+    for (i, &weight) in weights.iter().enumerate() {
+        if target < weight {
+            return i;
         }
-        print!("\n");
+        target -= weight;
     }
+    weights.len() - 1
 }
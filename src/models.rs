@@ -0,0 +1,448 @@
+use std::fs;
+use std::io::{self, BufWriter, Write};
+
+use petgraph::stable_graph::NodeIndex;
+use petgraph::Direction;
+
+use crate::export::create_parent;
+use crate::schedule::{Schedule, ScheduledTask};
+use crate::utils::Utils;
+
+// CPLEX LP lines are limited in length, long sums are wrapped after this many terms
+const TERMS_PER_LINE: usize = 16;
+
+// Writes the instance as a disjunctive MILP in CPLEX LP format.
+// S_i is the start of task i, x_i_k assigns task i to worker k and for every pair of tasks that are not
+// ordered by the precedences y_i_j decides which one comes first when they share a worker.
+pub(crate) fn write_lp(utils: &Utils, n_workers: i32, path: &str) -> io::Result<()> {
+    create_parent(path)?;
+    let mut out = BufWriter::new(fs::File::create(path)?);
+    let n = utils.n_tasks as usize;
+    let m = n_workers.max(1) as usize;
+    let costs = &utils.costs_vec;
+    let horizon: i64 = costs.iter().map(|&c| c as i64).sum();
+    let edges = edges(utils);
+    let reach = reachability(utils);
+
+    writeln!(out, "\\ Task graph with {} tasks on {} workers", n, m)?;
+    writeln!(out, "Minimize")?;
+    writeln!(out, " obj: Cmax")?;
+    writeln!(out, "Subject To")?;
+    for i in 0..n {
+        let terms: Vec<String> = (0..m).map(|k| format!("x_{}_{}", i, k)).collect();
+        write_sum(&mut out, &format!("assign_{}", i), &terms, "= 1")?;
+    }
+    for &(i, j) in &edges {
+        writeln!(out, " prec_{}_{}: S_{} - S_{} >= {}", i, j, j, i, costs[i])?;
+    }
+    for (i, cost) in costs.iter().enumerate() {
+        writeln!(out, " end_{}: Cmax - S_{} >= {}", i, i, cost)?;
+    }
+    for i in 0..n {
+        for j in (i + 1)..n {
+            if reach[i][j] || reach[j][i] || costs[i] == 0 || costs[j] == 0 {
+                continue;
+            }
+            for k in 0..m {
+                // i before j when y_i_j = 1, j before i otherwise, only enforced when both run on worker k
+                writeln!(
+                    out,
+                    " seq_{i}_{j}_{k}_a: S_{i} - S_{j} + {M} y_{i}_{j} + {M} x_{i}_{k} + {M} x_{j}_{k} <= {}",
+                    3 * horizon - costs[i] as i64,
+                    M = horizon
+                )?;
+                writeln!(
+                    out,
+                    " seq_{i}_{j}_{k}_b: S_{j} - S_{i} - {M} y_{i}_{j} + {M} x_{i}_{k} + {M} x_{j}_{k} <= {}",
+                    2 * horizon - costs[j] as i64,
+                    M = horizon
+                )?;
+            }
+        }
+    }
+
+    writeln!(out, "Bounds")?;
+    for i in 0..n {
+        writeln!(out, " 0 <= S_{} <= {}", i, horizon)?;
+    }
+    writeln!(out, " 0 <= Cmax <= {}", horizon)?;
+    writeln!(out, "General")?;
+    for i in 0..n {
+        writeln!(out, " S_{}", i)?;
+    }
+    writeln!(out, " Cmax")?;
+    writeln!(out, "Binary")?;
+    for i in 0..n {
+        for k in 0..m {
+            writeln!(out, " x_{}_{}", i, k)?;
+        }
+    }
+    for i in 0..n {
+        for j in (i + 1)..n {
+            if !(reach[i][j] || reach[j][i] || costs[i] == 0 || costs[j] == 0) {
+                writeln!(out, " y_{}_{}", i, j)?;
+            }
+        }
+    }
+    writeln!(out, "End")?;
+    out.flush()
+}
+
+// Writes the instance as a MiniZinc model. Workers are explicit (w) with pairwise disjunctions,
+// the cumulative constraint is redundant but gives the solver much stronger propagation.
+// The output item prints the same S_i / x_i_k names as the LP model so read_solution handles both.
+pub(crate) fn write_minizinc(utils: &Utils, n_workers: i32, path: &str) -> io::Result<()> {
+    create_parent(path)?;
+    let mut out = BufWriter::new(fs::File::create(path)?);
+    let edges = edges(utils);
+    let join = |values: Vec<String>| values.join(", ");
+
+    writeln!(out, "include \"cumulative.mzn\";")?;
+    writeln!(out)?;
+    writeln!(out, "int: n = {};", utils.n_tasks)?;
+    writeln!(out, "int: m = {};", n_workers.max(1))?;
+    writeln!(out, "set of int: TASKS = 1..n;")?;
+    writeln!(out, "set of int: WORKERS = 1..m;")?;
+    writeln!(
+        out,
+        "array[TASKS] of int: d = [{}];",
+        join(utils.costs_vec.iter().map(|c| c.to_string()).collect())
+    )?;
+    writeln!(out, "int: n_edges = {};", edges.len())?;
+    writeln!(
+        out,
+        "array[1..n_edges] of TASKS: pre_from = [{}];",
+        join(edges.iter().map(|(i, _)| (i + 1).to_string()).collect())
+    )?;
+    writeln!(
+        out,
+        "array[1..n_edges] of TASKS: pre_to = [{}];",
+        join(edges.iter().map(|(_, j)| (j + 1).to_string()).collect())
+    )?;
+    writeln!(out, "int: horizon = sum(d);")?;
+    writeln!(out)?;
+    writeln!(out, "array[TASKS] of var 0..horizon: s;")?;
+    writeln!(out, "array[TASKS] of var WORKERS: w;")?;
+    writeln!(out, "var 0..horizon: makespan;")?;
+    writeln!(out)?;
+    writeln!(
+        out,
+        "constraint forall(e in 1..n_edges)(s[pre_from[e]] + d[pre_from[e]] <= s[pre_to[e]]);"
+    )?;
+    writeln!(
+        out,
+        "constraint forall(i in TASKS)(s[i] + d[i] <= makespan);"
+    )?;
+    writeln!(
+        out,
+        "constraint forall(i, j in TASKS where i < j /\\ d[i] > 0 /\\ d[j] > 0)(w[i] != w[j] \\/ s[i] + d[i] <= s[j] \\/ s[j] + d[j] <= s[i]);"
+    )?;
+    writeln!(out, "constraint cumulative(s, d, [1 | i in TASKS], m);")?;
+    writeln!(out)?;
+    writeln!(out, "solve minimize makespan;")?;
+    writeln!(out)?;
+    writeln!(
+        out,
+        "output [\"S_\\(i - 1) \\(s[i])\\n\" | i in TASKS] ++ [\"x_\\(i - 1)_\\(w[i] - 1) 1\\n\" | i in TASKS] ++ [\"Cmax \\(makespan)\\n\"];"
+    )?;
+    out.flush()
+}
+
+// Reads a solver solution back into a schedule. Accepts "name value" lines (Gurobi, HiGHS, CBC and
+// the MiniZinc output above) as well as CPLEX XML solutions. Starts are required for every task,
+// a task without an assignment variable goes to the first worker idle over its whole run, in start
+// order, and the solution is rejected when no worker is.
+pub(crate) fn read_solution(utils: &Utils, n_workers: i32, path: &str) -> io::Result<Schedule> {
+    let contents = fs::read_to_string(path)?;
+    let n = utils.n_tasks as usize;
+    let m = n_workers.max(1) as usize;
+    let mut starts: Vec<Option<i32>> = vec![None; n];
+    let mut workers: Vec<Option<usize>> = vec![None; n];
+
+    for (name, value) in variables(&contents) {
+        let value: f64 = match value.parse() {
+            Ok(value) => value,
+            Err(_) => continue,
+        };
+        let parts: Vec<&str> = name.split('_').collect();
+        match parts.as_slice() {
+            ["S", task] => {
+                if let Ok(task) = task.parse::<usize>() {
+                    if task < n {
+                        starts[task] = Some(value.round() as i32);
+                    }
+                }
+            }
+            ["x", task, worker] if value > 0.5 => {
+                if let (Ok(task), Ok(worker)) = (task.parse::<usize>(), worker.parse::<usize>()) {
+                    if task < n && worker < m {
+                        workers[task] = Some(worker);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let starts: Vec<i32> = starts
+        .iter()
+        .enumerate()
+        .map(|(task, start)| {
+            start.ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("no start time for task {} in {}", task, path),
+                )
+            })
+        })
+        .collect::<io::Result<_>>()?;
+
+    // tasks the solution gives no worker go to the first worker idle over their whole run, the
+    // reported assignments stay as they are
+    let finish = |task: usize| starts[task] + utils.costs_vec[task];
+    let mut busy: Vec<Vec<(i32, i32)>> = vec![Vec::new(); m];
+    for (task, worker) in workers.iter().enumerate() {
+        if let Some(worker) = worker {
+            busy[*worker].push((starts[task], finish(task)));
+        }
+    }
+    let mut order: Vec<usize> = (0..n).filter(|&task| workers[task].is_none()).collect();
+    order.sort_by_key(|&task| (starts[task], task));
+    for task in order {
+        let (start, end) = (starts[task], finish(task));
+        let worker = (0..m)
+            .find(|&k| busy[k].iter().all(|&(s, e)| e <= start || end <= s))
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("no worker free for task {} at {} in {}", task, start, path),
+                )
+            })?;
+        busy[worker].push((start, end));
+        workers[task] = Some(worker);
+    }
+
+    let entries: Vec<ScheduledTask> = (0..n)
+        .map(|task| ScheduledTask {
+            task: task as i32,
+            worker: workers[task].unwrap() as i32,
+            start: starts[task],
+            finish: starts[task] + utils.costs_vec[task],
+        })
+        .collect();
+    Ok(Schedule::new(utils.n_tasks, m as i32, &entries))
+}
+
+// (name, value) pairs of a solution file in any of the supported layouts
+fn variables(contents: &str) -> Vec<(String, String)> {
+    let mut pairs = Vec::new();
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with('<') {
+            if let (Some(name), Some(value)) = (attribute(line, "name"), attribute(line, "value")) {
+                pairs.push((name, value));
+            }
+            continue;
+        }
+        // the variable name is followed by its value, other columns (indices, reduced costs) are ignored
+        let tokens: Vec<&str> = line.split_whitespace().filter(|t| *t != "=").collect();
+        if let Some(pos) = tokens
+            .iter()
+            .position(|t| t.starts_with("S_") || t.starts_with("x_") || *t == "Cmax")
+        {
+            if let Some(value) = tokens.get(pos + 1) {
+                pairs.push((
+                    tokens[pos].to_string(),
+                    value.trim_end_matches(';').to_string(),
+                ));
+            }
+        }
+    }
+    pairs
+}
+
+fn attribute(line: &str, key: &str) -> Option<String> {
+    let pattern = format!("{}=\"", key);
+    let start = line.find(&pattern)? + pattern.len();
+    let end = line[start..].find('"')? + start;
+    Some(line[start..end].to_string())
+}
+
+fn write_sum(out: &mut impl Write, name: &str, terms: &[String], rhs: &str) -> io::Result<()> {
+    write!(out, " {}:", name)?;
+    for (i, term) in terms.iter().enumerate() {
+        if i > 0 && i % TERMS_PER_LINE == 0 {
+            write!(out, "\n   ")?;
+        }
+        if i == 0 {
+            write!(out, " {}", term)?;
+        } else {
+            write!(out, " + {}", term)?;
+        }
+    }
+    writeln!(out, " {}", rhs)
+}
+
+fn edges(utils: &Utils) -> Vec<(usize, usize)> {
+    utils
+        .di_graph
+        .edge_indices()
+        .filter_map(|edge| utils.di_graph.edge_endpoints(edge))
+        .map(|(source, target)| (source.index(), target.index()))
+        .collect()
+}
+
+// reach[i][j] is true when task j (transitively) depends on task i, such pairs can never overlap
+fn reachability(utils: &Utils) -> Vec<Vec<bool>> {
+    let n = utils.n_tasks as usize;
+    let order =
+        petgraph::algo::toposort(&utils.di_graph, None).expect("task graph must be acyclic");
+    let mut reach = vec![vec![false; n]; n];
+    for node in order.iter().rev() {
+        let task = node.index();
+        let succs: Vec<usize> = utils
+            .di_graph
+            .neighbors_directed(NodeIndex::new(task), Direction::Outgoing)
+            .map(|s| s.index())
+            .collect();
+        for succ in succs {
+            reach[task][succ] = true;
+            let (from, to) = if task < succ {
+                let (a, b) = reach.split_at_mut(succ);
+                (&mut a[task], &b[0])
+            } else {
+                let (a, b) = reach.split_at_mut(task);
+                (&mut b[0], &a[succ])
+            };
+            for (r, &s) in from.iter_mut().zip(to.iter()) {
+                *r |= s;
+            }
+        }
+    }
+    reach
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils;
+
+    // three independent tasks of 2 between the entry and exit tasks
+    const GRAPH: &str = "3\n0 0 0\n1 2 1 0\n2 2 1 0\n3 2 1 0\n4 0 3 1 2 3\n";
+
+    fn path(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!(
+                "task_scheduling_models_{}_{}",
+                std::process::id(),
+                name
+            ))
+            .to_string_lossy()
+            .to_string()
+    }
+
+    fn read(contents: &str, name: &str) -> io::Result<Schedule> {
+        let path = path(name);
+        fs::write(&path, contents).unwrap();
+        let schedule = read_solution(&utils::from_stg(GRAPH), 2, &path);
+        let _ = fs::remove_file(&path);
+        schedule
+    }
+
+    fn workers(schedule: &Schedule) -> Vec<i32> {
+        schedule.tasks.iter().map(|t| t.unwrap().worker).collect()
+    }
+
+    #[test]
+    fn lp_orders_only_the_unordered_pairs() {
+        let path = path("model.lp");
+        write_lp(&utils::from_stg(GRAPH), 2, &path).unwrap();
+        let lp = fs::read_to_string(&path).unwrap();
+        let _ = fs::remove_file(&path);
+        assert!(lp.contains(" obj: Cmax"));
+        assert!(lp.contains(" assign_0: x_0_0 + x_0_1 = 1"));
+        assert!(lp.contains(" prec_0_1: S_1 - S_0 >= 0"));
+        assert!(lp.contains(" prec_1_4: S_4 - S_1 >= 2"));
+        assert!(lp.contains(" end_3: Cmax - S_3 >= 2"));
+        assert!(lp.contains(" 0 <= Cmax <= 6"));
+        // the three tasks of 2 pairwise on each worker, both directions
+        assert_eq!(lp.lines().filter(|l| l.starts_with(" seq_")).count(), 12);
+        let binaries: Vec<&str> = lp.lines().filter(|l| l.starts_with(" y_")).collect();
+        assert_eq!(binaries, vec![" y_1_2", " y_1_3", " y_2_3"]);
+        assert!(lp.trim_end().ends_with("End"));
+    }
+
+    #[test]
+    fn minizinc_lists_durations_and_precedences() {
+        let path = path("model.mzn");
+        write_minizinc(&utils::from_stg(GRAPH), 2, &path).unwrap();
+        let mzn = fs::read_to_string(&path).unwrap();
+        let _ = fs::remove_file(&path);
+        assert!(mzn.contains("int: n = 5;"));
+        assert!(mzn.contains("int: m = 2;"));
+        assert!(mzn.contains("array[TASKS] of int: d = [0, 2, 2, 2, 0];"));
+        assert!(mzn.contains("int: n_edges = 6;"));
+        assert!(mzn.contains("solve minimize makespan;"));
+    }
+
+    #[test]
+    fn reads_name_value_solutions() {
+        let solution = "S_0 0\nS_1 0\nS_2 0\nS_3 2\nS_4 4\nx_0_0 1\nx_1_1 1\nx_2_0 1\nx_3_1 1\nx_4_0 1\nCmax 4\n";
+        let schedule = read(solution, "plain.sol").unwrap();
+        assert_eq!(schedule.makespan, 4);
+        assert_eq!(workers(&schedule), vec![0, 1, 0, 1, 0]);
+        assert_eq!(schedule.tasks[3].unwrap().finish, 4);
+    }
+
+    #[test]
+    fn reads_cplex_xml_solutions() {
+        let mut solution = String::from("<?xml version = \"1.0\"?>\n<variables>\n");
+        for (name, value) in [
+            ("S_0", 0),
+            ("S_1", 0),
+            ("S_2", 2),
+            ("S_3", 0),
+            ("S_4", 4),
+            ("x_0_1", 1),
+            ("x_1_0", 1),
+            ("x_2_0", 1),
+            ("x_3_1", 1),
+            ("x_4_1", 1),
+        ] {
+            solution.push_str(&format!(
+                "  <variable name=\"{}\" index=\"0\" value=\"{}\"/>\n",
+                name, value
+            ));
+        }
+        solution.push_str("</variables>\n");
+        let schedule = read(&solution, "cplex.sol").unwrap();
+        assert_eq!(schedule.makespan, 4);
+        assert_eq!(workers(&schedule), vec![1, 0, 0, 1, 1]);
+    }
+
+    #[test]
+    fn fills_only_the_missing_workers() {
+        // the reported workers are the other way round from a first free assignment
+        let solution = "S_0 0\nS_1 0\nS_2 0\nS_3 2\nS_4 4\nx_1_1 1\nx_2_0 1\n";
+        let schedule = read(solution, "partial.sol").unwrap();
+        assert_eq!(workers(&schedule), vec![0, 1, 0, 0, 0]);
+    }
+
+    #[test]
+    fn rejects_a_task_without_a_free_worker() {
+        let solution = "S_0 0\nS_1 0\nS_2 0\nS_3 1\nS_4 4\nx_1_0 1\nx_2_1 1\n";
+        let error = read(solution, "crowded.sol").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(error.to_string().contains("no worker free for task 3"));
+    }
+
+    #[test]
+    fn rejects_a_missing_start() {
+        let error = read("S_0 0\nS_1 0\nS_2 0\nS_4 4\n", "no_start.sol").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(error.to_string().contains("no start time for task 3"));
+    }
+}
@@ -0,0 +1,90 @@
+use std::ops::ControlFlow;
+
+use crate::schedule::Schedule;
+use crate::stopping::StopReason;
+use crate::telemetry::EpochRecord;
+
+/// A [`Event::Stagnation`] is sent every this many epochs without a better schedule.
+pub const STAGNATION_EVENT_EPOCHS: i32 = 100;
+
+/// Something that happened to one colony of a run, see [`Observer`].
+///
+/// Every event carries the telemetry record of the epoch it happened in, with the makespans,
+/// the parameters and the pheromone stats of that epoch.
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub enum Event<'a> {
+    /// A colony finished an epoch, sent after the other events of the epoch.
+    EpochFinished { record: &'a EpochRecord },
+    /// The epoch built a schedule strictly better than the best of its colony.
+    NewBest {
+        record: &'a EpochRecord,
+        schedule: &'a Schedule,
+    },
+    /// The epoch ran with other parameters than the one before, after an adaptation or once
+    /// the base chance is drawn.
+    ParameterChange {
+        previous: &'a EpochRecord,
+        record: &'a EpochRecord,
+    },
+    /// Sent every [`STAGNATION_EVENT_EPOCHS`] epochs without a better schedule.
+    Stagnation {
+        record: &'a EpochRecord,
+        stagnant_epochs: i32,
+    },
+    /// The colony stopped, with its best schedule, None when it never built one. The record is
+    /// the last epoch the colony ran in this run.
+    ColonyFinished {
+        colony_id: i32,
+        reason: StopReason,
+        record: &'a EpochRecord,
+        schedule: Option<&'a Schedule>,
+    },
+}
+
+/// Receives the events of a run, added with
+/// [`SolveOptions::observe`](crate::SolveOptions::observe).
+///
+/// The colonies run on their own threads and call the observers from there, so an observer
+/// sees the events of different colonies interleaved. Returning [`ControlFlow::Break`] stops
+/// the whole run after the current epoch of every colony, with [`StopReason::Observer`].
+///
+/// Closures taking an `&Event` are observers:
+///
+/// ```no_run
+/// use std::ops::ControlFlow;
+/// use task_scheduling::{solve_with, Event, Instance, SolveOptions, SolverConfig};
+///
+/// let instance = Instance::load("graphs/rand0000.stg", false).unwrap();
+/// let good_enough = |event: &Event| match event {
+///     Event::NewBest { record, .. } if record.best <= 110 => ControlFlow::Break(()),
+///     _ => ControlFlow::Continue(()),
+/// };
+/// let options = SolveOptions::new().observe(&good_enough);
+/// let result = solve_with(&instance, &SolverConfig::new().workers(16), &options).unwrap();
+/// ```
+pub trait Observer: Sync {
+    fn on_event(&self, event: &Event<'_>) -> ControlFlow<()>;
+}
+
+impl<F> Observer for F
+where
+    F: Fn(&Event<'_>) -> ControlFlow<()> + Sync,
+{
+    fn on_event(&self, event: &Event<'_>) -> ControlFlow<()> {
+        self(event)
+    }
+}
+
+impl Event<'_> {
+    /// The telemetry record of the epoch the event happened in.
+    pub fn record(&self) -> &EpochRecord {
+        match *self {
+            Event::EpochFinished { record }
+            | Event::NewBest { record, .. }
+            | Event::ParameterChange { record, .. }
+            | Event::Stagnation { record, .. }
+            | Event::ColonyFinished { record, .. } => record,
+        }
+    }
+}
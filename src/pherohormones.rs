@@ -1,6 +1,10 @@
+use std::fmt;
 use std::fs::{self, File};
-use std::io::Write;
-use std::process::Command;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+use crate::export::{self, json_array, JsonObject};
+use crate::telemetry::PheromoneStats;
 
 use petgraph::{
     stable_graph::{EdgeIndex, NodeIndex, StableDiGraph},
@@ -22,7 +26,7 @@ impl Pherohormones {
     pub fn new(n_tasks: i32, output_dir: &str) -> Pherohormones {
         Pherohormones {
             di_graph: StableDiGraph::new(),
-            n_tasks: n_tasks,
+            n_tasks,
             output_dir: output_dir.to_string(),
 
             pheromones_sum: 0.0,
@@ -65,6 +69,7 @@ impl Pherohormones {
 
     pub fn evaporate_pherohormones(&mut self, evaporation_rate: f64) {
         let edges: Vec<EdgeIndex> = self.di_graph.edge_indices().collect();
+        let mut removed = false;
         for edge in edges {
             // returns the pointer to the weight ,i can change it directly
 
@@ -72,11 +77,22 @@ impl Pherohormones {
                 *weight = (*weight - evaporation_rate).max(0.0);
                 if *weight <= 0.00 {
                     self.di_graph.remove_edge(edge);
+                    removed = true;
                 }
             }
             self.update_pherohormones_sum();
             // self.print_pherohormones();
         }
+        // the slots of the evaporated trails are closed up, so the graph is always the one
+        // from_edges rebuilds from a checkpoint
+        if removed {
+            *self = Pherohormones::from_edges(
+                self.n_tasks,
+                &self.output_dir,
+                &self.edges(),
+                self.pheromones_sum,
+            );
+        }
     }
 
     // return avauilabe paths from a task related to trhe pherohormones
@@ -111,17 +127,70 @@ impl Pherohormones {
         //println!("Pheromones sum: {}", self.pheromones_sum);
     }
 
-    pub fn print_pherohormones(&mut self) {
-        for edge in self.di_graph.edge_indices() {
-            let (source, target) = self.di_graph.edge_endpoints(edge).unwrap();
-            let weight = self.di_graph.edge_weight(edge).unwrap();
-            println!(
-                "Edge from {} to {} with weight {}",
-                source.index() + 1,
-                target.index() + 1,
-                weight
+    // Every trail in edge index order. A graph the trails were added to in this order, which
+    // is what evaporation leaves, evolves exactly like this one: the order decides which trails
+    // an ant sees first and how the sum is added up.
+    pub fn edges(&self) -> Vec<(i32, i32, f64)> {
+        self.di_graph
+            .edge_indices()
+            .map(|edge| {
+                let (source, target) = self.di_graph.edge_endpoints(edge).unwrap();
+                (
+                    source.index() as i32,
+                    target.index() as i32,
+                    self.di_graph[edge],
+                )
+            })
+            .collect()
+    }
+
+    // The trails added one by one to a fresh graph, the sum is kept as it was added up
+    pub fn from_edges(
+        n_tasks: i32,
+        output_dir: &str,
+        edges: &[(i32, i32, f64)],
+        pheromones_sum: f64,
+    ) -> Pherohormones {
+        let mut ph = Pherohormones::new(n_tasks, output_dir);
+        ph.initialize();
+        for &(source, target, weight) in edges {
+            ph.di_graph.add_edge(
+                NodeIndex::new(source as usize),
+                NodeIndex::new(target as usize),
+                weight,
             );
         }
+        ph.pheromones_sum = pheromones_sum;
+        ph
+    }
+
+    // sum, spread and concentration of the trails for the telemetry log
+    pub(crate) fn stats(&self) -> PheromoneStats {
+        let mut stats = PheromoneStats {
+            n_edges: self.di_graph.edge_count(),
+            ..PheromoneStats::default()
+        };
+        if stats.n_edges == 0 {
+            return stats;
+        }
+        stats.min = f64::MAX;
+        for &weight in self.di_graph.edge_weights() {
+            stats.sum += weight;
+            stats.min = stats.min.min(weight);
+            stats.max = stats.max.max(weight);
+        }
+        if stats.sum > 0.0 {
+            stats.entropy = self
+                .di_graph
+                .edge_weights()
+                .filter(|&&weight| weight > 0.0)
+                .map(|&weight| {
+                    let p = weight / stats.sum;
+                    -p * p.ln()
+                })
+                .sum();
+        }
+        stats
     }
 
     pub fn to_gexf(&self, cycle: i32) -> String {
@@ -134,7 +203,6 @@ impl Pherohormones {
         // Thickness configuration - adjust these values to control edge sizes
         let thickness_min = 0.8; // Minimum visible thickness
         let thickness_max = 3.0; // Maximum thickness
-        let thickness_range = thickness_max - thickness_min;
 
         gexf.push_str(
             r##"<?xml version="1.0" encoding="UTF-8"?>
@@ -193,20 +261,7 @@ impl Pherohormones {
             if let Some((source, target)) = self.di_graph.edge_endpoints(edge) {
                 if let Some(&weight) = self.di_graph.edge_weight(edge) {
                     let t = (weight - min_weight) / weight_range;
-
-                    // Non-linear scaling to compress high values
-                    let scaled_t = t.powf(0.6); // Experiment with exponent (0.5-0.8)
-
-                    // Calculate thickness in configured range
-                    let thickness = thickness_min + (scaled_t * thickness_range);
-
-                    // Color gradient from blue to red
-                    let hue = (t * 240.0) as u8; // 0°(blue) to 240°(red)
-                    let color = match hue {
-                        0..=120 => (120 - hue, hue, 160),        // Blue to cyan
-                        121..=240 => (hue - 120, 240 - hue, 60), // Magenta to red
-                        _ => (0, 0, 255),
-                    };
+                    let (thickness, color) = edge_style(t, thickness_min, thickness_max);
 
                     gexf.push_str(&format!(
                         r##"
@@ -241,16 +296,442 @@ impl Pherohormones {
         gexf
     }
 
-    // Add this new save method
-    pub fn save_gephi(&self, iteration: i32) -> std::io::Result<()> {
+    // Snapshots are named by epoch only, the makespan of the trails goes in the description.
+    // Returns the path written.
+    pub fn save_gephi(&self, epoch: i32, cycles: i32) -> std::io::Result<String> {
         fs::create_dir_all(&self.output_dir)?;
-        let path = format!("{}/gephi_{:04}.gexf", self.output_dir, iteration);
+        let path = format!("{}/gephi_{:06}.gexf", self.output_dir, epoch);
         let mut file = File::create(&path)?;
 
         // Write BOM for UTF-8 compatibility
         file.write_all(b"\xEF\xBB\xBF")?;
-        file.write_all(self.to_gexf(iteration).as_bytes())?;
+        file.write_all(self.to_gexf(cycles).as_bytes())?;
+
+        Ok(path)
+    }
+
+    // Graphviz snapshot of the trails, edge penwidth and colour follow the weight like in the GEXF.
+    // Only tasks touched by a trail are drawn so large graphs stay readable.
+    pub fn to_dot(&self, label: &str) -> String {
+        let weights: Vec<f64> = self.di_graph.edge_weights().cloned().collect();
+        let max_weight = weights.iter().fold(0.0_f64, |a, &b| a.max(b));
+        let min_weight = weights.iter().fold(f64::MAX, |a, &b| a.min(b));
+        let weight_range = (max_weight - min_weight).max(f64::EPSILON);
+
+        let mut dot = String::new();
+        dot.push_str("digraph pherohormones {\n");
+        dot.push_str(&format!(
+            "  graph [label=\"{}\", labelloc=t, rankdir=LR, overlap=false];\n",
+            label
+        ));
+        dot.push_str("  node [shape=circle, style=filled, fillcolor=\"#c0c0c0\", fontsize=10];\n");
+
+        for node in self.di_graph.node_indices() {
+            let touched = self
+                .di_graph
+                .neighbors_directed(node, Direction::Outgoing)
+                .next()
+                .is_some()
+                || self
+                    .di_graph
+                    .neighbors_directed(node, Direction::Incoming)
+                    .next()
+                    .is_some();
+            if touched {
+                // 0-based like the task graph DOT and the schedule files
+                dot.push_str(&format!("  {0} [label=\"T{0}\"];\n", node.index()));
+            }
+        }
+        for edge in self.di_graph.edge_indices() {
+            let (source, target) = self.di_graph.edge_endpoints(edge).unwrap();
+            let weight = self.di_graph[edge];
+            let t = (weight - min_weight) / weight_range;
+            let (penwidth, color) = edge_style(t, 0.5, 5.0);
+            dot.push_str(&format!(
+                "  {} -> {} [penwidth={:.2}, color=\"#{:02x}{:02x}{:02x}\", tooltip=\"{:.4}\"];\n",
+                source.index(),
+                target.index(),
+                penwidth,
+                color.0,
+                color.1,
+                color.2,
+                weight
+            ));
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    // frames/frame_NNNN.dot, the numbering generate_animation.sh expects
+    pub fn save_dot(&self, frame: i32, label: &str) -> std::io::Result<String> {
+        let frames_dir = format!("{}/frames", self.output_dir);
+        fs::create_dir_all(&frames_dir)?;
+        let path = format!("{}/frame_{:04}.dot", frames_dir, frame);
+        fs::write(&path, self.to_dot(label))?;
+        Ok(path)
+    }
+    // The trails alone, without the graph layout a checkpoint keeps, for a colony to start from.
+    // Binary is 16 bytes a trail, JSON is there to be read by other tools.
+    pub(crate) fn save_trails(&self, path: &str, format: TrailFormat) -> io::Result<()> {
+        export::create_parent(path)?;
+        match format {
+            TrailFormat::Binary => fs::write(path, self.trails_to_bytes()),
+            TrailFormat::Json => fs::write(path, self.trails_to_json()),
+        }
+    }
+
+    // Either format, told apart by the magic bytes of the binary one. The trails come back on
+    // a graph of the task count they were saved with, which can be no more than max_tasks: they
+    // come from the same instance or a smaller variant of it.
+    pub fn load_trails(path: &str, max_tasks: i32) -> io::Result<Pherohormones> {
+        let bytes = fs::read(path)?;
+        let trails = if bytes.starts_with(TRAILS_MAGIC) {
+            Pherohormones::trails_from_bytes(&bytes, max_tasks)
+        } else {
+            String::from_utf8(bytes)
+                .map_err(|_| "neither binary nor JSON trails".to_string())
+                .and_then(|text| Pherohormones::trails_from_json(&text, max_tasks))
+        };
+        trails.map_err(|message| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path, message))
+        })
+    }
+
+    // Warm start: the trails of another run between tasks this graph also has, the ids are
+    // taken to mean the same tasks. Returns how many trails were kept.
+    pub fn warm_start(&mut self, trails: &Pherohormones) -> usize {
+        let mut kept = 0;
+        for edge in trails.di_graph.edge_indices() {
+            let (source, target) = trails.di_graph.edge_endpoints(edge).unwrap();
+            let weight = trails.di_graph[edge];
+            if source.index() < self.n_tasks as usize
+                && target.index() < self.n_tasks as usize
+                && weight > 0.0
+            {
+                self.deposit_pherohormones(source.index() as i32, target.index() as i32, weight);
+                kept += 1;
+            }
+        }
+        self.update_pherohormones_sum();
+        kept
+    }
+
+    // magic, version, task count and trail count, then source, target (u32) and weight (f64)
+    // for every trail, all little endian
+    fn trails_to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(13 + 16 * self.di_graph.edge_count());
+        bytes.extend_from_slice(TRAILS_MAGIC);
+        bytes.push(TRAILS_VERSION);
+        bytes.extend_from_slice(&(self.n_tasks as u32).to_le_bytes());
+        bytes.extend_from_slice(&(self.di_graph.edge_count() as u32).to_le_bytes());
+        for edge in self.di_graph.edge_indices() {
+            let (source, target) = self.di_graph.edge_endpoints(edge).unwrap();
+            bytes.extend_from_slice(&(source.index() as u32).to_le_bytes());
+            bytes.extend_from_slice(&(target.index() as u32).to_le_bytes());
+            bytes.extend_from_slice(&self.di_graph[edge].to_le_bytes());
+        }
+        bytes
+    }
+
+    fn trails_from_bytes(bytes: &[u8], max_tasks: i32) -> Result<Pherohormones, String> {
+        let mut rest = &bytes[TRAILS_MAGIC.len()..];
+        let mut take = |n: usize| -> Result<&[u8], String> {
+            if rest.len() < n {
+                return Err("truncated trails file".to_string());
+            }
+            let (head, tail) = rest.split_at(n);
+            rest = tail;
+            Ok(head)
+        };
+        let version = take(1)?[0];
+        if version != TRAILS_VERSION {
+            return Err(format!("unsupported trails version {}", version));
+        }
+        let u32_at = |b: &[u8]| u32::from_le_bytes(b.try_into().unwrap());
+        // checked before the edges so a corrupt count never sizes the graph
+        let n_tasks = u32_at(take(4)?);
+        let n_tasks = i32::try_from(n_tasks)
+            .ok()
+            .filter(|&n_tasks| n_tasks <= max_tasks)
+            .ok_or_else(|| too_many_tasks(n_tasks as i64, max_tasks))?;
+        let n_edges = u32_at(take(4)?);
+        // not sized from the count, a corrupt one ends as a truncated file instead
+        let mut edges = Vec::new();
+        for _ in 0..n_edges {
+            let source = u32_at(take(4)?);
+            let target = u32_at(take(4)?);
+            let weight = f64::from_le_bytes(take(8)?.try_into().unwrap());
+            edges.push((source as i32, target as i32, weight));
+        }
+        Pherohormones::from_trails(n_tasks, &edges)
+    }
+
+    // {"format":"pherohormones","version":1,"n_tasks":N,"edges":[[source,target,weight],...]}
+    fn trails_to_json(&self) -> String {
+        let edges = self.di_graph.edge_indices().map(|edge| {
+            let (source, target) = self.di_graph.edge_endpoints(edge).unwrap();
+            format!(
+                "[{},{},{}]",
+                source.index(),
+                target.index(),
+                self.di_graph[edge]
+            )
+        });
+        JsonObject::new()
+            .string("format", "pherohormones")
+            .field("version", TRAILS_VERSION)
+            .field("n_tasks", self.n_tasks)
+            .raw("edges", json_array(edges))
+            .to_string()
+    }
+
+    // reads back the layout above, whitespace is allowed anywhere
+    fn trails_from_json(text: &str, max_tasks: i32) -> Result<Pherohormones, String> {
+        let text: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+        let value_of = |key: &str| -> Result<&str, String> {
+            let start = text
+                .find(&format!("\"{}\":", key))
+                .ok_or_else(|| format!("missing {}", key))?
+                + key.len()
+                + 3;
+            let end = text[start..]
+                .find([',', '}'])
+                .map_or(text.len(), |end| start + end);
+            Ok(&text[start..end])
+        };
+        if value_of("format")? != "\"pherohormones\"" {
+            return Err("not a pheromone trails file".to_string());
+        }
+        let version = value_of("version")?;
+        if version != TRAILS_VERSION.to_string() {
+            return Err(format!("unsupported trails version {}", version));
+        }
+        let n_tasks: i32 = value_of("n_tasks")?
+            .parse()
+            .map_err(|_| "invalid n_tasks".to_string())?;
+        if n_tasks < 0 {
+            return Err("invalid n_tasks".to_string());
+        }
+        if n_tasks > max_tasks {
+            return Err(too_many_tasks(n_tasks as i64, max_tasks));
+        }
+        let start = text.find("\"edges\":[").ok_or("missing edges")? + 9;
+        let end = text[start..]
+            .find("]]")
+            .map_or(start, |end| start + end + 1);
+        let mut edges = Vec::new();
+        for edge in text[start..end].split("],[") {
+            let edge = edge.trim_matches(['[', ']']);
+            if edge.is_empty() {
+                continue;
+            }
+            let invalid = || format!("invalid trail [{}]", edge);
+            let fields: Vec<&str> = edge.split(',').collect();
+            let [source, target, weight] = fields.as_slice() else {
+                return Err(invalid());
+            };
+            edges.push((
+                source.parse().map_err(|_| invalid())?,
+                target.parse().map_err(|_| invalid())?,
+                weight.parse().map_err(|_| invalid())?,
+            ));
+        }
+        Pherohormones::from_trails(n_tasks, &edges)
+    }
+
+    fn from_trails(n_tasks: i32, edges: &[(i32, i32, f64)]) -> Result<Pherohormones, String> {
+        let mut ph = Pherohormones::new(n_tasks, "");
+        ph.initialize();
+        for &(source, target, weight) in edges {
+            if source < 0 || target < 0 || source >= n_tasks || target >= n_tasks {
+                return Err(format!(
+                    "trail {} -> {} outside the {} tasks",
+                    source, target, n_tasks
+                ));
+            }
+            ph.deposit_pherohormones(source, target, weight);
+        }
+        ph.update_pherohormones_sum();
+        Ok(ph)
+    }
+}
+
+fn too_many_tasks(n_tasks: i64, max_tasks: i32) -> String {
+    format!(
+        "trails over {} tasks, more than the {} of the instance",
+        n_tasks, max_tasks
+    )
+}
+
+const TRAILS_MAGIC: &[u8; 4] = b"PHRM";
+const TRAILS_VERSION: u8 = 1;
+
+// How the trails of a colony are written at the end of a run, `warm_start` reads either
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TrailFormat {
+    Binary,
+    Json,
+}
+
+impl TrailFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            TrailFormat::Binary => "bin",
+            TrailFormat::Json => "json",
+        }
+    }
+}
+
+impl fmt::Display for TrailFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrailFormat::Binary => write!(f, "binary"),
+            TrailFormat::Json => write!(f, "json"),
+        }
+    }
+}
+
+impl FromStr for TrailFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<TrailFormat, String> {
+        match s {
+            "binary" => Ok(TrailFormat::Binary),
+            "json" => Ok(TrailFormat::Json),
+            _ => Err(format!("invalid trail format '{}'", s)),
+        }
+    }
+}
+
+// thickness and colour of an edge from its weight normalised to 0..1
+fn edge_style(t: f64, thickness_min: f64, thickness_max: f64) -> (f64, (u8, u8, u8)) {
+    // Non-linear scaling to compress high values
+    let scaled_t = t.powf(0.6); // Experiment with exponent (0.5-0.8)
+
+    // Calculate thickness in configured range
+    let thickness = thickness_min + (scaled_t * (thickness_max - thickness_min));
+
+    (thickness, weight_color(t))
+}
+
+// Color gradient from blue to red for a weight normalised to 0..1
+pub(crate) fn weight_color(t: f64) -> (u8, u8, u8) {
+    let hue = (t * 240.0) as u8; // 0°(blue) to 240°(red)
+    match hue {
+        0..=120 => (120 - hue, hue, 160),        // Blue to cyan
+        121..=240 => (hue - 120, 240 - hue, 60), // Magenta to red
+        _ => (0, 0, 255),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!(
+                "task_scheduling_trails_{}_{}",
+                std::process::id(),
+                name
+            ))
+            .to_string_lossy()
+            .to_string()
+    }
+
+    fn trails() -> Pherohormones {
+        let mut ph = Pherohormones::new(5, "");
+        ph.initialize();
+        ph.deposit_pherohormones(0, 1, 0.5);
+        ph.deposit_pherohormones(1, 3, 0.25);
+        ph.deposit_pherohormones(0, 1, 0.125);
+        ph.deposit_pherohormones(4, 2, 1.0 / 3.0);
+        ph.update_pherohormones_sum();
+        ph
+    }
+
+    #[test]
+    fn trails_survive_both_formats() {
+        let ph = trails();
+        for format in [TrailFormat::Binary, TrailFormat::Json] {
+            let path = path(&format!("round_trip.{}", format.extension()));
+            ph.save_trails(&path, format).unwrap();
+            let loaded = Pherohormones::load_trails(&path, 5).unwrap();
+            let _ = fs::remove_file(&path);
+            assert_eq!(loaded.n_tasks, 5);
+            assert_eq!(loaded.edges(), ph.edges());
+            assert_eq!(loaded.pheromones_sum, ph.pheromones_sum);
+        }
+    }
+
+    #[test]
+    fn trails_of_a_larger_instance_are_rejected() {
+        let ph = trails();
+        for format in [TrailFormat::Binary, TrailFormat::Json] {
+            let path = path(&format!("larger.{}", format.extension()));
+            ph.save_trails(&path, format).unwrap();
+            let error = Pherohormones::load_trails(&path, 4).err().unwrap();
+            let _ = fs::remove_file(&path);
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+            assert!(error
+                .to_string()
+                .ends_with("trails over 5 tasks, more than the 4 of the instance"));
+        }
+    }
+
+    #[test]
+    fn corrupt_task_counts_never_size_the_graph() {
+        for n_tasks in [u32::MAX, i32::MAX as u32] {
+            let mut bytes = TRAILS_MAGIC.to_vec();
+            bytes.push(TRAILS_VERSION);
+            bytes.extend_from_slice(&n_tasks.to_le_bytes());
+            bytes.extend_from_slice(&0u32.to_le_bytes());
+            let error = Pherohormones::trails_from_bytes(&bytes, 300).err().unwrap();
+            assert_eq!(error, too_many_tasks(n_tasks as i64, 300));
+        }
+        let json = "{\"format\":\"pherohormones\",\"version\":1,\"n_tasks\":-1,\"edges\":[]}";
+        assert!(Pherohormones::trails_from_json(json, 300).is_err());
+    }
+
+    #[test]
+    fn broken_trail_files() {
+        let bytes = trails().trails_to_bytes();
+        let error = Pherohormones::trails_from_bytes(&bytes[..bytes.len() - 1], 5).err();
+        assert_eq!(error.as_deref(), Some("truncated trails file"));
+
+        let mut bytes = bytes;
+        bytes[4] = TRAILS_VERSION + 1;
+        assert!(Pherohormones::trails_from_bytes(&bytes, 5).is_err());
+
+        let outside = "{\"format\":\"pherohormones\",\"version\":1,\"n_tasks\":2,\"edges\":[[0,1,0.5],[1,2,0.5]]}";
+        let error = Pherohormones::trails_from_json(outside, 5).err();
+        assert_eq!(error.as_deref(), Some("trail 1 -> 2 outside the 2 tasks"));
+
+        let other = "{\"format\":\"gexf\",\"version\":1}";
+        assert!(Pherohormones::trails_from_json(other, 5).is_err());
+    }
+
+    #[test]
+    fn warm_start_keeps_the_shared_tasks() {
+        let mut smaller = Pherohormones::new(3, "");
+        smaller.initialize();
+        // 1 -> 3 and 4 -> 2 are outside the three tasks
+        assert_eq!(smaller.warm_start(&trails()), 1);
+        assert_eq!(smaller.edges(), vec![(0, 1, 0.625)]);
+        assert_eq!(smaller.pheromones_sum, 0.625);
+    }
 
-        Ok(())
+    #[test]
+    fn evaporation_closes_up_the_edge_list() {
+        let mut ph = trails();
+        ph.evaporate_pherohormones(0.3);
+        // 1 -> 3 evaporated, the rest keep their order
+        let edges = ph.edges();
+        assert_eq!(edges.len(), 2);
+        assert_eq!((edges[0].0, edges[0].1), (0, 1));
+        assert_eq!((edges[1].0, edges[1].1), (4, 2));
+        let rebuilt = Pherohormones::from_edges(5, "", &edges, ph.pheromones_sum);
+        assert_eq!(rebuilt.di_graph.edge_indices().count(), 2);
+        assert_eq!(rebuilt.edges(), edges);
     }
 }
@@ -0,0 +1,548 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use petgraph::stable_graph::NodeIndex;
+
+use crate::export;
+use crate::gantt;
+use crate::schedule::{Schedule, ScheduledTask};
+use crate::telemetry::{self, EpochRecord};
+use crate::utils::Utils;
+
+// reads one parameter of a telemetry record
+type Parameter = fn(&EpochRecord) -> f64;
+
+// Telemetry of one colony as found in the results directory
+pub(crate) struct ColonyLog {
+    // path of the log relative to the results directory, without the extension
+    pub label: String,
+    pub records: Vec<EpochRecord>,
+}
+
+impl ColonyLog {
+    pub fn last(&self) -> &EpochRecord {
+        self.records.last().unwrap()
+    }
+
+    // first epoch that reached the final best makespan
+    pub fn best_epoch(&self) -> i32 {
+        let best = self.last().best;
+        self.records
+            .iter()
+            .find(|record| record.best == best)
+            .map(|record| record.epoch)
+            .unwrap_or(0)
+    }
+
+    pub fn mean_construction_ms(&self) -> f64 {
+        self.records.iter().map(|r| r.construction_ms).sum::<f64>() / self.records.len() as f64
+    }
+}
+
+pub(crate) struct Report {
+    pub colonies: Vec<ColonyLog>,
+    // the best schedule found under the results directory and where it came from
+    pub best_schedule: Option<(String, Schedule)>,
+    pub files: Vec<String>,
+}
+
+// Reads every telemetry log under results_dir and writes the plots, the summary tables and the
+// Gantt chart of the best schedule into out_dir
+pub(crate) fn generate(results_dir: &str, out_dir: &str) -> io::Result<Report> {
+    let mut csv_files = Vec::new();
+    find_csv(Path::new(results_dir), Path::new(out_dir), &mut csv_files)?;
+    csv_files.sort();
+
+    let mut colonies = Vec::new();
+    let mut best: Option<(String, Utils, Schedule)> = None;
+    for path in &csv_files {
+        let path_str = path.to_string_lossy().to_string();
+        let relative = path
+            .strip_prefix(results_dir)
+            .unwrap_or(path)
+            .with_extension("")
+            .to_string_lossy()
+            .to_string();
+        if path
+            .file_name()
+            .is_some_and(|name| name == "best_schedule.csv")
+        {
+            let (utils, schedule) = read_schedule(&path_str)?;
+            if !schedule.is_empty()
+                && best
+                    .as_ref()
+                    .is_none_or(|(_, _, b)| schedule.makespan < b.makespan)
+            {
+                best = Some((relative, utils, schedule));
+            }
+        } else if let Some(records) = telemetry::read_log(&path_str)? {
+            if !records.is_empty() {
+                colonies.push(ColonyLog {
+                    label: relative,
+                    records,
+                });
+            }
+        }
+    }
+    colonies.sort_by_key(|colony| (colony.last().best, colony.label.clone()));
+
+    let mut files = Vec::new();
+    let mut write = |name: &str, contents: &str| -> io::Result<String> {
+        let path = format!("{}/{}", out_dir, name);
+        export::write_file(&path, contents)?;
+        files.push(path);
+        Ok(contents.to_string())
+    };
+
+    let convergence = write("convergence.svg", &convergence_svg(&colonies))?;
+    let parameters: [(&str, Parameter); 3] = [
+        ("deposit_rate", |r| r.deposit_rate),
+        ("evaporation_rate", |r| r.evaporation_rate),
+        ("base_chance", |r| r.base_chance),
+    ];
+    let mut scatters = Vec::new();
+    for (name, value) in parameters {
+        let svg = scatter_svg(&colonies, name, value);
+        scatters.push(write(&format!("scatter_{}.svg", name), &svg)?);
+    }
+    // adaptive colonies change these as they run
+    let mut trajectories = Vec::new();
+    for (name, value) in &parameters {
+        let svg = trajectory_svg(&colonies, name, *value);
+        trajectories.push(write(&format!("trajectory_{}.svg", name), &svg)?);
+    }
+    let gantt_svg = match &best {
+        Some((_, utils, schedule)) => Some(write("gantt.svg", &gantt::to_svg(schedule, utils))?),
+        None => None,
+    };
+    let best_label = best
+        .as_ref()
+        .map(|(label, _, schedule)| (label.as_str(), schedule.makespan));
+    write("summary.md", &markdown_summary(&colonies, best_label))?;
+
+    let mut html = String::new();
+    html.push_str(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>Colony report</title>\n",
+    );
+    html.push_str("<style>body { font-family: sans-serif; margin: 16px; } table { border-collapse: collapse; } td, th { border: 1px solid #ccc; padding: 4px 8px; text-align: right; } td:first-child { text-align: left; }</style>\n");
+    html.push_str("</head><body>\n<h1>Colony report</h1>\n");
+    html.push_str(&html_table(&colonies));
+    html.push_str("<h2>Convergence</h2>\n");
+    html.push_str(&convergence);
+    html.push_str("<h2>Parameters</h2>\n");
+    for svg in scatters.iter().chain(&trajectories) {
+        html.push_str(svg);
+    }
+    if let (Some(svg), Some((label, makespan))) = (&gantt_svg, best_label) {
+        let _ = writeln!(
+            html,
+            "<h2>Best schedule: {} (makespan {})</h2>",
+            label, makespan
+        );
+        html.push_str(svg);
+    }
+    html.push_str("</body></html>\n");
+    write("report.html", &html)?;
+
+    Ok(Report {
+        colonies,
+        best_schedule: best.map(|(label, _, schedule)| (label, schedule)),
+        files,
+    })
+}
+
+// every CSV below dir, the output directory of a previous report excluded
+fn find_csv(dir: &Path, skip: &Path, found: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            if path != skip {
+                find_csv(&path, skip, found)?;
+            }
+        } else if path.extension().is_some_and(|ext| ext == "csv") {
+            found.push(path);
+        }
+    }
+    Ok(())
+}
+
+// A schedule exported by write_schedule_csv carries the costs (finish - start) and the
+// predecessors, enough to rebuild the graph the Gantt chart needs
+fn read_schedule(path: &str) -> io::Result<(Utils, Schedule)> {
+    let contents = fs::read_to_string(path)?;
+    let mut entries = Vec::new();
+    let mut edges = Vec::new();
+    for line in contents.lines().skip(1) {
+        let fields: Vec<&str> = line.split(',').map(|f| f.trim()).collect();
+        let values: Vec<i32> = fields
+            .iter()
+            .take(4)
+            .filter_map(|f| f.parse().ok())
+            .collect();
+        // rows that are not a scheduled task, negative ids included, are left out
+        if values.len() != 4 || values[0] < 0 || values[1] < 0 {
+            continue;
+        }
+        let task = values[0];
+        entries.push(ScheduledTask {
+            task,
+            worker: values[1],
+            start: values[2],
+            finish: values[3],
+        });
+        if let Some(preds) = fields.get(4) {
+            for pred in preds
+                .split_whitespace()
+                .filter_map(|p| p.parse::<i32>().ok())
+            {
+                edges.push((pred, task));
+            }
+        }
+    }
+
+    let n_tasks = entries
+        .iter()
+        .map(|entry| entry.task + 1)
+        .max()
+        .unwrap_or(0);
+    let n_workers = entries
+        .iter()
+        .map(|entry| entry.worker + 1)
+        .max()
+        .unwrap_or(0);
+    let mut utils = Utils::new();
+    utils.n_tasks = n_tasks;
+    utils.costs_vec = vec![0; n_tasks as usize];
+    for task in 0..n_tasks {
+        utils.di_graph.add_node(task);
+    }
+    for entry in &entries {
+        utils.costs_vec[entry.task as usize] = entry.finish - entry.start;
+    }
+    for (pred, succ) in edges
+        .into_iter()
+        .filter(|&(pred, _)| pred >= 0 && pred < n_tasks)
+    {
+        utils.di_graph.add_edge(
+            NodeIndex::new(pred as usize),
+            NodeIndex::new(succ as usize),
+            utils.costs_vec[succ as usize],
+        );
+    }
+    Ok((utils, Schedule::new(n_tasks, n_workers, &entries)))
+}
+
+fn markdown_summary(colonies: &[ColonyLog], best: Option<(&str, i32)>) -> String {
+    let mut md = String::new();
+    md.push_str("# Colony report\n\n");
+    if let Some((label, makespan)) = best {
+        let _ = writeln!(
+            md,
+            "Best schedule: `{}` with makespan {}\n",
+            label, makespan
+        );
+    }
+    md.push_str("| Colony | Epochs | Best | Best epoch | Lower bound | Gap % | Deposit | Evaporation | Base chance | Construction ms |\n");
+    md.push_str("|---|---:|---:|---:|---:|---:|---:|---:|---:|---:|\n");
+    for colony in colonies {
+        let _ = writeln!(md, "| {} |", row(colony).join(" | "));
+    }
+    md
+}
+
+fn html_table(colonies: &[ColonyLog]) -> String {
+    let mut html = String::new();
+    html.push_str("<table>\n<tr><th>Colony</th><th>Epochs</th><th>Best</th><th>Best epoch</th><th>Lower bound</th><th>Gap %</th><th>Deposit</th><th>Evaporation</th><th>Base chance</th><th>Construction ms</th></tr>\n");
+    for colony in colonies {
+        let _ = writeln!(html, "<tr><td>{}</td></tr>", row(colony).join("</td><td>"));
+    }
+    html.push_str("</table>\n");
+    html
+}
+
+fn row(colony: &ColonyLog) -> Vec<String> {
+    let last = colony.last();
+    vec![
+        colony.label.clone(),
+        colony.records.len().to_string(),
+        last.best.to_string(),
+        colony.best_epoch().to_string(),
+        last.lower_bound.to_string(),
+        format!("{:.2}", last.gap() * 100.0),
+        format!("{:.4}", last.deposit_rate),
+        format!("{:.4}", last.evaporation_rate),
+        format!("{:.4}", last.base_chance),
+        format!("{:.3}", colony.mean_construction_ms()),
+    ]
+}
+
+/*##### SVG CHARTS ###### */
+
+const WIDTH: f64 = 900.0;
+const HEIGHT: f64 = 480.0;
+const LEFT: f64 = 70.0;
+const RIGHT: f64 = 200.0;
+const TOP: f64 = 40.0;
+const BOTTOM: f64 = 50.0;
+const COLORS: [&str; 10] = [
+    "#4e79a7", "#f28e2b", "#e15759", "#76b7b2", "#59a14f", "#edc948", "#b07aa1", "#ff9da7",
+    "#9c755f", "#bab0ac",
+];
+
+// plot area mapping data coordinates to pixels
+struct Chart {
+    x_min: f64,
+    x_max: f64,
+    y_min: f64,
+    y_max: f64,
+}
+
+impl Chart {
+    fn new(points: impl Iterator<Item = (f64, f64)>) -> Chart {
+        let mut chart = Chart {
+            x_min: f64::MAX,
+            x_max: f64::MIN,
+            y_min: f64::MAX,
+            y_max: f64::MIN,
+        };
+        for (x, y) in points {
+            chart.x_min = chart.x_min.min(x);
+            chart.x_max = chart.x_max.max(x);
+            chart.y_min = chart.y_min.min(y);
+            chart.y_max = chart.y_max.max(y);
+        }
+        if chart.x_min > chart.x_max {
+            (chart.x_min, chart.x_max, chart.y_min, chart.y_max) = (0.0, 1.0, 0.0, 1.0);
+        }
+        // a flat range still needs some room
+        for (min, max) in [
+            (&mut chart.x_min, &mut chart.x_max),
+            (&mut chart.y_min, &mut chart.y_max),
+        ] {
+            let pad = ((*max - *min) * 0.05).max(min.abs() * 0.01).max(1e-9);
+            *min -= pad;
+            *max += pad;
+        }
+        chart
+    }
+
+    fn px(&self, x: f64) -> f64 {
+        LEFT + (x - self.x_min) / (self.x_max - self.x_min) * (WIDTH - LEFT - RIGHT)
+    }
+
+    fn py(&self, y: f64) -> f64 {
+        HEIGHT - BOTTOM - (y - self.y_min) / (self.y_max - self.y_min) * (HEIGHT - TOP - BOTTOM)
+    }
+
+    // svg header, frame, ticks and labels
+    fn begin(&self, title: &str, x_label: &str, y_label: &str) -> String {
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}" font-family="sans-serif" font-size="11">"#,
+            w = WIDTH,
+            h = HEIGHT
+        );
+        let _ = writeln!(
+            svg,
+            r#"<rect x="0" y="0" width="{}" height="{}" fill="white"/>"#,
+            WIDTH, HEIGHT
+        );
+        let _ = writeln!(
+            svg,
+            r#"<text x="{:.1}" y="20" font-size="14" text-anchor="middle">{}</text>"#,
+            (LEFT + WIDTH - RIGHT) / 2.0,
+            title
+        );
+        let _ = writeln!(
+            svg,
+            r#"<rect x="{}" y="{}" width="{}" height="{}" fill="none" stroke="black"/>"#,
+            LEFT,
+            TOP,
+            WIDTH - LEFT - RIGHT,
+            HEIGHT - TOP - BOTTOM
+        );
+        for x in ticks(self.x_min, self.x_max) {
+            let _ = writeln!(
+                svg,
+                r##"<line x1="{px:.1}" y1="{t:.1}" x2="{px:.1}" y2="{b:.1}" stroke="#eee"/><text x="{px:.1}" y="{ty:.1}" text-anchor="middle">{label}</text>"##,
+                px = self.px(x),
+                t = TOP,
+                b = HEIGHT - BOTTOM,
+                ty = HEIGHT - BOTTOM + 15.0,
+                label = tick_label(x, self.x_max - self.x_min)
+            );
+        }
+        for y in ticks(self.y_min, self.y_max) {
+            let _ = writeln!(
+                svg,
+                r##"<line x1="{l:.1}" y1="{py:.1}" x2="{r:.1}" y2="{py:.1}" stroke="#eee"/><text x="{tx:.1}" y="{py:.1}" text-anchor="end" dominant-baseline="middle">{label}</text>"##,
+                py = self.py(y),
+                l = LEFT,
+                r = WIDTH - RIGHT,
+                tx = LEFT - 5.0,
+                label = tick_label(y, self.y_max - self.y_min)
+            );
+        }
+        let _ = writeln!(
+            svg,
+            r#"<text x="{:.1}" y="{:.1}" text-anchor="middle">{}</text>"#,
+            (LEFT + WIDTH - RIGHT) / 2.0,
+            HEIGHT - 10.0,
+            x_label
+        );
+        let _ = writeln!(
+            svg,
+            r#"<text x="15" y="{y:.1}" text-anchor="middle" transform="rotate(-90 15 {y:.1})">{}</text>"#,
+            y_label,
+            y = (TOP + HEIGHT - BOTTOM) / 2.0
+        );
+        svg
+    }
+
+    fn legend(&self, svg: &mut String, index: usize, label: &str) {
+        let y = TOP + 10.0 + index as f64 * 16.0;
+        let _ = writeln!(
+            svg,
+            r#"<rect x="{:.1}" y="{:.1}" width="10" height="10" fill="{}"/><text x="{:.1}" y="{:.1}" dominant-baseline="middle">{}</text>"#,
+            WIDTH - RIGHT + 10.0,
+            y - 5.0,
+            COLORS[index % COLORS.len()],
+            WIDTH - RIGHT + 25.0,
+            y,
+            label
+        );
+    }
+}
+
+// round tick positions, about eight per axis
+fn ticks(min: f64, max: f64) -> Vec<f64> {
+    let step = nice_step((max - min) / 8.0);
+    let mut ticks = Vec::new();
+    let mut tick = (min / step).ceil() * step;
+    while tick <= max {
+        ticks.push(tick);
+        tick += step;
+    }
+    ticks
+}
+
+// 1, 2 or 5 times a power of ten
+fn nice_step(raw: f64) -> f64 {
+    let power = 10_f64.powf(raw.log10().floor());
+    [1.0, 2.0, 5.0, 10.0]
+        .iter()
+        .map(|m| m * power)
+        .find(|&s| s >= raw)
+        .unwrap_or(10.0 * power)
+}
+
+fn tick_label(value: f64, range: f64) -> String {
+    let step = nice_step(range / 8.0);
+    let decimals = if step >= 1.0 {
+        0
+    } else {
+        (-step.log10()).ceil() as usize
+    };
+    format!("{:.*}", decimals, value)
+}
+
+// best makespan against epoch for every colony, drawn as steps where the best changes
+fn convergence_svg(colonies: &[ColonyLog]) -> String {
+    let series: Vec<(String, Vec<(f64, f64)>)> = colonies
+        .iter()
+        .map(|colony| {
+            let mut points: Vec<(f64, f64)> = Vec::new();
+            for record in &colony.records {
+                let best = record.best as f64;
+                match points.last() {
+                    Some(&(_, last)) if last == best => {}
+                    Some(&(_, last)) => {
+                        points.push((record.epoch as f64, last));
+                        points.push((record.epoch as f64, best));
+                    }
+                    None => points.push((record.epoch as f64, best)),
+                }
+            }
+            let last = colony.last();
+            points.push((last.epoch as f64, last.best as f64));
+            (colony.label.clone(), points)
+        })
+        .collect();
+    lines_svg("Best makespan per epoch", "epoch", "best makespan", &series)
+}
+
+// one polyline per labelled series
+pub(crate) fn lines_svg(
+    title: &str,
+    x_label: &str,
+    y_label: &str,
+    series: &[(String, Vec<(f64, f64)>)],
+) -> String {
+    let chart = Chart::new(series.iter().flat_map(|(_, points)| points.iter().copied()));
+    let mut svg = chart.begin(title, x_label, y_label);
+    for (i, (label, points)) in series.iter().enumerate() {
+        let coords: Vec<String> = points
+            .iter()
+            .map(|&(x, y)| format!("{:.1},{:.1}", chart.px(x), chart.py(y)))
+            .collect();
+        let _ = writeln!(
+            svg,
+            r#"<polyline fill="none" stroke="{}" stroke-width="1.5" points="{}"><title>{}</title></polyline>"#,
+            COLORS[i % COLORS.len()],
+            coords.join(" "),
+            label
+        );
+        chart.legend(&mut svg, i, label);
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+// final makespan of every colony against one of its parameters
+fn scatter_svg(colonies: &[ColonyLog], name: &str, value: Parameter) -> String {
+    let points: Vec<(f64, f64)> = colonies
+        .iter()
+        .map(|colony| (value(colony.last()), colony.last().best as f64))
+        .collect();
+    let chart = Chart::new(points.iter().copied());
+    let mut svg = chart.begin(
+        &format!("Final makespan against {}", name),
+        name,
+        "best makespan",
+    );
+    for (i, (colony, &(x, y))) in colonies.iter().zip(&points).enumerate() {
+        let _ = writeln!(
+            svg,
+            r#"<circle cx="{:.1}" cy="{:.1}" r="5" fill="{}"><title>{}: {} = {}, makespan {}</title></circle>"#,
+            chart.px(x),
+            chart.py(y),
+            COLORS[i % COLORS.len()],
+            colony.label,
+            name,
+            x,
+            y
+        );
+        chart.legend(&mut svg, i, &colony.label);
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+// one of the parameters against epoch for every colony
+fn trajectory_svg(colonies: &[ColonyLog], name: &str, value: Parameter) -> String {
+    let series: Vec<(String, Vec<(f64, f64)>)> = colonies
+        .iter()
+        .map(|colony| {
+            let points = colony
+                .records
+                .iter()
+                .map(|record| (record.epoch as f64, value(record)))
+                .collect();
+            (colony.label.clone(), points)
+        })
+        .collect();
+    lines_svg(&format!("{} per epoch", name), "epoch", name, &series)
+}
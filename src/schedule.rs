@@ -0,0 +1,104 @@
+use crate::worker_ant::WorkerAnt;
+
+// One task of a schedule: which worker ran it and when
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ScheduledTask {
+    pub task: i32,
+    pub worker: i32,
+    pub start: i32,
+    pub finish: i32,
+}
+
+// A complete solution, the same data seen per task and per worker
+#[derive(Clone, Debug, Default)]
+pub struct Schedule {
+    pub n_workers: i32,
+    // indexed by task id, None for a task that was never scheduled
+    pub tasks: Vec<Option<ScheduledTask>>,
+    // the tasks of every worker ordered by start
+    pub workers: Vec<Vec<ScheduledTask>>,
+    pub makespan: i32,
+}
+
+impl Schedule {
+    pub fn new(n_tasks: i32, n_workers: i32, entries: &[ScheduledTask]) -> Schedule {
+        // entries on a negative worker or of a task outside the graph are skipped
+        let entries: Vec<ScheduledTask> = entries
+            .iter()
+            .filter(|entry| entry.worker >= 0 && entry.task >= 0 && entry.task < n_tasks)
+            .copied()
+            .collect();
+        let n_workers = entries
+            .iter()
+            .map(|entry| entry.worker + 1)
+            .fold(n_workers, i32::max);
+        let mut tasks = vec![None; n_tasks.max(0) as usize];
+        let mut workers = vec![Vec::new(); n_workers as usize];
+        for entry in &entries {
+            tasks[entry.task as usize].get_or_insert(*entry);
+            workers[entry.worker as usize].push(*entry);
+        }
+        for list in &mut workers {
+            list.sort_by_key(|entry| (entry.start, entry.finish, entry.task));
+        }
+        let makespan = entries.iter().map(|entry| entry.finish).max().unwrap_or(0);
+
+        Schedule {
+            n_workers,
+            tasks,
+            workers,
+            makespan,
+        }
+    }
+
+    // the index of an ant is its worker id
+    pub(crate) fn from_ants(n_tasks: i32, ants: &[WorkerAnt]) -> Schedule {
+        let entries: Vec<ScheduledTask> = ants
+            .iter()
+            .enumerate()
+            .flat_map(|(worker, ant)| {
+                ant.task_history
+                    .iter()
+                    .map(move |&(task, start, finish)| ScheduledTask {
+                        task,
+                        worker: worker as i32,
+                        start,
+                        finish,
+                    })
+            })
+            .collect();
+        Schedule::new(n_tasks, ants.len() as i32, &entries)
+    }
+
+    // every scheduled task, worker by worker
+    pub fn entries(&self) -> Vec<ScheduledTask> {
+        self.workers.iter().flatten().copied().collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.workers.iter().all(|list| list.is_empty())
+    }
+
+    pub fn busy_time(&self, worker: usize) -> i64 {
+        self.workers[worker]
+            .iter()
+            .map(|entry| (entry.finish - entry.start) as i64)
+            .sum()
+    }
+
+    // cycles the workers spend waiting before the makespan
+    pub fn idle_time(&self) -> i64 {
+        (0..self.workers.len())
+            .map(|worker| self.makespan as i64 - self.busy_time(worker))
+            .sum()
+    }
+
+    // fraction of the worker time spent on tasks
+    pub fn utilisation(&self) -> f64 {
+        let capacity = self.makespan as i64 * self.n_workers as i64;
+        if capacity == 0 {
+            return 0.0;
+        }
+        (capacity - self.idle_time()) as f64 / capacity as f64
+    }
+}
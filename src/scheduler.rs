@@ -0,0 +1,294 @@
+use std::fmt;
+use std::io;
+use std::ops::ControlFlow;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::instance::Instance;
+use crate::logging::{debug, info};
+use crate::observer::{Event, Observer, STAGNATION_EVENT_EPOCHS};
+use crate::schedule::Schedule;
+use crate::stopping::{StopCondition, StopReason};
+use crate::telemetry::{EpochRecord, TelemetryLog};
+
+/// A search over schedules of an instance, run one step at a time by the solver.
+///
+/// The solver owns the loop: it stops the search with the stopping rules of the config, logs
+/// [`Scheduler::telemetry`] after every step and keeps the best schedule of every scheduler of
+/// the run. The ant colony is one, [`SolveOptions::schedulers`](crate::SolveOptions::schedulers)
+/// plugs in others.
+pub trait Scheduler: Send {
+    /// Short name in the results, e.g. `aco`.
+    fn name(&self) -> &str;
+
+    /// Prepares the search, called once before the first step. Returns where the search
+    /// starts, a fresh one at [`RunState::default`].
+    fn initialise(&mut self) -> RunState;
+
+    /// Runs one step, an epoch for the colonies, and returns the makespan of the schedule it
+    /// built.
+    fn step(&mut self, step: i32) -> i32;
+
+    /// The best schedule so far, None before the first step.
+    fn best(&self) -> Option<&Schedule>;
+
+    /// The last step as it ran, one line of the telemetry log.
+    fn telemetry(&self) -> EpochRecord;
+
+    /// Saves what a resumed run needs, every `checkpoint_every` steps and once stopped. Only the
+    /// colonies can be resumed, other schedulers keep nothing. An error ends the run.
+    fn checkpoint(&mut self, _state: &RunState) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Called once after the last step, writes whatever the scheduler keeps of the run.
+    fn finish(&mut self, _reason: StopReason) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Where a scheduler stands between two steps.
+#[derive(Clone, Debug, Default)]
+#[non_exhaustive]
+pub struct RunState {
+    /// The next step to run.
+    pub step: i32,
+    /// Steps since the best makespan last improved.
+    pub stagnant_steps: i32,
+    /// Time since the run started.
+    pub elapsed: Duration,
+    /// Set once the scheduler stopped for good, a cancelled one is resumed.
+    pub stopped: Option<StopReason>,
+    // bytes of the telemetry log up to `step`, where a resumed log continues
+    pub(crate) telemetry_len: u64,
+}
+
+/// What the solver gives a scheduler plugged in with
+/// [`SolveOptions::schedulers`](crate::SolveOptions::schedulers).
+#[non_exhaustive]
+pub struct SchedulerContext<'a> {
+    pub instance: &'a Instance,
+    /// Index of the scheduler in the run, one per colony of the config.
+    pub id: i32,
+    pub n_workers: i32,
+    /// Seed of this scheduler, drawn from the seed of the run.
+    pub seed: u64,
+    pub lower_bound: i32,
+}
+
+/// The built-in schedulers, picked with `scheduler` in the config.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SchedulerKind {
+    /// The ant colonies.
+    Aco,
+    /// Critical path list scheduling with random restarts, a baseline for the colonies.
+    List,
+}
+
+impl fmt::Display for SchedulerKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchedulerKind::Aco => write!(f, "aco"),
+            SchedulerKind::List => write!(f, "list"),
+        }
+    }
+}
+
+impl FromStr for SchedulerKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<SchedulerKind, String> {
+        match s {
+            "aco" => Ok(SchedulerKind::Aco),
+            "list" => Ok(SchedulerKind::List),
+            _ => Err(format!("unknown scheduler '{}'", s)),
+        }
+    }
+}
+
+// Runs one scheduler until a stopping rule ends it, the same loop for every scheduler of a run
+pub(crate) struct Driver<'a> {
+    pub id: i32,
+    pub stop: &'a StopCondition,
+    // every step goes to this CSV, improving or not, None keeps nothing
+    pub telemetry_path: Option<String>,
+    pub checkpoint_every: Option<i32>,
+    pub progress: &'a (dyn Fn(&EpochRecord) + Sync),
+    pub observers: &'a [&'a dyn Observer],
+}
+
+impl Driver<'_> {
+    // fails when an output of the scheduler cannot be written
+    pub fn run(&self, scheduler: &mut dyn Scheduler) -> io::Result<(i32, Schedule, StopReason)> {
+        let epochs = self.stop.max_epochs;
+        let mut state = scheduler.initialise();
+        // a scheduler that stopped before the run was interrupted has nothing left to do
+        if let Some(reason) = state
+            .stopped
+            .filter(|&reason| reason != StopReason::Cancelled)
+        {
+            return Ok(self.finished(scheduler, reason));
+        }
+        // a resumed scheduler drops the records written after its checkpoint
+        let mut telemetry = self
+            .telemetry_path
+            .as_ref()
+            .map(|path| {
+                if state.telemetry_len > 0 {
+                    TelemetryLog::resume(path, state.telemetry_len)
+                } else {
+                    TelemetryLog::create(path)
+                }
+            })
+            .transpose()?;
+        let mut stop_reason = StopReason::Epochs;
+        let mut previous: Option<EpochRecord> = None;
+
+        for step in state.step..epochs {
+            let best = scheduler.best().map_or(i32::MAX, |best| best.makespan);
+            let cycles = scheduler.step(step);
+            let improved = cycles < best;
+            if improved {
+                state.stagnant_steps = 0;
+            } else {
+                state.stagnant_steps += 1;
+            }
+            let record = scheduler.telemetry();
+            if let Some(telemetry) = &mut telemetry {
+                telemetry.log(&record)?;
+            }
+            (self.progress)(&record);
+            let mut flow = ControlFlow::Continue(());
+            if let (true, Some(schedule)) = (improved, scheduler.best()) {
+                flow = self.notify(
+                    flow,
+                    &Event::NewBest {
+                        record: &record,
+                        schedule,
+                    },
+                );
+            }
+            let changed = previous
+                .as_ref()
+                .filter(|previous| !same_parameters(previous, &record));
+            if let Some(previous) = changed {
+                flow = self.notify(
+                    flow,
+                    &Event::ParameterChange {
+                        previous,
+                        record: &record,
+                    },
+                );
+            }
+            if state.stagnant_steps > 0 && state.stagnant_steps % STAGNATION_EVENT_EPOCHS == 0 {
+                flow = self.notify(
+                    flow,
+                    &Event::Stagnation {
+                        record: &record,
+                        stagnant_epochs: state.stagnant_steps,
+                    },
+                );
+            }
+            flow = self.notify(flow, &Event::EpochFinished { record: &record });
+            if flow.is_break() {
+                self.stop.request(StopReason::Observer);
+            }
+
+            // Periodic logging, the live progress line shows the same without the noise
+            if step % 100 == 0 {
+                debug!(
+                    colony: self.id,
+                    "Progress - Epoch {}/{}: Best = {}, Current = {}",
+                    step,
+                    epochs,
+                    record.best,
+                    cycles
+                );
+            }
+            state.step = step + 1;
+            // Early stopping, this scheduler or all of them
+            if let Some(reason) = self.stop.after_epoch(cycles, state.stagnant_steps) {
+                info!(
+                    colony: self.id,
+                    "Stopped at epoch {} ({}), Best = {}, Current = {}",
+                    step,
+                    reason,
+                    record.best,
+                    cycles
+                );
+                stop_reason = reason;
+                break;
+            }
+            if self
+                .checkpoint_every
+                .is_some_and(|every| every > 0 && state.step % every == 0)
+            {
+                state.elapsed = self.stop.elapsed();
+                state.telemetry_len = telemetry_position(&mut telemetry)?;
+                scheduler.checkpoint(&state)?;
+            }
+            previous = Some(record);
+        }
+
+        if let Some(telemetry) = &mut telemetry {
+            telemetry.flush()?;
+        }
+        // the last state either says the scheduler is done or, when cancelled, where to go on from
+        if self.checkpoint_every.is_some() {
+            state.stopped = Some(stop_reason);
+            state.elapsed = self.stop.elapsed();
+            state.telemetry_len = telemetry_position(&mut telemetry)?;
+            scheduler.checkpoint(&state)?;
+        }
+        scheduler.finish(stop_reason)?;
+        Ok(self.finished(scheduler, stop_reason))
+    }
+
+    // every observer sees every event, the run stops if any of them asks
+    fn notify(&self, flow: ControlFlow<()>, event: &Event) -> ControlFlow<()> {
+        self.observers
+            .iter()
+            .fold(flow, |flow, observer| match observer.on_event(event) {
+                ControlFlow::Break(()) => ControlFlow::Break(()),
+                ControlFlow::Continue(()) => flow,
+            })
+    }
+
+    fn finished(
+        &self,
+        scheduler: &dyn Scheduler,
+        reason: StopReason,
+    ) -> (i32, Schedule, StopReason) {
+        let best = scheduler.best();
+        // the colony is done, there is nothing left to stop
+        let _ = self.notify(
+            ControlFlow::Continue(()),
+            &Event::ColonyFinished {
+                colony_id: self.id,
+                reason,
+                record: &scheduler.telemetry(),
+                schedule: best,
+            },
+        );
+        match best {
+            Some(best) => (best.makespan, best.clone(), reason),
+            None => (i32::MAX, Schedule::default(), reason),
+        }
+    }
+}
+
+// whether two epochs ran with the same parameters
+fn same_parameters(a: &EpochRecord, b: &EpochRecord) -> bool {
+    a.alfa == b.alfa
+        && a.beta == b.beta
+        && a.base_chance == b.base_chance
+        && a.deposit_rate == b.deposit_rate
+        && a.evaporation_rate == b.evaporation_rate
+}
+
+// where a resumed scheduler continues its telemetry log
+fn telemetry_position(telemetry: &mut Option<TelemetryLog>) -> io::Result<u64> {
+    telemetry
+        .as_mut()
+        .map_or(Ok(0), |telemetry| telemetry.position())
+}
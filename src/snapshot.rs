@@ -0,0 +1,246 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::str::FromStr;
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::thread::{self, JoinHandle};
+
+use crate::heatmap::{Heatmap, HeatmapAnimation};
+use crate::pherohormones::Pherohormones;
+
+// When the colony writes a copy of its pheromone trails (GEXF, DOT frame, PNG heatmap and a GIF frame)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SnapshotPolicy {
+    Off,
+    // every time the best solution improves or is matched
+    OnImprovement,
+    // every n epochs whether the trails improved or not
+    EveryNEpochs(i32),
+    // on improvement, keeping only the files of the last k snapshots on disk
+    LastK(usize),
+}
+
+impl SnapshotPolicy {
+    pub fn wants(&self, epoch: i32, improved: bool) -> bool {
+        match *self {
+            SnapshotPolicy::Off => false,
+            SnapshotPolicy::OnImprovement | SnapshotPolicy::LastK(_) => improved,
+            SnapshotPolicy::EveryNEpochs(n) => n > 0 && epoch % n == 0,
+        }
+    }
+}
+
+// off, improvement, every:N or last:K as written in config files
+impl fmt::Display for SnapshotPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotPolicy::Off => write!(f, "off"),
+            SnapshotPolicy::OnImprovement => write!(f, "improvement"),
+            SnapshotPolicy::EveryNEpochs(n) => write!(f, "every:{}", n),
+            SnapshotPolicy::LastK(k) => write!(f, "last:{}", k),
+        }
+    }
+}
+
+impl FromStr for SnapshotPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<SnapshotPolicy, String> {
+        let invalid = || format!("invalid snapshot policy '{}'", s);
+        match s.split_once(':') {
+            None if s == "off" => Ok(SnapshotPolicy::Off),
+            None if s == "improvement" => Ok(SnapshotPolicy::OnImprovement),
+            Some(("every", n)) => n
+                .parse()
+                .map(SnapshotPolicy::EveryNEpochs)
+                .map_err(|_| invalid()),
+            Some(("last", k)) => k.parse().map(SnapshotPolicy::LastK).map_err(|_| invalid()),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+struct Snapshot {
+    pherohormones: Pherohormones,
+    epoch: i32,
+    cycles: i32,
+    label: String,
+}
+
+// snapshots waiting to be written before new ones are dropped instead of stalling the search
+const QUEUE_SIZE: usize = 8;
+
+// Writes the snapshots on its own thread so the colony only pays for a clone of the trails.
+// Every file of a snapshot is named after its epoch, the makespan only goes inside the files.
+pub(crate) struct SnapshotWriter {
+    policy: SnapshotPolicy,
+    sender: Option<SyncSender<Snapshot>>,
+    handle: Option<JoinHandle<io::Result<()>>>,
+    pending: Option<Snapshot>,
+    pub dropped: usize,
+}
+
+impl SnapshotWriter {
+    pub fn new(policy: SnapshotPolicy, output_dir: &str) -> SnapshotWriter {
+        if policy == SnapshotPolicy::Off {
+            return SnapshotWriter {
+                policy,
+                sender: None,
+                handle: None,
+                pending: None,
+                dropped: 0,
+            };
+        }
+        let (sender, receiver) = mpsc::sync_channel(QUEUE_SIZE);
+        let output_dir = output_dir.to_string();
+        let handle = thread::spawn(move || write_snapshots(receiver, policy, &output_dir));
+        SnapshotWriter {
+            policy,
+            sender: Some(sender),
+            handle: Some(handle),
+            pending: None,
+            dropped: 0,
+        }
+    }
+
+    pub fn submit(
+        &mut self,
+        pherohormones: &Pherohormones,
+        epoch: i32,
+        cycles: i32,
+        improved: bool,
+        label: String,
+    ) {
+        let Some(sender) = &self.sender else {
+            return;
+        };
+        // a snapshot held back while the queue was full goes first
+        if let Some(snapshot) = self.pending.take() {
+            self.pending = try_send(sender, snapshot);
+        }
+        if !self.policy.wants(epoch, improved) {
+            return;
+        }
+        let snapshot = Snapshot {
+            pherohormones: pherohormones.clone(),
+            epoch,
+            cycles,
+            label,
+        };
+        // when the writer is behind only the newest snapshot is kept, so the last trails are never lost
+        if self.pending.is_some() {
+            self.dropped += 1;
+            self.pending = Some(snapshot);
+        } else {
+            self.pending = try_send(sender, snapshot);
+        }
+    }
+
+    // waits for the queued snapshots to be written, the error of a writer that gave up is
+    // only known here
+    pub fn finish(&mut self) -> io::Result<()> {
+        if let (Some(sender), Some(snapshot)) = (&self.sender, self.pending.take()) {
+            let _ = sender.send(snapshot);
+        }
+        self.sender = None;
+        match self.handle.take() {
+            Some(handle) => handle.join().expect("snapshot writer panicked"),
+            None => Ok(()),
+        }
+    }
+}
+
+// gives the snapshot back when the queue is full, a writer that stopped on an error takes
+// nothing more and reports it from finish
+fn try_send(sender: &SyncSender<Snapshot>, snapshot: Snapshot) -> Option<Snapshot> {
+    match sender.try_send(snapshot) {
+        Err(TrySendError::Full(snapshot)) => Some(snapshot),
+        Ok(()) | Err(TrySendError::Disconnected(_)) => None,
+    }
+}
+
+impl Drop for SnapshotWriter {
+    fn drop(&mut self) {
+        let _ = self.finish();
+    }
+}
+
+fn write_snapshots(
+    receiver: Receiver<Snapshot>,
+    policy: SnapshotPolicy,
+    output_dir: &str,
+) -> io::Result<()> {
+    let mut animation: Option<HeatmapAnimation> = None;
+    let mut written: VecDeque<Vec<String>> = VecDeque::new();
+
+    // dot frames are numbered in the order they are written so the animation plays in sequence
+    for (frame, snapshot) in receiver.into_iter().enumerate() {
+        let ph = &snapshot.pherohormones;
+        let gexf = ph.save_gephi(snapshot.epoch, snapshot.cycles)?;
+        let dot = ph.save_dot(frame as i32, &snapshot.label)?;
+        let heatmap = Heatmap::from_pherohormones(ph);
+        let png = format!("{}/heatmaps/heatmap_{:06}.png", output_dir, snapshot.epoch);
+        heatmap.write_png(&png)?;
+        if animation.is_none() {
+            animation = Some(HeatmapAnimation::create(
+                &format!("{}/animation.gif", output_dir),
+                heatmap.side,
+            )?);
+        }
+        if let Some(animation) = animation.as_mut() {
+            animation.add_frame(&heatmap, 20)?;
+        }
+
+        // the animation keeps every frame, only the single snapshot files are removed
+        if let SnapshotPolicy::LastK(k) = policy {
+            written.push_back(vec![gexf, dot, png]);
+            while written.len() > k {
+                for path in written.pop_front().unwrap() {
+                    let _ = fs::remove_file(path);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn policy_parses_what_it_prints() {
+        for policy in [
+            SnapshotPolicy::Off,
+            SnapshotPolicy::OnImprovement,
+            SnapshotPolicy::EveryNEpochs(25),
+            SnapshotPolicy::LastK(3),
+        ] {
+            assert_eq!(policy.to_string().parse::<SnapshotPolicy>(), Ok(policy));
+        }
+    }
+
+    #[test]
+    fn policy_rejects_unknown_values() {
+        for value in [
+            "", "on", "every", "every:", "every:x", "last:-1", "last:2:3", "off:1",
+        ] {
+            assert_eq!(
+                value.parse::<SnapshotPolicy>(),
+                Err(format!("invalid snapshot policy '{}'", value))
+            );
+        }
+    }
+
+    #[test]
+    fn policy_wants() {
+        assert!(!SnapshotPolicy::Off.wants(0, true));
+        assert!(SnapshotPolicy::OnImprovement.wants(7, true));
+        assert!(!SnapshotPolicy::OnImprovement.wants(7, false));
+        assert!(SnapshotPolicy::LastK(2).wants(7, true));
+        assert!(SnapshotPolicy::EveryNEpochs(5).wants(10, false));
+        assert!(!SnapshotPolicy::EveryNEpochs(5).wants(11, true));
+        assert!(!SnapshotPolicy::EveryNEpochs(0).wants(0, true));
+    }
+}
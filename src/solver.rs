@@ -0,0 +1,874 @@
+use std::fmt;
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+use rand::Rng;
+use rayon::iter::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator};
+
+use crate::bounds::LowerBounds;
+use crate::branch_bound::{BnbResult, BranchAndBound};
+use crate::checkpoint::{ColonyCheckpoint, RunCheckpoint};
+use crate::colony::Colony;
+use crate::config::SolverConfig;
+use crate::export::{self, json_array, JsonObject};
+use crate::instance::Instance;
+use crate::list_scheduler::ListScheduler;
+use crate::logging::{self, info, warn, LogLevel, ProgressDisplay};
+use crate::observer::Observer;
+use crate::pherohormones::Pherohormones;
+use crate::schedule::Schedule;
+use crate::scheduler::{Driver, Scheduler, SchedulerContext, SchedulerKind};
+use crate::snapshot::SnapshotPolicy;
+use crate::stopping::{CancelToken, StopCondition, StopReason};
+use crate::telemetry::EpochRecord;
+use crate::utils::Utils;
+use crate::{gantt, models, trace, validator};
+
+/// The best schedule of one colony and the parameters it ended with.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ColonyResult {
+    pub thread_id: i32,
+    /// Name of the scheduler that ran in this slot, `aco` for the colonies.
+    pub scheduler: String,
+    pub deposit_rate: f64,
+    pub evaporation_rate: f64,
+    pub base_chance: f64,
+    pub max_weight: f64,
+    pub alfa: f64,
+    pub beta: f64,
+    pub seed: u64,
+    pub best_cycle: i32,
+    pub stop_reason: StopReason,
+    pub lower_bound: i32,
+    pub gap: f64,
+    pub output_dir: String,
+    pub schedule: Schedule,
+}
+impl ColonyResult {
+    pub(crate) fn save_all(
+        results: &[ColonyResult],
+        output_dir: &str,
+        utils: &Utils,
+    ) -> io::Result<()> {
+        // Ensure the output directory exists
+        std::fs::create_dir_all(output_dir)?;
+
+        for result in results {
+            let thread_dir = format!("{}/thread_{}", output_dir, result.thread_id);
+            std::fs::create_dir_all(&thread_dir)?;
+            let filename = format!("{}/result.txt", thread_dir);
+            let mut file = std::fs::File::create(&filename)?;
+            writeln!(file, "{}", result)?;
+
+            // the best schedule of every colony in machine readable form
+            export::write_schedule_csv(
+                &result.schedule,
+                utils,
+                &format!("{}/best_schedule.csv", thread_dir),
+            )?;
+            export::write_schedule_json(
+                &result.schedule,
+                utils,
+                &format!("{}/best_schedule.json", thread_dir),
+            )?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn to_json(&self) -> JsonObject {
+        JsonObject::new()
+            .field("thread_id", self.thread_id)
+            .string("scheduler", &self.scheduler)
+            .float("deposit_rate", self.deposit_rate)
+            .float("evaporation_rate", self.evaporation_rate)
+            .float("base_chance", self.base_chance)
+            .float("max_weight", self.max_weight)
+            .float("alfa", self.alfa)
+            .float("beta", self.beta)
+            .field("seed", self.seed)
+            .field("makespan", self.best_cycle)
+            .string("stop_reason", &self.stop_reason.to_string())
+            .float("gap", self.gap)
+            .float("utilisation", self.schedule.utilisation())
+            .string("output_dir", &self.output_dir)
+    }
+}
+impl fmt::Display for ColonyResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Thread ID: {}\nScheduler: {}\nDeposit Rate: {:.4}\nEvaporation Rate: {:.4}\nBase Chance: {:.4}\nMax Weight: {:.4}\nAlfa: {:.4}\nBeta: {:.4}\nSeed: {}\nBest Cycle Count: {}\nStop Reason: {}\nLower Bound: {}\nGap: {:.2}%\nIdle Time: {}\nUtilisation: {:.2}%\nOutput Directory: {}",
+            self.thread_id,
+            self.scheduler,
+            self.deposit_rate,
+            self.evaporation_rate,
+            self.base_chance,
+            self.max_weight,
+            self.alfa,
+            self.beta,
+            self.seed,
+            self.best_cycle,
+            self.stop_reason,
+            self.lower_bound,
+            self.gap * 100.0,
+            self.schedule.idle_time(),
+            self.schedule.utilisation() * 100.0,
+            self.output_dir
+        )
+    }
+}
+
+/// What a run of all the colonies on one instance produced.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct SolveResult {
+    pub n_tasks: i32,
+    pub n_workers: i32,
+    pub makespan: i32,
+    pub lower_bound: i32,
+    pub gap: f64,
+    /// The seed that reproduces the run when set in the config.
+    pub seed: u64,
+    /// Best schedule of the run, its makespan is `makespan`.
+    pub schedule: Schedule,
+    /// Every colony with its parameters and best schedule.
+    pub colonies: Vec<ColonyResult>,
+    /// Epochs run by all the colonies together.
+    pub evaluations: u64,
+    /// Stopped by the cancel token, the makespan is only the best so far.
+    pub cancelled: bool,
+    /// Only for prototype graphs.
+    pub exact: Option<BnbResult>,
+    pub elapsed: Duration,
+}
+
+/// How [`solve_with`] runs, beyond the [`SolverConfig`].
+///
+/// ```no_run
+/// use task_scheduling::{solve_with, Instance, SolveOptions, SolverConfig};
+///
+/// let instance = Instance::load("graphs/rand0000.stg", false).unwrap();
+/// let config = SolverConfig::new().workers(16).epochs(500).seed(7);
+/// let options = SolveOptions::new().on_progress(&|record| {
+///     println!("colony {} epoch {}: {}", record.colony_id, record.epoch, record.best)
+/// });
+/// let result = solve_with(&instance, &config, &options).unwrap();
+/// println!("makespan {}", result.makespan);
+/// ```
+#[derive(Default)]
+pub struct SolveOptions<'a> {
+    results_dir: Option<String>,
+    progress: Option<&'a (dyn Fn(&EpochRecord) + Sync)>,
+    observers: Vec<&'a dyn Observer>,
+    schedulers: Option<&'a BuildScheduler>,
+    cancel: CancelToken,
+}
+
+/// Builds the scheduler of every colony slot of a run, see [`SolveOptions::schedulers`].
+pub type BuildScheduler = dyn Fn(&SchedulerContext) -> Box<dyn Scheduler> + Sync;
+
+impl<'a> SolveOptions<'a> {
+    pub fn new() -> SolveOptions<'a> {
+        SolveOptions::default()
+    }
+
+    /// Writes every output of the binary under `dir`: schedules, telemetry, trails, snapshots
+    /// and checkpoints. Nothing is written without one.
+    pub fn results_dir(mut self, dir: &str) -> SolveOptions<'a> {
+        self.results_dir = Some(dir.to_string());
+        self
+    }
+
+    /// Called by every colony after each of its epochs, from the colony's thread.
+    pub fn on_progress(mut self, progress: &'a (dyn Fn(&EpochRecord) + Sync)) -> SolveOptions<'a> {
+        self.progress = Some(progress);
+        self
+    }
+
+    /// Sends every event of the run to the observer, see [`Observer`]. Can be called more than
+    /// once, every observer sees every event.
+    pub fn observe(mut self, observer: &'a dyn Observer) -> SolveOptions<'a> {
+        self.observers.push(observer);
+        self
+    }
+
+    /// Runs these schedulers instead of the one of the config, one per colony of the config.
+    /// They share the stopping rules and outputs of the colonies.
+    pub fn schedulers(mut self, build: &'a BuildScheduler) -> SolveOptions<'a> {
+        self.schedulers = Some(build);
+        self
+    }
+
+    /// Stops the run once the token is cancelled, from any thread.
+    pub fn cancel(mut self, cancel: &CancelToken) -> SolveOptions<'a> {
+        self.cancel = cancel.clone();
+        self
+    }
+}
+
+/// Runs the colonies of the config on the instance and returns the best schedule, or why the
+/// config cannot run, see [`SolverConfig::validate`].
+pub fn solve(instance: &Instance, config: &SolverConfig) -> Result<SolveResult, String> {
+    solve_with(instance, config, &SolveOptions::new())
+}
+
+/// [`solve`] with progress callbacks, observers, cancellation or output files.
+pub fn solve_with(
+    instance: &Instance,
+    config: &SolverConfig,
+    options: &SolveOptions,
+) -> Result<SolveResult, String> {
+    config.validate()?;
+    run_colonies(instance, config, options, Vec::new()).map_err(|e| e.to_string())
+}
+
+// Runs the colonies of the config on file_path/graph_name and writes every output under results_path.
+// A cancelled run still writes everything for the best schedules found so far.
+pub(crate) fn solve_file(
+    config: &SolverConfig,
+    file_path: &str,
+    graph_name: &str,
+    results_path: &str,
+    cancel: &CancelToken,
+) -> io::Result<SolveResult> {
+    config.validate().map_err(invalid_config)?;
+    let instance = Instance::open(file_path, graph_name, config.prototype)?;
+    let display = progress_display(graph_name, config);
+    let mut options = SolveOptions::new().results_dir(results_path).cancel(cancel);
+    if let Some(display) = &display {
+        options = options.observe(display);
+    }
+    run_colonies(&instance, config, &options, Vec::new())
+}
+
+// Continues a run from the checkpoints it wrote, colonies without one start over. A seeded run
+// ends the same as if it had never been interrupted.
+pub(crate) fn resume(checkpoint_dir: &str, cancel: &CancelToken) -> io::Result<SolveResult> {
+    let run = RunCheckpoint::load(checkpoint_dir)?;
+    run.config.validate().map_err(invalid_config)?;
+    info!(
+        "Resuming {} with {} of {} colonies from their checkpoint",
+        run.graph_name,
+        run.colonies.len(),
+        run.config.colonies
+    );
+    let instance = Instance::open(&run.file_path, &run.graph_name, run.config.prototype)?;
+    let display = progress_display(&run.graph_name, &run.config);
+    let mut options = SolveOptions::new()
+        .results_dir(&run.results_path)
+        .cancel(cancel);
+    if let Some(display) = &display {
+        options = options.observe(display);
+    }
+    run_colonies(&instance, &run.config, &options, run.colonies)
+}
+
+fn invalid_config(e: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, e)
+}
+
+// the live line of the command line runs, left out when the output is not a terminal
+fn progress_display(graph_name: &str, config: &SolverConfig) -> Option<ProgressDisplay> {
+    logging::progress_line()
+        .then(|| ProgressDisplay::new(graph_name, config.colonies, config.epochs))
+}
+
+// Everything is written under the results directory of the options when there is one, a
+// library run without it only prints to the console
+fn run_colonies(
+    instance: &Instance,
+    config: &SolverConfig,
+    options: &SolveOptions,
+    mut checkpoints: Vec<ColonyCheckpoint>,
+) -> io::Result<SolveResult> {
+    let results_path = options.results_dir.as_deref();
+    let cancel = &options.cancel;
+    let is_proto = instance.prototype;
+    let n_ants = instance.workers.unwrap_or(config.workers);
+    let file_path = instance.file_path.as_str();
+    let graph_name = instance.name.as_str();
+    let epochs = config.epochs;
+    let benchmark = config.benchmark;
+    let n_threads = config.colonies;
+    let deposit_base = config.deposit_base;
+    // replace with the reported seed to reproduce a run
+    let seed: u64 = config.seed.unwrap_or_else(|| rand::rng().random());
+
+    /*##### INIT ###### */
+    let mut utils = instance.utils.clone();
+
+    let bounds = LowerBounds::new(&utils, n_ants);
+    info!("{}", bounds);
+
+    if let Some(results_path) = results_path {
+        // the input graph with costs, levels and critical path, render with `dot -Tsvg`
+        export::write_file(
+            &format!("{}/{}.dot", results_path, graph_name),
+            &utils.to_dot(),
+        )?;
+    }
+
+    if let (true, Some(results_path)) = (config.export_models, results_path) {
+        let model_path = format!("{}/models/{}", results_path, graph_name);
+        models::write_lp(&utils, n_ants, &format!("{}.lp", model_path))?;
+        models::write_minizinc(&utils, n_ants, &format!("{}.mzn", model_path))?;
+        info!("Models written to {}.lp and {}.mzn", model_path, model_path);
+    }
+    let external_solution = config
+        .solver_solution
+        .as_ref()
+        .map(|path| models::read_solution(&utils, n_ants, path))
+        .transpose()?;
+
+    // utils.print_graph();
+    // utils.print_vecs();
+    info!("Seed: {}", seed);
+    // without a results directory the colonies keep everything in memory
+    let results_dir = results_path.unwrap_or_default();
+    let checkpoint_every = results_path.and(config.checkpoint_every);
+    if checkpoint_every.is_some() && checkpoints.is_empty() {
+        let config = SolverConfig {
+            seed: Some(seed),
+            ..config.clone()
+        };
+        RunCheckpoint::start(&config, file_path, graph_name, results_dir)?;
+    }
+    //base chance must be a vector of chances
+    utils.init_parameters_vec(n_threads, deposit_base, config.evaporation_ratio, seed);
+    // let parameters = vec![
+    //     (0.01, 0.005),    // Original parameters 1 to 2
+    //     (0.01, 0.00125),  // New combination  1 to 8
+    //     (0.01, 0.000625), // New combination 1 to 16
+    // ];
+
+    /*##### CALL AND MEASURE ###### */
+
+    let start_time = Instant::now();
+
+    let snapshots = match results_path {
+        Some(_) => config.snapshots,
+        None => SnapshotPolicy::Off,
+    };
+    let trails = results_path.and(config.trails);
+
+    // the time limit counts from here, or from where the checkpoints left it
+    let mut stop = StopCondition::new(config, bounds.best(), cancel);
+    if !checkpoints.is_empty() {
+        stop.resume(
+            checkpoints.iter().map(|c| c.epoch as u64).sum(),
+            checkpoints
+                .iter()
+                .map(|c| c.elapsed)
+                .max()
+                .unwrap_or_default(),
+        );
+    }
+
+    let schedulers: Vec<Box<dyn Scheduler>> = match (options.schedulers, config.scheduler) {
+        (Some(build), _) => (0..n_threads)
+            .map(|i| {
+                build(&SchedulerContext {
+                    instance,
+                    id: i,
+                    n_workers: n_ants,
+                    seed: seed.wrapping_add(i as u64),
+                    lower_bound: bounds.best(),
+                })
+            })
+            .collect(),
+        (None, SchedulerKind::List) => (0..n_threads)
+            .map(|i| {
+                Box::new(ListScheduler::new(
+                    &utils,
+                    n_ants,
+                    i,
+                    seed.wrapping_add(i as u64),
+                    bounds.best(),
+                )) as Box<dyn Scheduler>
+            })
+            .collect(),
+        (None, SchedulerKind::Aco) => {
+            let colonies: Vec<_> = utils
+                .thread_pherohormones
+                .clone()
+                .into_par_iter()
+                .with_min_len(1) // Force no work stealing
+                .with_max_len(1) // Force 1 task per thread
+                .enumerate()
+                .map(|(i, (dr, er))| {
+                    let output_dir = format!("{}/thread_{}", results_dir, i);
+                    Colony::new(
+                        &utils,
+                        n_ants,
+                        dr,
+                        er,
+                        results_dir,
+                        &output_dir,
+                        graph_name,
+                        i as i32,
+                        1.0,
+                        config.base_chance,
+                        config.alfa,
+                        config.beta,
+                        0.0,
+                        seed.wrapping_add(i as u64),
+                        snapshots,
+                        config.adaptive,
+                        trails,
+                    )
+                })
+                .collect();
+
+            let warm_start = match &config.warm_start {
+                Some(path) => {
+                    let trails = Pherohormones::load_trails(path, utils.n_tasks)?;
+                    info!(
+                        "Warm start from {}: {} trails over {} tasks",
+                        path,
+                        trails.di_graph.edge_count(),
+                        trails.n_tasks
+                    );
+                    Some(trails)
+                }
+                None => None,
+            };
+            colonies
+                .into_iter()
+                .map(|mut colony| {
+                    if let Some(i) = checkpoints
+                        .iter()
+                        .position(|c| c.thread_id == colony.thread_id)
+                    {
+                        colony.resume_from = Some(checkpoints.swap_remove(i));
+                    }
+                    colony.warm_start = warm_start.clone();
+                    colony.lower_bound = bounds.best();
+                    Box::new(colony) as Box<dyn Scheduler>
+                })
+                .collect()
+        }
+    };
+    let progress = options.progress.unwrap_or(&|_| {});
+    let results: Vec<ColonyResult> = schedulers
+        .into_par_iter()
+        .enumerate()
+        .map(|(i, mut scheduler)| {
+            let output_dir = format!("{}/thread_{}", results_dir, i);
+            let driver = Driver {
+                id: i as i32,
+                stop: &stop,
+                telemetry_path: results_path
+                    .map(|_| format!("{}/thread_{}_{}.csv", output_dir, i, graph_name)),
+                checkpoint_every,
+                progress,
+                observers: &options.observers,
+            };
+            let (best_cycle, best_schedule, stop_reason) = driver.run(scheduler.as_mut())?;
+            // the parameters the last step ran with
+            let last = scheduler.telemetry();
+            Ok(ColonyResult {
+                scheduler: scheduler.name().to_string(),
+                deposit_rate: last.deposit_rate,
+                evaporation_rate: last.evaporation_rate,
+                best_cycle,
+                stop_reason,
+                lower_bound: bounds.best(),
+                gap: bounds.gap(best_cycle),
+                output_dir,
+                thread_id: i as i32,
+                base_chance: last.base_chance,
+                max_weight: last.max_weight,
+                alfa: last.alfa,
+                beta: last.beta,
+                seed: seed.wrapping_add(i as u64),
+                schedule: best_schedule,
+            })
+        })
+        .collect::<io::Result<_>>()?;
+
+    /*##### FIND BEST RESULT ###### */
+    let best_result = results.iter().min_by_key(|r| r.best_cycle).unwrap();
+
+    info!(
+        "Best colony {}: Best Cycle Count {}, Gap {:.2}%, Utilisation {:.2}%",
+        best_result.thread_id,
+        best_result.best_cycle,
+        best_result.gap * 100.0,
+        best_result.schedule.utilisation() * 100.0
+    );
+    info!(
+        "Best colony {}: Deposit Rate {:.4}, Evaporation Rate {:.4}, Output Directory {}",
+        best_result.thread_id,
+        best_result.deposit_rate,
+        best_result.evaporation_rate,
+        best_result.output_dir
+    );
+    //end Time and print
+    let end_time = Instant::now();
+
+    if let Some(results_path) = results_path {
+        //Save the results
+        ColonyResult::save_all(&results, results_path, &utils)?;
+        // best schedule of the whole run next to a summary of everything needed to reproduce it
+        export::write_schedule_csv(
+            &best_result.schedule,
+            &utils,
+            &format!("{}/best_schedule.csv", results_path),
+        )?;
+        export::write_schedule_json(
+            &best_result.schedule,
+            &utils,
+            &format!("{}/best_schedule.json", results_path),
+        )?;
+        gantt::write_svg(
+            &best_result.schedule,
+            &utils,
+            &format!("{}/best_schedule.svg", results_path),
+        )?;
+        gantt::write_html(
+            &best_result.schedule,
+            &utils,
+            &format!("{} - thread {}", graph_name, best_result.thread_id),
+            &format!("{}/best_schedule.html", results_path),
+        )?;
+        // open in chrome://tracing or ui.perfetto.dev
+        trace::write_trace(
+            &best_result.schedule,
+            &utils,
+            graph_name,
+            &format!("{}/best_schedule.trace.json", results_path),
+        )?;
+        let summary = JsonObject::new()
+            .raw(
+                "instance",
+                JsonObject::new()
+                    .string("name", graph_name)
+                    .string("path", &format!("{}{}", file_path, graph_name))
+                    .field("prototype", is_proto)
+                    .field("n_tasks", utils.n_tasks)
+                    .field("n_edges", utils.di_graph.edge_count())
+                    .field(
+                        "total_work",
+                        utils.costs_vec.iter().map(|&c| c as i64).sum::<i64>(),
+                    )
+                    .field("n_workers", n_ants)
+                    .to_string(),
+            )
+            .raw(
+                "parameters",
+                JsonObject::new()
+                    .string("scheduler", &config.scheduler.to_string())
+                    .field("epochs", epochs)
+                    .field("benchmark", benchmark)
+                    .field("stop_at_lower_bound", config.stop_at_lower_bound)
+                    .raw("stagnation", json_option(config.stagnation))
+                    .raw(
+                        "time_limit",
+                        json_option(config.time_limit.map(|t| t.as_secs_f64())),
+                    )
+                    .raw("max_evaluations", json_option(config.max_evaluations))
+                    .field("n_threads", n_threads)
+                    .float("deposit_base", deposit_base)
+                    // null when drawn per colony, see the colonies for the values
+                    .raw("evaporation_ratio", json_option(config.evaporation_ratio))
+                    .float("alfa", config.alfa)
+                    .float("beta", config.beta)
+                    .raw("base_chance", json_option(config.base_chance))
+                    .field("adaptive", config.adaptive)
+                    .string("snapshots", &config.snapshots.to_string())
+                    .raw(
+                        "warm_start",
+                        config
+                            .warm_start
+                            .as_deref()
+                            .map_or("null".to_string(), export::json_string),
+                    )
+                    .to_string(),
+            )
+            .field("seed", seed)
+            .field("evaluations", stop.evaluations())
+            .field("makespan", best_result.best_cycle)
+            .field("best_thread", best_result.thread_id)
+            .raw(
+                "lower_bound",
+                JsonObject::new()
+                    .field("best", bounds.best())
+                    .field("critical_path", bounds.critical_path)
+                    .field("work", bounds.work)
+                    .field("fernandez_bussell", bounds.fernandez_bussell)
+                    .to_string(),
+            )
+            .float("gap", best_result.gap)
+            .float(
+                "elapsed_seconds",
+                end_time.duration_since(start_time).as_secs_f64(),
+            )
+            .raw("colonies", json_array(results.iter().map(|r| r.to_json())));
+        export::write_file(
+            &format!("{}/run_summary.json", results_path),
+            &summary.to_string(),
+        )?;
+        // the config with the seed that was drawn, enough to reproduce the run
+        SolverConfig {
+            seed: Some(seed),
+            ..config.clone()
+        }
+        .save(&format!("{}/config.txt", results_path))?;
+    }
+
+    if let Some(schedule) = &external_solution {
+        let makespan = schedule.makespan;
+        info!(
+            "External solver: Makespan {}, Gap {:.2}%",
+            makespan,
+            bounds.gap(makespan) * 100.0
+        );
+        for violation in validator::validate(&utils, n_ants, &schedule.entries(), makespan) {
+            warn!("External solver schedule invalid: {}", violation);
+        }
+    }
+
+    /*##### EXACT SOLVER ###### */
+    // small graphs are solved to optimality seeded with the best colony, giving ground truth for the ants
+    let mut exact_result = None;
+    let cancelled = cancel.is_cancelled();
+    if cancelled {
+        warn!("Run cancelled, results written for the best schedules so far");
+    } else if is_proto {
+        let mut bnb =
+            BranchAndBound::new(&utils, n_ants, config.bnb_node_limit, config.bnb_time_limit);
+        let exact = bnb.solve(&best_result.schedule);
+        info!("Branch and bound: Makespan {}", exact.makespan);
+        if exact.proven_optimal {
+            info!("Branch and bound: Proven optimal");
+        } else {
+            info!(
+                "Branch and bound: Limit reached, Lower Bound: {} Gap: {:.2}%",
+                exact.lower_bound,
+                exact.gap() * 100.0
+            );
+        }
+        info!(
+            "Branch and bound: Nodes {} in {:?}",
+            exact.nodes, exact.elapsed
+        );
+        for violation in
+            validator::validate(&utils, n_ants, &exact.schedule.entries(), exact.makespan)
+        {
+            warn!("Branch and bound schedule invalid: {}", violation);
+        }
+        // the chart goes to stdout, shown at the default level only
+        if logging::enabled(LogLevel::Info) && !logging::json() {
+            Utils::print_gantt_chart(&exact.schedule);
+        }
+        if let Some(results_path) = results_path {
+            gantt::write_html(
+                &exact.schedule,
+                &utils,
+                &format!("{} - branch and bound", graph_name),
+                &format!("{}/exact_schedule.html", results_path),
+            )?;
+        }
+        info!(
+            "Colony Gap to exact: {:.2}%",
+            (best_result.best_cycle - exact.makespan) as f64 / exact.makespan.max(1) as f64 * 100.0
+        );
+        exact_result = Some(exact);
+    }
+
+    // gantt_chart, see best_schedule.svg / best_schedule.html for large makespans
+    // Utils::print_gantt_chart(&best_result.schedule);
+
+    Ok(SolveResult {
+        n_tasks: utils.n_tasks,
+        n_workers: n_ants,
+        makespan: best_result.best_cycle,
+        lower_bound: bounds.best(),
+        gap: best_result.gap,
+        seed,
+        schedule: best_result.schedule.clone(),
+        evaluations: stop.evaluations(),
+        colonies: results,
+        cancelled,
+        exact: exact_result,
+        elapsed: end_time.duration_since(start_time),
+    })
+}
+
+fn json_option<T: fmt::Display>(value: Option<T>) -> String {
+    value.map_or("null".to_string(), |v| v.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::ops::ControlFlow;
+
+    use super::*;
+    use crate::logging::{set_log_level, LogLevel};
+    use crate::observer::Event;
+
+    // a fresh results directory holding a small layered graph, every task waits for the one
+    // four before it and every third one also for the one three before it
+    fn instance(name: &str) -> (Instance, String) {
+        set_log_level(LogLevel::Error);
+        let dir = std::env::temp_dir()
+            .join(format!("task_scheduling_{}_{}", name, std::process::id()))
+            .to_string_lossy()
+            .to_string();
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let n = 120;
+        let mut stg = format!("{}\n0 0 0\n", n);
+        for task in 1..=n {
+            let mut predecessors = vec![(task - 4).max(0)];
+            if task > 4 && task % 3 == 0 {
+                predecessors.push(task - 3);
+            }
+            let predecessors: Vec<String> = predecessors.iter().map(|p| p.to_string()).collect();
+            stg.push_str(&format!(
+                "{} {} {} {}\n",
+                task,
+                task * 7 % 9 + 1,
+                predecessors.len(),
+                predecessors.join(" ")
+            ));
+        }
+        stg.push_str(&format!(
+            "{} 0 4 {} {} {} {}\n",
+            n + 1,
+            n - 3,
+            n - 2,
+            n - 1,
+            n
+        ));
+        fs::write(format!("{}/graph.stg", dir), stg).unwrap();
+        let instance = Instance::open(&format!("{}/", dir), "graph.stg", false).unwrap();
+        (instance, format!("{}/results", dir))
+    }
+
+    fn config() -> SolverConfig {
+        SolverConfig::new()
+            .workers(3)
+            .colonies(2)
+            .seed(11)
+            .stop_at_lower_bound(false)
+            .snapshots(SnapshotPolicy::Off)
+            .trails(None)
+            .checkpoint_every(10)
+    }
+
+    #[test]
+    fn resumed_colony_that_had_stopped_reports_its_last_epoch() {
+        let (instance, results) = instance("stopped");
+        let config = config().epochs(2000).stagnation(15);
+        // the first colony to stop on its own interrupts the run
+        let cancel = CancelToken::new();
+        let interrupt = |event: &Event| {
+            if let Event::ColonyFinished { reason, .. } = event {
+                if *reason != StopReason::Cancelled {
+                    cancel.cancel();
+                }
+            }
+            ControlFlow::Continue(())
+        };
+        let options = SolveOptions::new()
+            .results_dir(&results)
+            .cancel(&cancel)
+            .observe(&interrupt);
+        let first = run_colonies(&instance, &config, &options, Vec::new()).unwrap();
+        assert!(first.cancelled);
+
+        let resumed = resume(&RunCheckpoint::dir(&results), &CancelToken::new()).unwrap();
+        let stopped: Vec<&ColonyResult> = first
+            .colonies
+            .iter()
+            .filter(|colony| colony.stop_reason != StopReason::Cancelled)
+            .collect();
+        assert!(!stopped.is_empty());
+        for before in stopped {
+            let after = &resumed.colonies[before.thread_id as usize];
+            assert_eq!(after.thread_id, before.thread_id);
+            assert_eq!(after.stop_reason, before.stop_reason);
+            assert_eq!(after.best_cycle, before.best_cycle);
+            assert_eq!(after.deposit_rate, before.deposit_rate);
+            assert_eq!(after.evaporation_rate, before.evaporation_rate);
+            assert_eq!(after.base_chance, before.base_chance);
+            assert_eq!(after.max_weight, before.max_weight);
+            assert_eq!(after.alfa, before.alfa);
+            assert_eq!(after.beta, before.beta);
+            assert!(after.deposit_rate > 0.0 && after.max_weight > 0.0);
+        }
+        let _ = fs::remove_dir_all(results.trim_end_matches("/results"));
+    }
+
+    // every telemetry line of every colony without its timing, the last column
+    fn telemetry(results: &str, colonies: i32) -> Vec<String> {
+        (0..colonies)
+            .flat_map(|i| {
+                let path = format!("{}/thread_{}/thread_{}_graph.stg.csv", results, i, i);
+                fs::read_to_string(path)
+                    .unwrap()
+                    .lines()
+                    .map(|line| line.rsplit_once(',').unwrap().0.to_string())
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn resumed_run_ends_like_an_uninterrupted_one() {
+        let (instance, results) = instance("resume");
+        let straight = format!("{}/straight", results);
+        let interrupted = format!("{}/interrupted", results);
+        let config = config().epochs(150).adaptive(true);
+
+        let options = SolveOptions::new().results_dir(&straight);
+        let expected = run_colonies(&instance, &config, &options, Vec::new()).unwrap();
+
+        let cancel = CancelToken::new();
+        let interrupt = |event: &Event| {
+            if let Event::EpochFinished { record } = event {
+                if record.epoch == 40 {
+                    cancel.cancel();
+                }
+            }
+            ControlFlow::Continue(())
+        };
+        let options = SolveOptions::new()
+            .results_dir(&interrupted)
+            .cancel(&cancel)
+            .observe(&interrupt);
+        let first = run_colonies(&instance, &config, &options, Vec::new()).unwrap();
+        assert!(first.cancelled);
+        let resumed = resume(&RunCheckpoint::dir(&interrupted), &CancelToken::new()).unwrap();
+
+        assert!(!resumed.cancelled);
+        assert_eq!(resumed.makespan, expected.makespan);
+        for (after, before) in resumed.colonies.iter().zip(&expected.colonies) {
+            assert_eq!(after.best_cycle, before.best_cycle);
+            assert_eq!(after.schedule.entries(), before.schedule.entries());
+        }
+        assert_eq!(telemetry(&interrupted, 2), telemetry(&straight, 2));
+        let _ = fs::remove_dir_all(results.trim_end_matches("/results"));
+    }
+
+    #[test]
+    fn invalid_config_is_rejected_before_the_run() {
+        let (instance, results) = instance("invalid");
+        let config = config().workers(0);
+        let error = solve_file(
+            &config,
+            &instance.file_path,
+            &instance.name,
+            &results,
+            &CancelToken::new(),
+        )
+        .err()
+        .unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        assert!(fs::metadata(&results).is_err());
+        let _ = fs::remove_dir_all(results.trim_end_matches("/results"));
+    }
+}
@@ -0,0 +1,503 @@
+use std::f64::consts::PI;
+
+// Descriptive statistics and the non parametric tests used to compare solver configurations
+
+pub(crate) fn mean(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        return f64::NAN;
+    }
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+pub(crate) fn median(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        return f64::NAN;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let mid = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        (sorted[mid - 1] + sorted[mid]) / 2.0
+    } else {
+        sorted[mid]
+    }
+}
+
+// sample standard deviation, 0.0 for a single value
+pub(crate) fn std_dev(values: &[f64]) -> f64 {
+    if values.len() < 2 {
+        return 0.0;
+    }
+    let m = mean(values);
+    let squares: f64 = values.iter().map(|v| (v - m) * (v - m)).sum();
+    (squares / (values.len() - 1) as f64).sqrt()
+}
+
+// two sided 95% Student t quantiles for 1 to 30 degrees of freedom
+const T_975: [f64; 30] = [
+    12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228, 2.201, 2.179, 2.160,
+    2.145, 2.131, 2.120, 2.110, 2.101, 2.093, 2.086, 2.080, 2.074, 2.069, 2.064, 2.060, 2.056,
+    2.052, 2.048, 2.045, 2.042,
+];
+
+// two sided 95% quantile of Student's t, the normal one past the table
+pub(crate) fn t_975(df: usize) -> f64 {
+    T_975.get(df.max(1) - 1).copied().unwrap_or(1.960)
+}
+
+// 95% confidence interval of the mean, t based so it holds for a handful of seeds
+pub(crate) fn confidence_interval(values: &[f64]) -> (f64, f64) {
+    let m = mean(values);
+    if values.len() < 2 {
+        return (m, m);
+    }
+    let half = t_975(values.len() - 1) * std_dev(values) / (values.len() as f64).sqrt();
+    (m - half, m + half)
+}
+
+// 1-based ranks, ties get the mean of the ranks they span
+pub(crate) fn ranks(values: &[f64]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|&a, &b| values[a].total_cmp(&values[b]));
+    let mut ranks = vec![0.0; values.len()];
+    let mut i = 0;
+    while i < order.len() {
+        let mut j = i;
+        while j + 1 < order.len() && values[order[j + 1]] == values[order[i]] {
+            j += 1;
+        }
+        let rank = (i + j) as f64 / 2.0 + 1.0;
+        for &k in &order[i..=j] {
+            ranks[k] = rank;
+        }
+        i = j + 1;
+    }
+    ranks
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Wilcoxon {
+    // pairs with a non zero difference
+    pub n: usize,
+    // rank sums of the pairs where x is larger and where y is larger
+    pub w_plus: f64,
+    pub w_minus: f64,
+    pub z: f64,
+    // two sided, from the normal approximation (rough below about 10 pairs)
+    pub p_value: f64,
+}
+
+// Wilcoxon signed-rank test on paired samples
+pub(crate) fn wilcoxon_signed_rank(x: &[f64], y: &[f64]) -> Wilcoxon {
+    let differences: Vec<f64> = x
+        .iter()
+        .zip(y)
+        .map(|(a, b)| a - b)
+        .filter(|d| *d != 0.0)
+        .collect();
+    let n = differences.len();
+    if n == 0 {
+        return Wilcoxon {
+            n,
+            w_plus: 0.0,
+            w_minus: 0.0,
+            z: 0.0,
+            p_value: 1.0,
+        };
+    }
+    let absolute: Vec<f64> = differences.iter().map(|d| d.abs()).collect();
+    let ranks = ranks(&absolute);
+    let w_plus: f64 = differences
+        .iter()
+        .zip(&ranks)
+        .filter(|(d, _)| **d > 0.0)
+        .map(|(_, r)| r)
+        .sum();
+    let total = (n * (n + 1)) as f64 / 2.0;
+    let w_minus = total - w_plus;
+
+    let n = n as f64;
+    let expected = n * (n + 1.0) / 4.0;
+    let mut variance = n * (n + 1.0) * (2.0 * n + 1.0) / 24.0;
+    for t in tie_sizes(&absolute) {
+        variance -= (t * t * t - t) / 48.0;
+    }
+    let z = if variance > 0.0 {
+        // continuity correction towards the mean
+        let distance = (w_plus - expected).abs() - 0.5;
+        distance.max(0.0) / variance.sqrt() * (w_plus - expected).signum()
+    } else {
+        0.0
+    };
+    Wilcoxon {
+        n: differences.len(),
+        w_plus,
+        w_minus,
+        z,
+        p_value: (2.0 * (1.0 - normal_cdf(z.abs()))).min(1.0),
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct Friedman {
+    pub statistic: f64,
+    pub df: usize,
+    pub p_value: f64,
+    // mean rank of every treatment over the blocks, 1 is best when lower values are better
+    pub mean_ranks: Vec<f64>,
+}
+
+// Friedman test, every block (instance) holds one value per treatment (configuration)
+pub(crate) fn friedman(blocks: &[Vec<f64>]) -> Friedman {
+    let k = blocks.first().map_or(0, |b| b.len());
+    let n = blocks.len() as f64;
+    let mut rank_sums = vec![0.0; k];
+    let mut tie_correction = 0.0;
+    for block in blocks {
+        for (sum, rank) in rank_sums.iter_mut().zip(ranks(block)) {
+            *sum += rank;
+        }
+        tie_correction += tie_sizes(block).map(|t| t * t * t - t).sum::<f64>();
+    }
+    let mean_ranks: Vec<f64> = rank_sums.iter().map(|s| s / n.max(1.0)).collect();
+    if k < 2 || blocks.is_empty() {
+        return Friedman {
+            statistic: 0.0,
+            df: k.saturating_sub(1),
+            p_value: 1.0,
+            mean_ranks,
+        };
+    }
+
+    let kf = k as f64;
+    let squares: f64 = rank_sums.iter().map(|r| r * r).sum();
+    let statistic = 12.0 / (n * kf * (kf + 1.0)) * squares - 3.0 * n * (kf + 1.0);
+    let denominator = 1.0 - tie_correction / (n * (kf * kf * kf - kf));
+    let statistic = if denominator > 0.0 {
+        statistic / denominator
+    } else {
+        0.0
+    };
+    Friedman {
+        statistic,
+        df: k - 1,
+        p_value: chi_square_sf(statistic, (k - 1) as f64),
+        mean_ranks,
+    }
+}
+
+// Treatments not significantly worse than the best one after a significant Friedman test,
+// by the Conover post-hoc comparison at the 95% level (the elimination step of F-race)
+pub(crate) fn friedman_survivors(blocks: &[Vec<f64>]) -> Vec<usize> {
+    let k = blocks.first().map_or(0, |b| b.len());
+    let n = blocks.len();
+    if k < 2 || n < 2 {
+        return (0..k).collect();
+    }
+    let mut rank_sums = vec![0.0; k];
+    let mut squares = 0.0;
+    for block in blocks {
+        for (sum, rank) in rank_sums.iter_mut().zip(ranks(block)) {
+            *sum += rank;
+            squares += rank * rank;
+        }
+    }
+    let (nf, kf) = (n as f64, k as f64);
+    let sum_squares: f64 = rank_sums.iter().map(|r| r * r).sum();
+    let variance = 2.0 * (nf * squares - sum_squares) / ((nf - 1.0) * (kf - 1.0));
+    if variance <= 0.0 {
+        return (0..k).collect();
+    }
+    let best = rank_sums.iter().copied().fold(f64::INFINITY, f64::min);
+    let difference = t_975((n - 1) * (k - 1)) * variance.sqrt();
+    (0..k)
+        .filter(|&j| rank_sums[j] - best <= difference)
+        .collect()
+}
+
+// sizes of the groups of equal values, only groups larger than one
+fn tie_sizes(values: &[f64]) -> impl Iterator<Item = f64> {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let mut sizes = Vec::new();
+    let mut i = 0;
+    while i < sorted.len() {
+        let j = sorted[i..].iter().take_while(|&&v| v == sorted[i]).count();
+        if j > 1 {
+            sizes.push(j as f64);
+        }
+        i += j;
+    }
+    sizes.into_iter()
+}
+
+pub(crate) fn normal_cdf(z: f64) -> f64 {
+    0.5 * (1.0 + erf(z / std::f64::consts::SQRT_2))
+}
+
+// Abramowitz and Stegun 7.1.26, absolute error below 1.5e-7
+fn erf(x: f64) -> f64 {
+    let t = 1.0 / (1.0 + 0.3275911 * x.abs());
+    let poly = t
+        * (0.254829592
+            + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    let y = 1.0 - poly * (-x * x).exp();
+    y.copysign(x)
+}
+
+// probability of a chi square variable with df degrees of freedom being above x
+pub(crate) fn chi_square_sf(x: f64, df: f64) -> f64 {
+    if x <= 0.0 {
+        return 1.0;
+    }
+    upper_gamma_regularized(df / 2.0, x / 2.0)
+}
+
+// Q(a, x) by its series below a + 1 and its continued fraction above (Numerical Recipes 6.2)
+fn upper_gamma_regularized(a: f64, x: f64) -> f64 {
+    let log_prefix = a * x.ln() - x - ln_gamma(a);
+    if x < a + 1.0 {
+        let mut term = 1.0 / a;
+        let mut sum = term;
+        let mut ap = a;
+        for _ in 0..500 {
+            ap += 1.0;
+            term *= x / ap;
+            sum += term;
+            if term.abs() < sum.abs() * 1e-15 {
+                break;
+            }
+        }
+        (1.0 - sum * log_prefix.exp()).clamp(0.0, 1.0)
+    } else {
+        let tiny = 1e-300;
+        let mut b = x + 1.0 - a;
+        let mut c = 1.0 / tiny;
+        let mut d = 1.0 / b;
+        let mut h = d;
+        for i in 1..500 {
+            let an = -(i as f64) * (i as f64 - a);
+            b += 2.0;
+            d = an * d + b;
+            if d.abs() < tiny {
+                d = tiny;
+            }
+            c = b + an / c;
+            if c.abs() < tiny {
+                c = tiny;
+            }
+            d = 1.0 / d;
+            let delta = d * c;
+            h *= delta;
+            if (delta - 1.0).abs() < 1e-15 {
+                break;
+            }
+        }
+        (log_prefix.exp() * h).clamp(0.0, 1.0)
+    }
+}
+
+// Lanczos approximation (g = 7, n = 9)
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+    if x < 0.5 {
+        // reflection formula
+        return (PI / (PI * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+    let x = x - 1.0;
+    let mut sum = COEFFICIENTS[0];
+    for (i, c) in COEFFICIENTS.iter().enumerate().skip(1) {
+        sum += c / (x + i as f64);
+    }
+    let t = x + 7.5;
+    0.5 * (2.0 * PI).ln() + (x + 0.5) * t.ln() - t + sum.ln()
+}
+
+// Dolan-More performance profiles, costs[instance][solver] with lower is better.
+// For every solver the fraction of instances it solved within a factor tau of the best solver,
+// as the points (tau, fraction) where the fraction steps up.
+pub(crate) fn performance_profile(costs: &[Vec<f64>]) -> Vec<Vec<(f64, f64)>> {
+    let k = costs.first().map_or(0, |c| c.len());
+    let n = costs.len() as f64;
+    (0..k)
+        .map(|solver| {
+            let mut ratios: Vec<f64> = costs
+                .iter()
+                .map(|instance| {
+                    let best = instance.iter().copied().fold(f64::INFINITY, f64::min);
+                    if best > 0.0 {
+                        instance[solver] / best
+                    } else if instance[solver] == best {
+                        1.0
+                    } else {
+                        f64::INFINITY
+                    }
+                })
+                .collect();
+            ratios.sort_by(f64::total_cmp);
+            let mut points: Vec<(f64, f64)> = Vec::new();
+            for (i, &tau) in ratios.iter().enumerate() {
+                if !tau.is_finite() {
+                    break;
+                }
+                let fraction = (i + 1) as f64 / n;
+                match points.last_mut() {
+                    Some(last) if last.0 == tau => last.1 = fraction,
+                    _ => points.push((tau, fraction)),
+                }
+            }
+            points
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64, tolerance: f64) -> bool {
+        (a - b).abs() < tolerance
+    }
+
+    #[test]
+    fn descriptive_statistics() {
+        assert_eq!(mean(&[1.0, 2.0, 6.0]), 3.0);
+        assert_eq!(median(&[5.0, 1.0, 3.0]), 3.0);
+        assert_eq!(median(&[4.0, 1.0, 3.0, 2.0]), 2.5);
+        assert!(median(&[]).is_nan());
+        assert_eq!(std_dev(&[1.0, 2.0, 3.0]), 1.0);
+        assert_eq!(std_dev(&[4.0]), 0.0);
+        let (low, high) = confidence_interval(&[1.0, 2.0, 3.0]);
+        assert!(close(low, 2.0 - 4.303 / 3f64.sqrt(), 1e-12));
+        assert!(close(high, 2.0 + 4.303 / 3f64.sqrt(), 1e-12));
+        assert_eq!(t_975(0), 12.706);
+        assert_eq!(t_975(100), 1.960);
+    }
+
+    #[test]
+    fn ties_share_their_mean_rank() {
+        assert_eq!(ranks(&[3.0, 1.0, 2.0, 1.0]), vec![4.0, 1.5, 3.0, 1.5]);
+        assert_eq!(ranks(&[7.0, 7.0, 7.0]), vec![2.0, 2.0, 2.0]);
+    }
+
+    #[test]
+    fn wilcoxon_without_ties() {
+        let x = [11.0, 12.0, 10.0, 14.0, 15.0, 16.0, 17.0, 18.0];
+        let y = [10.0, 10.0, 13.0, 10.0, 10.0, 10.0, 10.0, 10.0];
+        let test = wilcoxon_signed_rank(&x, &y);
+        assert_eq!(test.n, 8);
+        assert_eq!(test.w_plus, 33.0);
+        assert_eq!(test.w_minus, 3.0);
+        // (|33 - 18| - 0.5) / sqrt(51)
+        assert!(close(test.z, 2.030406, 1e-6));
+        assert!(close(test.p_value, 0.042315, 1e-5));
+    }
+
+    #[test]
+    fn wilcoxon_with_ties_and_zero_differences() {
+        let x = [2.0, 2.0, 3.0, 1.0, 4.0, 5.0];
+        let y = [1.0, 1.0, 1.0, 3.0, 1.0, 5.0];
+        let test = wilcoxon_signed_rank(&x, &y);
+        assert_eq!(test.n, 5);
+        assert_eq!(test.w_plus, 11.5);
+        assert_eq!(test.w_minus, 3.5);
+        // the variance loses (2^3 - 2) / 48 for each pair of ties
+        assert!(close(test.z, 3.5 / 13.5f64.sqrt(), 1e-12));
+        assert!(close(test.p_value, 0.340803, 1e-5));
+
+        let same = wilcoxon_signed_rank(&y, &y);
+        assert_eq!(same.n, 0);
+        assert_eq!(same.p_value, 1.0);
+    }
+
+    #[test]
+    fn friedman_ranks_a_consistent_order() {
+        let blocks = vec![
+            vec![1.0, 2.0, 3.0],
+            vec![10.0, 20.0, 30.0],
+            vec![5.0, 6.0, 9.0],
+            vec![0.5, 0.7, 0.9],
+        ];
+        let test = friedman(&blocks);
+        assert_eq!(test.df, 2);
+        assert_eq!(test.mean_ranks, vec![1.0, 2.0, 3.0]);
+        assert!(close(test.statistic, 8.0, 1e-12));
+        // the chi square survival function with 2 degrees of freedom is exp(-x / 2)
+        assert!(close(test.p_value, (-4.0f64).exp(), 1e-9));
+    }
+
+    #[test]
+    fn friedman_without_differences() {
+        let test = friedman(&[vec![1.0, 1.0], vec![2.0, 2.0]]);
+        assert_eq!(test.statistic, 0.0);
+        assert_eq!(test.p_value, 1.0);
+        assert_eq!(friedman(&[vec![1.0]]).p_value, 1.0);
+    }
+
+    #[test]
+    fn conover_drops_the_clearly_worse() {
+        let blocks: Vec<Vec<f64>> = (0..6)
+            .map(|i| {
+                if i % 2 == 0 {
+                    vec![1.0, 2.0, 3.0]
+                } else {
+                    vec![1.0, 3.0, 2.0]
+                }
+            })
+            .collect();
+        assert_eq!(friedman_survivors(&blocks), vec![0]);
+
+        // the first two swap places, only the third is always last
+        let blocks: Vec<Vec<f64>> = (0..6)
+            .map(|i| {
+                if i % 2 == 0 {
+                    vec![1.0, 2.0, 3.0]
+                } else {
+                    vec![2.0, 1.0, 3.0]
+                }
+            })
+            .collect();
+        assert_eq!(friedman_survivors(&blocks), vec![0, 1]);
+
+        let ties = vec![vec![4.0, 4.0, 4.0]; 6];
+        assert_eq!(friedman_survivors(&ties), vec![0, 1, 2]);
+        assert_eq!(friedman_survivors(&ties[..1]), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn distributions() {
+        assert!(close(normal_cdf(0.0), 0.5, 1e-7));
+        assert!(close(normal_cdf(1.959964), 0.975, 1e-6));
+        assert!(close(normal_cdf(-1.959964), 0.025, 1e-6));
+        assert!(close(chi_square_sf(3.841459, 1.0), 0.05, 1e-6));
+        assert!(close(chi_square_sf(18.307038, 10.0), 0.05, 1e-6));
+        assert!(close(chi_square_sf(0.5, 4.0), 0.973501, 1e-6));
+        assert_eq!(chi_square_sf(0.0, 3.0), 1.0);
+        assert!(close(ln_gamma(5.0), 24f64.ln(), 1e-12));
+        assert!(close(ln_gamma(0.5), PI.sqrt().ln(), 1e-12));
+    }
+
+    #[test]
+    fn performance_profile_steps() {
+        let costs = vec![
+            vec![1.0, 2.0],
+            vec![2.0, 2.0],
+            vec![4.0, 2.0],
+            vec![0.0, 1.0],
+        ];
+        let profiles = performance_profile(&costs);
+        assert_eq!(profiles[0], vec![(1.0, 0.75), (2.0, 1.0)]);
+        // never within a finite factor of a zero cost
+        assert_eq!(profiles[1], vec![(1.0, 0.5), (2.0, 0.75)]);
+    }
+}
@@ -0,0 +1,174 @@
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+use crate::config::SolverConfig;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StopReason {
+    Epochs,
+    Stagnation,
+    TimeLimit,
+    Evaluations,
+    LowerBound,
+    Target,
+    Cancelled,
+    // an observer asked the run to stop
+    Observer,
+}
+
+impl fmt::Display for StopReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            StopReason::Epochs => "epochs",
+            StopReason::Stagnation => "stagnation",
+            StopReason::TimeLimit => "time limit",
+            StopReason::Evaluations => "evaluations",
+            StopReason::LowerBound => "lower bound",
+            StopReason::Target => "target",
+            StopReason::Cancelled => "cancelled",
+            StopReason::Observer => "observer",
+        })
+    }
+}
+
+impl FromStr for StopReason {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<StopReason, String> {
+        match s {
+            "epochs" => Ok(StopReason::Epochs),
+            "stagnation" => Ok(StopReason::Stagnation),
+            "time limit" => Ok(StopReason::TimeLimit),
+            "evaluations" => Ok(StopReason::Evaluations),
+            "lower bound" => Ok(StopReason::LowerBound),
+            "target" => Ok(StopReason::Target),
+            "cancelled" => Ok(StopReason::Cancelled),
+            "observer" => Ok(StopReason::Observer),
+            _ => Err(format!("unknown stop reason '{}'", s)),
+        }
+    }
+}
+
+// Set from outside a run, e.g. on Ctrl-C, to stop every colony after its current epoch.
+// Clones share the flag.
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> CancelToken {
+        CancelToken::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+// The stopping rules of one run, shared by all its colonies, the first rule reached ends the
+// search. Epochs and stagnation are counted per colony and stop only that colony; the time
+// limit, the evaluation budget, the lower bound, the target and a cancellation stop every
+// colony, so the first one to reach the optimum ends the run.
+pub(crate) struct StopCondition {
+    pub max_epochs: i32,
+    // epochs without a better makespan
+    pub stagnation: Option<i32>,
+    // the benchmark of the config, 0 never stops
+    pub target: i32,
+    pub lower_bound: i32,
+    stop_at_lower_bound: bool,
+    time_limit: Option<Duration>,
+    // schedules built over all the colonies
+    max_evaluations: Option<u64>,
+    start: Instant,
+    evaluations: AtomicU64,
+    global: OnceLock<StopReason>,
+    cancel: CancelToken,
+}
+
+impl StopCondition {
+    pub fn new(config: &SolverConfig, lower_bound: i32, cancel: &CancelToken) -> StopCondition {
+        StopCondition {
+            max_epochs: config.epochs,
+            stagnation: config.stagnation,
+            target: config.benchmark,
+            lower_bound,
+            stop_at_lower_bound: config.stop_at_lower_bound,
+            time_limit: config.time_limit,
+            max_evaluations: config.max_evaluations,
+            start: Instant::now(),
+            evaluations: AtomicU64::new(0),
+            global: OnceLock::new(),
+            cancel: cancel.clone(),
+        }
+    }
+
+    // a run resumed from checkpoints keeps the budgets it already spent
+    pub fn resume(&mut self, evaluations: u64, elapsed: Duration) {
+        self.evaluations = AtomicU64::new(evaluations);
+        self.start = Instant::now().checked_sub(elapsed).unwrap_or(self.start);
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    pub fn evaluations(&self) -> u64 {
+        self.evaluations.load(Ordering::Relaxed)
+    }
+
+    // why every colony stops, once any of them hit a shared rule
+    pub fn global(&self) -> Option<StopReason> {
+        if let Some(&reason) = self.global.get() {
+            return Some(reason);
+        }
+        if self.cancel.is_cancelled() {
+            return Some(self.stop_all(StopReason::Cancelled));
+        }
+        match self.time_limit {
+            Some(limit) if self.start.elapsed() >= limit => {
+                Some(self.stop_all(StopReason::TimeLimit))
+            }
+            _ => None,
+        }
+    }
+
+    // stops every colony after its current epoch, unless a shared rule already did
+    pub fn request(&self, reason: StopReason) {
+        self.stop_all(reason);
+    }
+
+    // the first reason wins, a colony stopping later reports the same one
+    fn stop_all(&self, reason: StopReason) -> StopReason {
+        *self.global.get_or_init(|| reason)
+    }
+
+    // Called by a colony after each epoch with the makespan it built and the epochs since its
+    // best improved, the reason to stop if there is one. The epoch limit is the colony's loop.
+    pub fn after_epoch(&self, cycles: i32, stagnant_epochs: i32) -> Option<StopReason> {
+        let evaluations = self.evaluations.fetch_add(1, Ordering::Relaxed) + 1;
+        // a solution on the lower bound is proven optimal, nothing left to search
+        if self.stop_at_lower_bound && cycles <= self.lower_bound {
+            return Some(self.stop_all(StopReason::LowerBound));
+        }
+        if cycles <= self.target {
+            return Some(self.stop_all(StopReason::Target));
+        }
+        if self.max_evaluations.is_some_and(|max| evaluations >= max) {
+            return Some(self.stop_all(StopReason::Evaluations));
+        }
+        if let Some(reason) = self.global() {
+            return Some(reason);
+        }
+        if self.stagnation.is_some_and(|max| stagnant_epochs >= max) {
+            return Some(StopReason::Stagnation);
+        }
+        None
+    }
+}
@@ -0,0 +1,284 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Seek, SeekFrom, Write};
+
+use crate::export::create_parent;
+use crate::logging::warn;
+
+// Summary of the pheromone trails at the end of an epoch
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PheromoneStats {
+    pub sum: f64,
+    // Shannon entropy (nats) of the weights normalised by their sum, low when a few trails dominate
+    pub entropy: f64,
+    pub min: f64,
+    pub max: f64,
+    pub n_edges: usize,
+}
+
+// One line of the telemetry log, written for every epoch of every colony
+#[derive(Clone, Debug, Default)]
+pub struct EpochRecord {
+    pub colony_id: i32,
+    pub epoch: i32,
+    pub current: i32,
+    pub best: i32,
+    pub lower_bound: i32,
+    pub alfa: f64,
+    pub beta: f64,
+    pub base_chance: f64,
+    pub deposit_rate: f64,
+    pub evaporation_rate: f64,
+    pub max_weight: f64,
+    pub pheromones: PheromoneStats,
+    pub construction_ms: f64,
+}
+
+impl EpochRecord {
+    // relative distance of the best makespan to the lower bound
+    pub fn gap(&self) -> f64 {
+        if self.lower_bound <= 0 {
+            return 0.0;
+        }
+        (self.best - self.lower_bound) as f64 / self.lower_bound as f64
+    }
+}
+
+pub(crate) const HEADER: &str = "colony_id,epoch,current,best,lower_bound,gap,alfa,beta,base_chance,deposit_rate,evaporation_rate,max_weight,ph_sum,ph_entropy,ph_min,ph_max,ph_edges,construction_ms";
+
+// CSV with a header line, one record per epoch
+pub(crate) struct TelemetryLog {
+    out: BufWriter<File>,
+}
+
+impl TelemetryLog {
+    // truncates a log left by a previous run
+    pub fn create(path: &str) -> io::Result<TelemetryLog> {
+        create_parent(path)?;
+        let mut out = BufWriter::new(File::create(path)?);
+        writeln!(out, "{}", HEADER)?;
+        Ok(TelemetryLog { out })
+    }
+
+    // continues a log from a checkpoint, the records written after it are dropped
+    pub fn resume(path: &str, len: u64) -> io::Result<TelemetryLog> {
+        let mut file = OpenOptions::new().write(true).open(path)?;
+        file.set_len(len)?;
+        file.seek(SeekFrom::End(0))?;
+        Ok(TelemetryLog {
+            out: BufWriter::new(file),
+        })
+    }
+
+    // bytes written so far, everything is flushed to the file first
+    pub fn position(&mut self) -> io::Result<u64> {
+        self.out.flush()?;
+        self.out.get_mut().stream_position()
+    }
+
+    pub fn log(&mut self, record: &EpochRecord) -> io::Result<()> {
+        let ph = &record.pheromones;
+        writeln!(
+            self.out,
+            "{},{},{},{},{},{:.6},{},{},{},{},{},{},{},{:.6},{},{},{},{:.3}",
+            record.colony_id,
+            record.epoch,
+            record.current,
+            record.best,
+            record.lower_bound,
+            record.gap(),
+            record.alfa,
+            record.beta,
+            record.base_chance,
+            record.deposit_rate,
+            record.evaporation_rate,
+            record.max_weight,
+            ph.sum,
+            ph.entropy,
+            ph.min,
+            ph.max,
+            ph.n_edges,
+            record.construction_ms
+        )
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+// Reads back a log written by TelemetryLog, None when the file is some other CSV
+pub(crate) fn read_log(path: &str) -> io::Result<Option<Vec<EpochRecord>>> {
+    let contents = fs::read_to_string(path)?;
+    let mut lines = contents.lines();
+    if lines.next().map(str::trim) != Some(HEADER) {
+        return Ok(None);
+    }
+    let invalid = |line_number: usize| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}:{}: malformed telemetry record", path, line_number + 2),
+        )
+    };
+    let lines: Vec<(usize, &str)> = lines
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .collect();
+    let mut records = Vec::with_capacity(lines.len());
+    for (i, &(line_number, line)) in lines.iter().enumerate() {
+        match parse_record(line) {
+            Some(record) => records.push(record),
+            // a run killed mid-write leaves a partial last line, the records before it stand
+            None if i + 1 == lines.len() => {
+                warn!("{}: partial last telemetry record skipped", path);
+            }
+            None => return Err(invalid(line_number)),
+        }
+    }
+    Ok(Some(records))
+}
+
+// one line of the log, None when it is not a whole record
+fn parse_record(line: &str) -> Option<EpochRecord> {
+    let fields: Vec<&str> = line.split(',').collect();
+    if fields.len() != HEADER.split(',').count() {
+        return None;
+    }
+    let int = |i: usize| fields[i].parse::<i32>().ok();
+    let float = |i: usize| fields[i].parse::<f64>().ok();
+    Some(EpochRecord {
+        colony_id: int(0)?,
+        epoch: int(1)?,
+        current: int(2)?,
+        best: int(3)?,
+        lower_bound: int(4)?,
+        // 5 is the gap, derived from best and lower_bound
+        alfa: float(6)?,
+        beta: float(7)?,
+        base_chance: float(8)?,
+        deposit_rate: float(9)?,
+        evaporation_rate: float(10)?,
+        max_weight: float(11)?,
+        pheromones: PheromoneStats {
+            sum: float(12)?,
+            entropy: float(13)?,
+            min: float(14)?,
+            max: float(15)?,
+            n_edges: fields[16].parse().ok()?,
+        },
+        construction_ms: float(17)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!(
+                "task_scheduling_telemetry_{}_{}",
+                std::process::id(),
+                name
+            ))
+            .to_string_lossy()
+            .to_string()
+    }
+
+    fn record(epoch: i32) -> EpochRecord {
+        EpochRecord {
+            colony_id: 1,
+            epoch,
+            current: 120 - epoch,
+            best: 110,
+            lower_bound: 100,
+            alfa: 1.0,
+            beta: 2.5,
+            base_chance: 0.25,
+            deposit_rate: 0.01,
+            evaporation_rate: 0.003,
+            max_weight: 0.125,
+            pheromones: PheromoneStats {
+                sum: 4.5,
+                entropy: 1.25,
+                min: 0.001,
+                max: 0.5,
+                n_edges: 42,
+            },
+            construction_ms: 1.5,
+        }
+    }
+
+    fn write(path: &str, epochs: i32) -> TelemetryLog {
+        let mut log = TelemetryLog::create(path).unwrap();
+        for epoch in 0..epochs {
+            log.log(&record(epoch)).unwrap();
+        }
+        log.flush().unwrap();
+        log
+    }
+
+    #[test]
+    fn reads_back_what_it_logged() {
+        let path = path("round_trip.csv");
+        write(&path, 3);
+        let records = read_log(&path).unwrap().unwrap();
+        let _ = fs::remove_file(&path);
+        assert_eq!(records.len(), 3);
+        let last = &records[2];
+        let expected = record(2);
+        assert_eq!(last.epoch, 2);
+        assert_eq!(last.current, expected.current);
+        assert_eq!(last.evaporation_rate, expected.evaporation_rate);
+        assert_eq!(last.pheromones.n_edges, 42);
+        assert_eq!(last.pheromones.entropy, 1.25);
+        assert_eq!(last.construction_ms, 1.5);
+        assert!((last.gap() - 0.1).abs() < 1e-12);
+    }
+
+    #[test]
+    fn skips_a_partial_last_line_only() {
+        let path = path("partial.csv");
+        write(&path, 2);
+        let whole = fs::read_to_string(&path).unwrap();
+        fs::write(&path, format!("{}1,2,118,110", whole)).unwrap();
+        assert_eq!(read_log(&path).unwrap().unwrap().len(), 2);
+
+        // the same line with a whole record after it
+        let first = whole.lines().nth(1).unwrap();
+        fs::write(&path, format!("{}1,2,118,110\n{}\n", whole, first)).unwrap();
+        let error = read_log(&path).unwrap_err();
+        let _ = fs::remove_file(&path);
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(error
+            .to_string()
+            .ends_with(":4: malformed telemetry record"));
+    }
+
+    #[test]
+    fn other_csv_files_are_not_logs() {
+        let path = path("other.csv");
+        fs::write(&path, "task,worker,start,finish\n1,0,0,3\n").unwrap();
+        assert!(read_log(&path).unwrap().is_none());
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn resume_drops_the_records_after_the_checkpoint() {
+        let path = path("resume.csv");
+        let mut log = write(&path, 2);
+        let len = log.position().unwrap();
+        log.log(&record(2)).unwrap();
+        drop(log);
+        let mut log = TelemetryLog::resume(&path, len).unwrap();
+        log.log(&record(5)).unwrap();
+        log.flush().unwrap();
+        let epochs: Vec<i32> = read_log(&path)
+            .unwrap()
+            .unwrap()
+            .iter()
+            .map(|r| r.epoch)
+            .collect();
+        let _ = fs::remove_file(&path);
+        assert_eq!(epochs, vec![0, 1, 5]);
+    }
+}
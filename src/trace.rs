@@ -0,0 +1,115 @@
+use std::io;
+
+use crate::export::{self, json_array, JsonObject};
+use crate::schedule::Schedule;
+use crate::utils::Utils;
+
+// Trace Event Format (chrome://tracing, Perfetto) with one cycle as one microsecond.
+// Every worker is a thread of a single process, every task a complete event and every
+// precedence edge a flow arrow from the predecessor to the successor.
+pub(crate) fn trace_json(schedule: &Schedule, utils: &Utils, name: &str) -> String {
+    let predecessors = utils.predecessors();
+    let mut events = Vec::new();
+
+    events.push(
+        JsonObject::new()
+            .string("name", "process_name")
+            .string("ph", "M")
+            .field("pid", 0)
+            .raw("args", JsonObject::new().string("name", name).to_string()),
+    );
+    for worker in 0..schedule.workers.len() {
+        events.push(
+            JsonObject::new()
+                .string("name", "thread_name")
+                .string("ph", "M")
+                .field("pid", 0)
+                .field("tid", worker)
+                .raw(
+                    "args",
+                    JsonObject::new()
+                        .string("name", &format!("Worker {}", worker))
+                        .to_string(),
+                ),
+        );
+    }
+
+    for entry in schedule.tasks.iter().flatten() {
+        let task = entry.task as usize;
+        events.push(
+            JsonObject::new()
+                .string("name", &format!("task {}", entry.task))
+                .string("cat", "task")
+                .string("ph", "X")
+                .field("ts", entry.start)
+                .field("dur", entry.finish - entry.start)
+                .field("pid", 0)
+                .field("tid", entry.worker)
+                .raw(
+                    "args",
+                    JsonObject::new()
+                        .field("id", entry.task)
+                        .field("cost", utils.costs_vec[task])
+                        .raw("predecessors", json_array(&predecessors[task]))
+                        .to_string(),
+                ),
+        );
+    }
+
+    // the flow starts inside the predecessor slice and ends on the slice enclosing the successor start
+    let mut flow_id = 0;
+    for edge in utils.di_graph.edge_indices() {
+        let (source, target) = utils.di_graph.edge_endpoints(edge).unwrap();
+        let (Some(&Some(pred)), Some(&Some(succ))) = (
+            schedule.tasks.get(source.index()),
+            schedule.tasks.get(target.index()),
+        ) else {
+            continue;
+        };
+        let name = format!("{} -> {}", pred.task, succ.task);
+        events.push(
+            JsonObject::new()
+                .string("name", &name)
+                .string("cat", "precedence")
+                .string("ph", "s")
+                .field("id", flow_id)
+                .field("ts", pred.start)
+                .field("pid", 0)
+                .field("tid", pred.worker),
+        );
+        events.push(
+            JsonObject::new()
+                .string("name", &name)
+                .string("cat", "precedence")
+                .string("ph", "f")
+                .string("bp", "e")
+                .field("id", flow_id)
+                .field("ts", succ.start)
+                .field("pid", 0)
+                .field("tid", succ.worker),
+        );
+        flow_id += 1;
+    }
+
+    JsonObject::new()
+        .raw("traceEvents", json_array(events))
+        .raw(
+            "otherData",
+            JsonObject::new()
+                .string("instance", name)
+                .field("makespan", schedule.makespan)
+                .field("n_workers", schedule.n_workers)
+                .string("time_unit", "1 cycle = 1 us")
+                .to_string(),
+        )
+        .to_string()
+}
+
+pub(crate) fn write_trace(
+    schedule: &Schedule,
+    utils: &Utils,
+    name: &str,
+    path: &str,
+) -> io::Result<()> {
+    export::write_file(path, &trace_json(schedule, utils, name))
+}
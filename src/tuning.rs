@@ -0,0 +1,512 @@
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::sync::Mutex;
+
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+
+use crate::batch;
+use crate::config::SolverConfig;
+use crate::export::{self, create_parent};
+use crate::logging::{error, info};
+use crate::snapshot::SnapshotPolicy;
+use crate::solver;
+use crate::stats;
+use crate::stopping::CancelToken;
+
+// blocks every candidate runs before the first elimination test
+const FIRST_TEST: usize = 5;
+// Friedman significance level of the elimination test
+const ALPHA: f64 = 0.05;
+// configurations kept from one iteration to the next
+const N_ELITES: usize = 3;
+
+// One tunable parameter, any key of the config file
+#[derive(Clone, Debug)]
+pub(crate) struct Parameter {
+    pub name: String,
+    pub min: f64,
+    pub max: f64,
+    pub integer: bool,
+    // sampled uniformly in log space, for rates spanning orders of magnitude
+    pub log: bool,
+}
+
+impl Parameter {
+    // the parameter space as [0, 1], linear or logarithmic
+    fn unit_of(&self, value: f64) -> f64 {
+        if self.log {
+            (value.ln() - self.min.ln()) / (self.max.ln() - self.min.ln())
+        } else {
+            (value - self.min) / (self.max - self.min)
+        }
+    }
+
+    fn value_at(&self, unit: f64) -> f64 {
+        let unit = unit.clamp(0.0, 1.0);
+        let value = if self.log {
+            (self.min.ln() + unit * (self.max.ln() - self.min.ln())).exp()
+        } else {
+            self.min + unit * (self.max - self.min)
+        };
+        if self.integer {
+            value.round()
+        } else {
+            value
+        }
+    }
+
+    fn sample(&self, rng: &mut ChaCha8Rng) -> f64 {
+        self.value_at(rng.random_range(0.0..=1.0))
+    }
+
+    // a normal step around value, spread is the standard deviation as a fraction of the range
+    fn perturb(&self, value: f64, spread: f64, rng: &mut ChaCha8Rng) -> f64 {
+        // Box-Muller
+        let u1: f64 = rng.random_range(f64::EPSILON..1.0);
+        let u2: f64 = rng.random_range(0.0..1.0);
+        let normal = (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos();
+        self.value_at(self.unit_of(value) + normal * spread)
+    }
+
+    fn format(&self, value: f64) -> String {
+        if self.integer {
+            format!("{}", value as i64)
+        } else {
+            format!("{:.6}", value)
+        }
+    }
+}
+
+// the colony parameters that used to be drawn at random for every thread
+pub(crate) fn default_space() -> Vec<Parameter> {
+    let real = |name: &str, min: f64, max: f64, log: bool| Parameter {
+        name: name.to_string(),
+        min,
+        max,
+        integer: false,
+        log,
+    };
+    vec![
+        real("deposit_base", 0.001, 0.1, true),
+        real("evaporation_ratio", 0.1, 0.75, false),
+        real("alfa", 0.0, 2.0, false),
+        real("beta", 0.1, 3.0, false),
+        real("base_chance", 0.01, 2.0, true),
+    ]
+}
+
+// One parameter per line, `<config key> <int|real> <min> <max> [log]`, '#' starts a comment.
+// e.g. `colonies int 2 12` or `deposit_base real 0.001 0.1 log`
+pub(crate) fn read_space(path: &str) -> io::Result<Vec<Parameter>> {
+    let mut space = Vec::new();
+    for (line_number, line) in std::fs::read_to_string(path)?.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+        let invalid = |message: &str| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}:{}: {}", path, line_number + 1, message),
+            )
+        };
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let (name, kind, min, max) = match fields[..] {
+            [name, kind, min, max] | [name, kind, min, max, "log"] => (name, kind, min, max),
+            _ => return Err(invalid("expected <name> <int|real> <min> <max> [log]")),
+        };
+        let integer = match kind {
+            "int" => true,
+            "real" => false,
+            _ => return Err(invalid("the type is int or real")),
+        };
+        let min: f64 = min.parse().map_err(|_| invalid("invalid min"))?;
+        let max: f64 = max.parse().map_err(|_| invalid("invalid max"))?;
+        let log = fields.len() == 5;
+        if min >= max || (log && min <= 0.0) {
+            return Err(invalid("the range must be min < max, and positive for log"));
+        }
+        // the value must be something the config file accepts
+        let probe = if integer {
+            "1".to_string()
+        } else {
+            min.to_string()
+        };
+        SolverConfig::default()
+            .set(name, &probe)
+            .map_err(|e| invalid(&e))?;
+        space.push(Parameter {
+            name: name.to_string(),
+            min,
+            max,
+            integer,
+            log,
+        });
+    }
+    Ok(space)
+}
+
+pub(crate) struct TuningOptions {
+    pub instances_dir: String,
+    // file name pattern, '*' and '?' wildcards
+    pub pattern: String,
+    pub out_dir: String,
+    // everything that is not tuned
+    pub base: SolverConfig,
+    pub space: Vec<Parameter>,
+    // total number of solver runs
+    pub budget: usize,
+    pub seed: u64,
+}
+
+// A configuration raced by the tuner
+#[derive(Clone, Debug)]
+pub(crate) struct Candidate {
+    pub id: usize,
+    pub values: Vec<f64>,
+    pub config: SolverConfig,
+    // makespan on every block it ran on, by block
+    pub results: HashMap<usize, f64>,
+}
+
+impl Candidate {
+    fn mean(&self) -> f64 {
+        stats::mean(&self.results.values().copied().collect::<Vec<_>>())
+    }
+}
+
+pub(crate) struct Tuning {
+    // best first
+    pub elites: Vec<Candidate>,
+    pub evaluations: usize,
+    pub iterations: usize,
+    pub files: Vec<String>,
+}
+
+// Iterated F-race (as in irace): every iteration samples candidates around the elites of the
+// previous one and races them over the training instances, dropping the ones a Friedman test
+// finds worse, until the budget of solver runs is spent. A cancelled tuning writes the elites
+// of the blocks it completed.
+pub(crate) fn tune(options: &TuningOptions, cancel: &CancelToken) -> io::Result<Tuning> {
+    let instances = batch::list_instances(&options.instances_dir, &options.pattern)?;
+    if instances.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("no instance matches {}", options.pattern),
+        ));
+    }
+    let log_path = format!("{}/evaluations.csv", options.out_dir);
+    create_parent(&log_path)?;
+    let mut log = BufWriter::new(File::create(&log_path)?);
+    writeln!(log, "iteration,candidate,block,instance,seed,makespan")?;
+    let mut tuner = Tuner {
+        options,
+        instances,
+        blocks: Vec::new(),
+        rng: ChaCha8Rng::seed_from_u64(options.seed),
+        next_id: 0,
+        evaluations: 0,
+        log: Mutex::new(log),
+        cancel,
+    };
+
+    let d = options.space.len().max(1) as f64;
+    let n_iterations = 2 + d.log2().floor() as usize;
+    let mut elites: Vec<Candidate> = Vec::new();
+    let mut spread = 0.5;
+    let mut iteration = 0;
+    while tuner.evaluations < options.budget && iteration < n_iterations && !cancel.is_cancelled() {
+        iteration += 1;
+        let budget = (options.budget - tuner.evaluations) / (n_iterations - iteration + 1);
+        let n_candidates = (budget / (FIRST_TEST + iteration.min(5))).max(N_ELITES + 2);
+        let n_new = n_candidates.saturating_sub(elites.len());
+        info!(
+            "=== Iteration {}/{}: {} elites + {} new candidates, {} runs ===",
+            iteration,
+            n_iterations,
+            elites.len(),
+            n_new,
+            budget
+        );
+        let mut candidates = elites.clone();
+        for _ in 0..n_new {
+            let values = if elites.is_empty() {
+                options
+                    .space
+                    .iter()
+                    .map(|p| p.sample(&mut tuner.rng))
+                    .collect()
+            } else {
+                tuner.sample_around(&elites, spread)
+            };
+            candidates.push(tuner.candidate(values));
+        }
+        let survivors = tuner.race(candidates, iteration, tuner.evaluations + budget);
+        if survivors.iter().all(|c| c.results.is_empty()) {
+            // cancelled before a single block, the previous elites stand
+            break;
+        }
+        elites = survivors.into_iter().take(N_ELITES).collect();
+        for (rank, elite) in elites.iter().enumerate() {
+            info!(
+                "Elite {}: candidate {} mean makespan {:.2} over {} runs ({})",
+                rank + 1,
+                elite.id,
+                elite.mean(),
+                elite.results.len(),
+                tuner.describe(&elite.values)
+            );
+        }
+        // the samples close in on the elites as the iterations go (irace's rule)
+        spread *= (1.0 / n_new.max(1) as f64).powf(1.0 / d);
+    }
+    tuner.log.lock().unwrap().flush()?;
+
+    let mut files = vec![log_path];
+    let mut csv = String::from("rank,candidate,runs,mean_makespan");
+    for parameter in &options.space {
+        let _ = write!(csv, ",{}", parameter.name);
+    }
+    csv.push('\n');
+    for (rank, elite) in elites.iter().enumerate() {
+        let _ = write!(
+            csv,
+            "{},{},{},{:.3}",
+            rank + 1,
+            elite.id,
+            elite.results.len(),
+            elite.mean()
+        );
+        for (parameter, &value) in options.space.iter().zip(&elite.values) {
+            let _ = write!(csv, ",{}", parameter.format(value));
+        }
+        csv.push('\n');
+        // the config as the solver reads it, with the seed left to chance again
+        let path = format!("{}/elite_{}.txt", options.out_dir, rank + 1);
+        SolverConfig {
+            seed: options.base.seed,
+            ..elite.config.clone()
+        }
+        .save(&path)?;
+        files.push(path);
+    }
+    let path = format!("{}/elites.csv", options.out_dir);
+    export::write_file(&path, &csv)?;
+    files.push(path);
+
+    Ok(Tuning {
+        elites,
+        evaluations: tuner.evaluations,
+        iterations: iteration,
+        files,
+    })
+}
+
+struct Tuner<'a> {
+    options: &'a TuningOptions,
+    instances: Vec<String>,
+    // (instance, seed) every candidate of a race runs on in this order, each pass over the
+    // instances is shuffled and uses the next seed
+    blocks: Vec<(String, u64)>,
+    rng: ChaCha8Rng,
+    next_id: usize,
+    evaluations: usize,
+    log: Mutex<BufWriter<File>>,
+    cancel: &'a CancelToken,
+}
+
+impl Tuner<'_> {
+    fn candidate(&mut self, values: Vec<f64>) -> Candidate {
+        let mut config = SolverConfig {
+            // tuning only needs the makespan
+            snapshots: SnapshotPolicy::Off,
+            checkpoint_every: None,
+            trails: None,
+            export_models: false,
+            solver_solution: None,
+            ..self.options.base.clone()
+        };
+        for (parameter, &value) in self.options.space.iter().zip(&values) {
+            config
+                .set(&parameter.name, &parameter.format(value))
+                .expect("parameter checked when the space was read");
+        }
+        self.next_id += 1;
+        Candidate {
+            id: self.next_id,
+            values,
+            config,
+            results: HashMap::new(),
+        }
+    }
+
+    // a perturbed copy of an elite, better elites are picked more often
+    fn sample_around(&mut self, elites: &[Candidate], spread: f64) -> Vec<f64> {
+        let weights: Vec<usize> = (0..elites.len()).map(|rank| elites.len() - rank).collect();
+        let mut pick = self.rng.random_range(0..weights.iter().sum::<usize>());
+        let mut parent = 0;
+        while pick >= weights[parent] {
+            pick -= weights[parent];
+            parent += 1;
+        }
+        let options = self.options;
+        elites[parent]
+            .values
+            .iter()
+            .zip(&options.space)
+            .map(|(&value, parameter)| parameter.perturb(value, spread, &mut self.rng))
+            .collect()
+    }
+
+    fn describe(&self, values: &[f64]) -> String {
+        self.options
+            .space
+            .iter()
+            .zip(values)
+            .map(|(p, &v)| format!("{} = {}", p.name, p.format(v)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn block(&mut self, index: usize) -> (String, u64) {
+        while self.blocks.len() <= index {
+            let seed = (self.blocks.len() / self.instances.len()) as u64 + 1;
+            let mut pass = self.instances.clone();
+            pass.shuffle(&mut self.rng);
+            self.blocks
+                .extend(pass.into_iter().map(|instance| (instance, seed)));
+        }
+        self.blocks[index].clone()
+    }
+
+    // Runs the candidates block by block and drops the ones found worse, returns the
+    // survivors best first. Stops once only the elites are left or the budget is reached.
+    fn race(
+        &mut self,
+        mut alive: Vec<Candidate>,
+        iteration: usize,
+        budget: usize,
+    ) -> Vec<Candidate> {
+        let mut n_blocks = 0;
+        loop {
+            let block = n_blocks;
+            let pending = alive
+                .iter()
+                .filter(|c| !c.results.contains_key(&block))
+                .count();
+            if self.evaluations + pending > budget {
+                break;
+            }
+            let (instance, seed) = self.block(block);
+            self.evaluations += pending;
+            let results: Option<Vec<f64>> = alive
+                .par_iter()
+                .map(|candidate| match candidate.results.get(&block) {
+                    Some(&makespan) => Some(makespan),
+                    None => self.evaluate(candidate, iteration, block, &instance, seed),
+                })
+                .collect();
+            // a block cut short by a cancellation is left out
+            let Some(results) = results else {
+                break;
+            };
+            for (candidate, makespan) in alive.iter_mut().zip(results) {
+                candidate.results.insert(block, makespan);
+            }
+            n_blocks += 1;
+
+            if n_blocks >= FIRST_TEST && alive.len() > 1 {
+                let blocks: Vec<Vec<f64>> = (0..n_blocks)
+                    .map(|b| alive.iter().map(|c| c.results[&b]).collect())
+                    .collect();
+                if stats::friedman(&blocks).p_value < ALPHA {
+                    let survivors = stats::friedman_survivors(&blocks);
+                    if survivors.len() < alive.len() {
+                        info!(
+                            "Block {}: {} of {} candidates survive",
+                            n_blocks,
+                            survivors.len(),
+                            alive.len()
+                        );
+                    }
+                    alive = survivors.into_iter().map(|i| alive[i].clone()).collect();
+                }
+            }
+            if alive.len() <= N_ELITES && n_blocks >= FIRST_TEST {
+                break;
+            }
+        }
+
+        if n_blocks == 0 {
+            return alive;
+        }
+        // best mean rank over the blocks all the survivors ran on
+        let blocks: Vec<Vec<f64>> = (0..n_blocks)
+            .map(|b| alive.iter().map(|c| c.results[&b]).collect())
+            .collect();
+        let ranks = stats::friedman(&blocks).mean_ranks;
+        let mut order: Vec<usize> = (0..alive.len()).collect();
+        order.sort_by(|&a, &b| ranks[a].total_cmp(&ranks[b]));
+        order.into_iter().map(|i| alive[i].clone()).collect()
+    }
+
+    fn evaluate(
+        &self,
+        candidate: &Candidate,
+        iteration: usize,
+        block: usize,
+        instance: &str,
+        seed: u64,
+    ) -> Option<f64> {
+        if self.cancel.is_cancelled() {
+            return None;
+        }
+        let stem = Path::new(instance)
+            .file_stem()
+            .map_or(instance.to_string(), |s| s.to_string_lossy().to_string());
+        let results_path = format!(
+            "{}/runs/candidate_{}/{}_seed_{}/",
+            self.options.out_dir, candidate.id, stem, seed
+        );
+        let config = SolverConfig {
+            seed: Some(seed),
+            ..candidate.config.clone()
+        };
+        let instances_dir = format!("{}/", self.options.instances_dir.trim_end_matches('/'));
+        // a candidate that crashes the solver loses every block it crashes on
+        let outcome = panic::catch_unwind(AssertUnwindSafe(|| {
+            solver::solve_file(
+                &config,
+                &instances_dir,
+                instance,
+                &results_path,
+                self.cancel,
+            )
+        }));
+        let makespan = match outcome {
+            Ok(Ok(outcome)) if outcome.cancelled => return None,
+            Ok(Ok(outcome)) => outcome.makespan as f64,
+            Ok(Err(e)) => {
+                error!("Candidate {} failed on {}: {}", candidate.id, instance, e);
+                f64::INFINITY
+            }
+            Err(_) => f64::INFINITY,
+        };
+        let mut log = self.log.lock().unwrap();
+        writeln!(
+            log,
+            "{},{},{},{},{},{}",
+            iteration, candidate.id, block, instance, seed, makespan
+        )
+        .and_then(|_| log.flush())
+        .expect("Failed to write the tuning log");
+        Some(makespan)
+    }
+}
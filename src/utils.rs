@@ -1,13 +1,15 @@
-use std::fs::OpenOptions;
-use std::io::{BufRead, BufReader, Error, Write};
-use std::path::Path;
-use std::{fs, io};
+use std::fs;
+use std::io::{self, BufRead, BufReader};
 
+use petgraph::algo::toposort;
 use petgraph::stable_graph::{EdgeIndex, NodeIndex, StableDiGraph};
 use petgraph::Direction;
-use rand::Rng;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 
-use crate::worker_ant::WorkerAnt; // Import the Rng trait to use gen_range
+use crate::bounds;
+use crate::logging::debug;
+use crate::schedule::Schedule;
 
 // This class is the  one reponsable to Store the information related to the entry task graph only
 // the idea is that the cromosssomes ( phreromones) will be stored in the ant class in a separate graph
@@ -48,73 +50,64 @@ impl Utils {
             thread_pherohormones: Vec::new(),
         }
     }
-    pub fn show_content(file_path: &str) {
-        //println!("In file {}", file_path);
-
-        let contents =
-            fs::read_to_string(file_path).expect("Should have been able to read the file");
-
-        println!("With text:\n{}", contents);
-    }
-
-    pub fn initialize_graph(&mut self, file_path: &str, task_graph: &str) {
+    pub fn initialize_graph(&mut self, file_path: &str, task_graph: &str) -> io::Result<()> {
         let path = format!("{}{}", file_path, task_graph);
-        if let Ok(file) = fs::File::open(path) {
-            let reader = BufReader::new(file);
-            let mut count: i32 = 0;
-            let mut task: i32 = 0;
-            let mut line_count: i32 = 0;
-            let mut n_tasks: i32 = 0;
-            for line in reader.lines() {
-                let line = line.expect("Failed to read line from file");
-                //println!("{}", line);
-                if line.starts_with("#") {
-                    break;
-                }
-                let parsed_vec: Vec<i32> = line
-                    .split_whitespace()
-                    .map(|s| s.trim().parse::<i32>().expect("Invalid integer"))
-                    .collect();
-
-                for i in &parsed_vec {
-                    if count == 0 {
-                        if line_count == 0 {
-                            n_tasks = *i + 2;
-                            self.remaining_vec = vec![0; n_tasks as usize];
-                            self.costs_vec = vec![0; n_tasks as usize];
-                            self.unlocks_vec = vec![0; n_tasks as usize];
-                            self.visibility = vec![0.0; n_tasks as usize];
-                            for j in 0..n_tasks {
-                                self.di_graph.add_node(j);
-                            }
-                        } else {
-                            //println!("Task: {}", i);
-                            task = *i;
-                            count += 1;
+        let file = fs::File::open(&path)
+            .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path, e)))?;
+        let reader = BufReader::new(file);
+        let mut count: i32 = 0;
+        let mut task: i32 = 0;
+        for (line_count, line) in reader.lines().enumerate() {
+            let line = line?;
+            //println!("{}", line);
+            if line.starts_with("#") {
+                break;
+            }
+            let parsed_vec = parse_line(&path, line_count, &line)?;
+
+            for i in &parsed_vec {
+                if count == 0 {
+                    if line_count == 0 {
+                        let n_tasks = *i + 2;
+                        if *i < 0 {
+                            return Err(invalid_line(&path, line_count, "negative task count"));
+                        }
+                        self.remaining_vec = vec![0; n_tasks as usize];
+                        self.costs_vec = vec![0; n_tasks as usize];
+                        self.unlocks_vec = vec![0; n_tasks as usize];
+                        self.visibility = vec![0.0; n_tasks as usize];
+                        for j in 0..n_tasks {
+                            self.di_graph.add_node(j);
                         }
-                    } else if count == 1 {
-                        //println!("Cost: {}", i);
-                        self.costs_vec[task as usize] = *i;
-                        count += 1;
-                    } else if count == 2 {
-                        //println!("Degree: {}", i);
-                        count += 1;
                     } else {
-                        //println!(" {}", i);use std::io::Write
-                        self.remaining_vec[task as usize] += 1;
-                        self.di_graph.add_edge(
-                            NodeIndex::new(*i as usize),
-                            NodeIndex::new(task as usize),
-                            0,
-                        );
+                        //println!("Task: {}", i);
+                        task = self.task_id(&path, line_count, *i)?;
+                        count += 1;
                     }
+                } else if count == 1 {
+                    //println!("Cost: {}", i);
+                    if *i < 0 {
+                        return Err(invalid_line(&path, line_count, "negative cost"));
+                    }
+                    self.costs_vec[task as usize] = *i;
+                    count += 1;
+                } else if count == 2 {
+                    //println!("Degree: {}", i);
+                    count += 1;
+                } else {
+                    //println!(" {}", i);use std::io::Write
+                    let predecessor = self.task_id(&path, line_count, *i)?;
+                    self.remaining_vec[task as usize] += 1;
+                    self.di_graph.add_edge(
+                        NodeIndex::new(predecessor as usize),
+                        NodeIndex::new(task as usize),
+                        0,
+                    );
                 }
-                count = 0;
-                line_count += 1;
             }
-        } else {
-            eprintln!("Error opening the file");
+            count = 0;
         }
+        self.check_acyclic(&path, 0)
     }
 
     // Function to initialize the graph from a file for the prototype types of file
@@ -124,79 +117,106 @@ impl Utils {
         file_path: &str,
         task_graph: &str,
         n_ants: &mut i32,
-    ) {
+    ) -> io::Result<()> {
         let path = format!("{}{}", file_path, task_graph);
-        if let Ok(file) = fs::File::open(path) {
-            let reader = BufReader::new(file);
-            let mut count: i32 = 0;
-            let mut task: i32 = 0;
-            let mut line_count: i32 = 0;
-            let mut n_tasks: i32 = 0;
-            for line in reader.lines() {
-                let line = line.expect("Failed to read line from file");
-                //println!("{}", line);
-                if line.starts_with("#") {
-                    break;
-                }
-                let parsed_vec: Vec<i32> = line
-                    .split_whitespace()
-                    .map(|s| s.trim().parse::<i32>().expect("Invalid integer"))
-                    .collect();
-
-                for i in &parsed_vec {
-                    if count == 0 {
-                        if line_count == 0 {
-                            n_tasks = *i + 1;
-                            self.remaining_vec = vec![0; n_tasks as usize];
-                            self.costs_vec = vec![0; n_tasks as usize];
-                            self.unlocks_vec = vec![0; n_tasks as usize];
-                            self.visibility = vec![0.0; n_tasks as usize];
-
-                            for j in 0..n_tasks {
-                                self.di_graph.add_node(j);
-                            }
-                            count += 1;
-                        } else {
-                            //println!("Task: {}", i);
-                            task = *i - 1;
-                            count += 1;
+        let file = fs::File::open(&path)
+            .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path, e)))?;
+        let reader = BufReader::new(file);
+        let mut count: i32 = 0;
+        let mut task: i32 = 0;
+        for (line_count, line) in reader.lines().enumerate() {
+            let line = line?;
+            //println!("{}", line);
+            if line.starts_with("#") {
+                break;
+            }
+            let parsed_vec = parse_line(&path, line_count, &line)?;
+
+            for i in &parsed_vec {
+                if count == 0 {
+                    if line_count == 0 {
+                        let n_tasks = *i + 1;
+                        if *i < 0 {
+                            return Err(invalid_line(&path, line_count, "negative task count"));
                         }
-                    } else if count == 1 {
-                        if line_count == 0 {
-                            *n_ants = *i;
-                        } else {
-                            //println!("Cost: {}", i);
-                            self.costs_vec[task as usize] = *i;
-                            count += 1;
+                        self.remaining_vec = vec![0; n_tasks as usize];
+                        self.costs_vec = vec![0; n_tasks as usize];
+                        self.unlocks_vec = vec![0; n_tasks as usize];
+                        self.visibility = vec![0.0; n_tasks as usize];
+
+                        for j in 0..n_tasks {
+                            self.di_graph.add_node(j);
                         }
-                    } else if count == 2 {
-                        //println!("Degree: {}", i);
                         count += 1;
                     } else {
-                        //println!(" {}", i);use std::io::Write
-                        self.remaining_vec[task as usize] += 1;
-                        self.di_graph.add_edge(
-                            NodeIndex::new((*i - 1) as usize),
-                            NodeIndex::new(task as usize),
-                            0,
-                        );
+                        //println!("Task: {}", i);
+                        task = self.task_id(&path, line_count, *i - 1)?;
+                        count += 1;
                     }
+                } else if count == 1 {
+                    if line_count == 0 {
+                        *n_ants = *i;
+                    } else {
+                        //println!("Cost: {}", i);
+                        if *i < 0 {
+                            return Err(invalid_line(&path, line_count, "negative cost"));
+                        }
+                        self.costs_vec[task as usize] = *i;
+                        count += 1;
+                    }
+                } else if count == 2 {
+                    //println!("Degree: {}", i);
+                    count += 1;
+                } else {
+                    //println!(" {}", i);use std::io::Write
+                    let predecessor = self.task_id(&path, line_count, *i - 1)?;
+                    self.remaining_vec[task as usize] += 1;
+                    self.di_graph.add_edge(
+                        NodeIndex::new(predecessor as usize),
+                        NodeIndex::new(task as usize),
+                        0,
+                    );
                 }
-                count = 0;
-                line_count += 1;
             }
-        } else {
-            eprintln!("Error opening the file");
+            count = 0;
+        }
+        self.check_acyclic(&path, 1)
+    }
+
+    // a task id of the file, which must be one of the tasks its header announced
+    fn task_id(&self, path: &str, line_count: usize, task: i32) -> io::Result<i32> {
+        if task < 0 || task as usize >= self.costs_vec.len() {
+            return Err(invalid_line(
+                path,
+                line_count,
+                &format!("task {} outside the {} tasks", task, self.costs_vec.len()),
+            ));
         }
+        Ok(task)
+    }
+
+    // the ants and the bounds need a DAG, the tasks of a cycle would never become available.
+    // first_id is the id the file gives to the first task
+    fn check_acyclic(&self, path: &str, first_id: usize) -> io::Result<()> {
+        toposort(&self.di_graph, None).map(|_| ()).map_err(|cycle| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "{}: task {} is on a dependency cycle",
+                    path,
+                    cycle.node_id().index() + first_id
+                ),
+            )
+        })
     }
 
     pub fn update_visibility(&mut self) {
         let mut max: f64 = 0.0;
-        println!("update_visibility ");
+        debug!("Updating the visibility of {} tasks", self.n_tasks);
 
         for i in 0..self.n_tasks as usize {
-            let cost_ratio = (1.0 - (self.costs_vec[i] as f64 / self.max_cost as f64)) as f64;
-            let unlocks_ratio = (self.unlocks_vec[i] as f64 / self.max_unlocks as f64) as f64;
+            let cost_ratio = 1.0 - (self.costs_vec[i] as f64 / self.max_cost as f64);
+            let unlocks_ratio = self.unlocks_vec[i] as f64 / self.max_unlocks as f64;
 
             self.visibility[i] = cost_ratio + unlocks_ratio;
             //findmax
@@ -206,7 +226,7 @@ impl Utils {
         }
         //normalization
         for i in 0..self.n_tasks as usize {
-            self.visibility[i] = self.visibility[i] / max;
+            self.visibility[i] /= max;
         }
 
         self.visibility_sum = self.visibility.iter().sum();
@@ -240,7 +260,7 @@ impl Utils {
                 .neighbors_directed(source, Direction::Outgoing)
                 .count();
 
-            self.unlocks_vec[source.index() as usize] = outgoing_edges as i32;
+            self.unlocks_vec[source.index()] = outgoing_edges as i32;
 
             if let Some(&weight) = self.costs_vec.get(target_index) {
                 self.di_graph.update_edge(source, target, weight);
@@ -250,7 +270,7 @@ impl Utils {
 
     pub fn init_arrays(&mut self) {
         self.n_tasks = self.di_graph.node_count() as i32;
-        print!("n_tasks: {}", self.n_tasks);
+        debug!("n_tasks: {}", self.n_tasks);
 
         self.update_weights_unlocks();
         self.find_max_cost_unlocks();
@@ -258,131 +278,120 @@ impl Utils {
         self.update_visibility();
     }
     // initiates the evaporation and deposit rates
-    pub fn init_parameters_vec(&mut self, n_threads: i32, deposit_base: f64) {
-        let mut rng = rand::rng();
+    // evaporation_ratio fixes the evaporation of every colony to that fraction of the deposit rate
+    pub fn init_parameters_vec(
+        &mut self,
+        n_threads: i32,
+        deposit_base: f64,
+        evaporation_ratio: Option<f64>,
+        seed: u64,
+    ) {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
 
         for i in 0..n_threads {
             let deposit_rate = deposit_base;
 
             // Gera taxa de evaporação aleatória entre 10% e 90% da taxa de depósito
-            let evaporation_rate = rng.random_range(deposit_base * 0.1..deposit_base * 0.75);
-
-            println!(
-                "deposit_rate: {} , evaporation_rate: {} , thread_id: {}",
-                deposit_rate, evaporation_rate, i,
+            let evaporation_rate = match evaporation_ratio {
+                Some(ratio) => deposit_base * ratio,
+                None => rng.random_range(deposit_base * 0.1..deposit_base * 0.75),
+            };
+
+            debug!(
+                colony: i,
+                "deposit_rate: {} , evaporation_rate: {}",
+                deposit_rate,
+                evaporation_rate
             );
             self.thread_pherohormones
                 .push((deposit_rate, evaporation_rate));
         }
     }
-    pub fn delete_file(dir_path: &str, file_name: &str) {
-        // Construct the full file path
-        let file_path = std::path::Path::new(dir_path).join(file_name);
-
-        // Check if the file exists
-        if file_path.exists() {
-            // Attempt to delete the file
-            if let Err(e) = std::fs::remove_file(&file_path) {
-                eprintln!("Failed to delete file {}: {}", file_path.display(), e);
-            } else {
-                println!("File {} deleted successfully.", file_path.display());
-            }
-        } else {
-            println!("File {} does not exist.", file_path.display());
-        }
-    }
-    pub fn append_to_csv(
-        epoch: i32,
-        max_weight: f64,
-        cycles_spent: i32,
-        dir_path: &str,
-        file_name: &str,
-    ) -> io::Result<()> {
-        // Construct the full file path
-        let file_path = Path::new(dir_path).join(file_name);
-
-        // Create the directory if it doesn't exist
-        if let Some(parent) = file_path.parent() {
-            fs::create_dir_all(parent)?;
-        }
-        // Open the file in append mode, create it if it doesn't exist
-        let mut file = OpenOptions::new()
-            .write(true)
-            .append(true)
-            .create(true)
-            .open(&file_path)?;
-
-        // Construct the CSV record
-        let record = format!("{},{},{}\n", epoch, max_weight, cycles_spent);
-
-        // Write the record to the file
-        file.write_all(record.as_bytes())?;
-
-        Ok(())
-    }
-    //********//
-    // PRINTS //
-    //********//
-    pub fn print_graph(&self) {
-        print!("n_tasks: {}", self.n_tasks);
-        println!("Nodes in the graph:");
-        for node in self.di_graph.node_indices() {
-            println!("Node {}: {:?}", node.index(), self.di_graph[node]);
-        }
-
-        println!("Edges in the graph:");
+    // sorted predecessors of every task
+    pub fn predecessors(&self) -> Vec<Vec<i32>> {
+        let mut predecessors = vec![Vec::new(); self.n_tasks as usize];
         for edge in self.di_graph.edge_indices() {
             let (source, target) = self.di_graph.edge_endpoints(edge).unwrap();
-            let weight = self.di_graph[edge];
-            println!(
-                "Edge from {} to {} with weight {}",
-                source.index(),
-                target.index(),
-                weight
-            );
+            predecessors[target.index()].push(source.index() as i32);
+        }
+        for preds in &mut predecessors {
+            preds.sort_unstable();
         }
+        predecessors
     }
-    pub fn print_vecs(&self) {
-        println!("Task: \t Remainig: \t Cost : \t Unlocks \t Visibility :");
-        for i in 0..self.n_tasks as usize {
-            println!(
-                "{}        \t {}       \t{}       \t{}       \t{}",
-                i,
-                self.remaining_vec[i],
-                self.costs_vec[i],
-                self.unlocks_vec[i],
-                self.visibility[i]
-            );
+    // Graphviz view of the input graph: every task with its cost and level (longest chain of
+    // predecessors), tasks and edges on a critical path drawn in red
+    pub fn to_dot(&self) -> String {
+        let (heads, tails) = bounds::heads_and_tails(self);
+        let critical_path = heads
+            .iter()
+            .zip(&tails)
+            .map(|(h, t)| h + t)
+            .max()
+            .unwrap_or(0);
+        let critical: Vec<bool> = heads
+            .iter()
+            .zip(&tails)
+            .map(|(h, t)| h + t == critical_path)
+            .collect();
+
+        let mut levels = vec![0; self.n_tasks as usize];
+        for node in toposort(&self.di_graph, None).expect("task graph must be acyclic") {
+            for succ in self.di_graph.neighbors_directed(node, Direction::Outgoing) {
+                levels[succ.index()] = levels[succ.index()].max(levels[node.index()] + 1);
+            }
         }
 
-        println!(
-            "max_cost :{} , max_unlocks :{}\n",
-            self.max_cost, self.max_unlocks
+        let mut dot = String::new();
+        dot.push_str("digraph tasks {\n");
+        dot.push_str(&format!(
+            "  graph [label=\"{} tasks, critical path {}\", labelloc=t, rankdir=TB];\n",
+            self.n_tasks, critical_path
+        ));
+        dot.push_str(
+            "  node [shape=box, style=\"rounded,filled\", fillcolor=\"#dce6f2\", fontsize=10];\n",
         );
-    }
-    pub fn print_remaining_vec(&self, n_tasks: usize) {
-        println!(" Remainig: :");
-        for i in 0..n_tasks {
-            println!(" task {} : {}", i, self.remaining_vec[i]);
+        // one rank per level so the drawing follows the precedence depth
+        let max_level = levels.iter().copied().max().unwrap_or(0);
+        for level in 0..=max_level {
+            let tasks: Vec<String> = (0..self.n_tasks as usize)
+                .filter(|&task| levels[task] == level)
+                .map(|task| task.to_string())
+                .collect();
+            dot.push_str(&format!("  {{ rank=same; {}; }}\n", tasks.join("; ")));
         }
-    }
-    pub fn print_visibility(&self, n_tasks: usize, visibility: &Vec<f64>) {
-        for i in 0..n_tasks {
-            println!("task {} visibility {}", i, visibility[i]);
+        for task in 0..self.n_tasks as usize {
+            let color = if critical[task] {
+                ", fillcolor=\"#f4a6a6\", color=\"#c00000\""
+            } else {
+                ""
+            };
+            dot.push_str(&format!(
+                "  {} [label=\"T{}\\ncost {}\\nlevel {}\"{}];\n",
+                task, task, self.costs_vec[task], levels[task], color
+            ));
+        }
+        for edge in self.di_graph.edge_indices() {
+            let (source, target) = self.di_graph.edge_endpoints(edge).unwrap();
+            let (s, t) = (source.index(), target.index());
+            // a critical edge is tight: the successor can not start any earlier
+            let tight = critical[s] && critical[t] && heads[t] == heads[s] + self.costs_vec[s];
+            let style = if tight {
+                " [color=\"#c00000\", penwidth=2]"
+            } else {
+                ""
+            };
+            dot.push_str(&format!("  {} -> {}{};\n", s, t, style));
         }
+        dot.push_str("}\n");
+        dot
     }
-    pub fn print_gantt_chart(ants: &Vec<WorkerAnt>) {
+    //********//
+    // PRINTS //
+    //********//
+    pub fn print_gantt_chart(schedule: &Schedule) {
         // Find maximum cycle and calculate widths
-        let max_cycle = ants
-            .iter()
-            .flat_map(|ant| {
-                ant.task_history
-                    .iter()
-                    .filter(|&&(_, start, end)| end > start)
-                    .map(|&(_, _, end)| end)
-            })
-            .max()
-            .unwrap_or(0);
+        let max_cycle = schedule.makespan;
 
         let cycle_width = max_cycle.to_string().len().max(4);
         let column_width = 7; // Width for each ant column
@@ -390,7 +399,7 @@ impl Utils {
         // Print header
         println!("\nGantt Chart:");
         print!("{:width$}", "Cycle", width = cycle_width);
-        for i in 0..ants.len() {
+        for i in 0..schedule.workers.len() {
             print!(
                 "| {:^width$} ",
                 format!(" Ant {} ", i),
@@ -401,7 +410,7 @@ impl Utils {
 
         // // Print separator
         // print!("{:->width$}", "", width = cycle_width);
-        // for _ in 0..best_result.ants.len() {
+        // for _ in 0..schedule.workers.len() {
         //     print!("|{:->width$}", "", width = column_width);
         // }
         // println!("|");
@@ -409,16 +418,13 @@ impl Utils {
         // Print rows
         for cycle in 0..max_cycle {
             print!("{:width$} ", cycle, width = cycle_width);
-            for ant in ants {
-                let task = ant
-                    .task_history
+            for tasks in &schedule.workers {
+                let task = tasks
                     .iter()
-                    .find(|&&(_, start, end)| start <= cycle && cycle < end)
-                    .map(|&(id, _, _)| id)
-                    .unwrap_or(0);
+                    .find(|entry| entry.start <= cycle && cycle < entry.finish);
 
-                if task > 0 {
-                    print!("| {:^width$} ", task, width = column_width);
+                if let Some(entry) = task {
+                    print!("| {:^width$} ", entry.task, width = column_width);
                 } else {
                     print!("| {:^width$} ", "", width = column_width);
                 }
@@ -427,3 +433,99 @@ impl Utils {
         }
     }
 }
+
+// the integers of one line of a task graph file
+fn parse_line(path: &str, line_count: usize, line: &str) -> io::Result<Vec<i32>> {
+    line.split_whitespace()
+        .map(|s| {
+            s.parse::<i32>()
+                .map_err(|_| invalid_line(path, line_count, &format!("invalid integer '{}'", s)))
+        })
+        .collect()
+}
+
+fn invalid_line(path: &str, line_count: usize, message: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("{}:{}: {}", path, line_count + 1, message),
+    )
+}
+
+// a graph read from the STG text, through a file like the real ones
+#[cfg(test)]
+pub(crate) fn from_stg(stg: &str) -> Utils {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    let dir = std::env::temp_dir().to_string_lossy().to_string();
+    let name = format!(
+        "task_scheduling_graph_{}_{}.stg",
+        std::process::id(),
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    );
+    fs::write(format!("{}/{}", dir, name), stg).unwrap();
+    let mut utils = Utils::new();
+    utils.initialize_graph(&format!("{}/", dir), &name).unwrap();
+    utils.init_arrays();
+    let _ = fs::remove_file(format!("{}/{}", dir, name));
+    utils
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::instance::Instance;
+
+    // the error of opening a graph that cannot be read, written to a file of its own
+    fn open(name: &str, contents: &str, prototype: bool) -> String {
+        let dir = format!("{}/", std::env::temp_dir().to_string_lossy());
+        let name = format!("task_scheduling_utils_{}_{}", std::process::id(), name);
+        std::fs::write(format!("{}{}", dir, name), contents).unwrap();
+        let error = Instance::open(&dir, &name, prototype).err().unwrap();
+        let _ = std::fs::remove_file(format!("{}{}", dir, name));
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+        error.to_string()
+    }
+
+    #[test]
+    fn rejects_a_dependency_cycle() {
+        let error = open(
+            "cycle.stg",
+            "3\n0 0 0\n1 1 1 3\n2 1 1 1\n3 1 1 2\n4 0 1 3\n",
+            false,
+        );
+        assert!(error.ends_with("is on a dependency cycle"), "{}", error);
+        let error = open(
+            "self_loop.stg",
+            "2\n0 0 0\n1 1 1 1\n2 1 1 0\n3 0 2 1 2\n",
+            false,
+        );
+        assert!(
+            error.ends_with(": task 1 is on a dependency cycle"),
+            "{}",
+            error
+        );
+        let error = open("cycle.proto", "3 2\n1 1 0\n2 1 1 3\n3 1 1 2\n", true);
+        assert!(error.ends_with("is on a dependency cycle"), "{}", error);
+    }
+
+    #[test]
+    fn rejects_a_negative_cost() {
+        let error = open(
+            "negative.stg",
+            "2\n0 0 0\n1 -4 1 0\n2 1 1 0\n3 0 2 1 2\n",
+            false,
+        );
+        assert!(error.ends_with(":3: negative cost"), "{}", error);
+        let error = open("negative.proto", "2 2\n1 3 0\n2 -1 1 1\n", true);
+        assert!(error.ends_with(":3: negative cost"), "{}", error);
+    }
+
+    #[test]
+    fn rejects_tasks_outside_the_header() {
+        let error = open("outside.stg", "1\n0 0 0\n1 1 1 5\n2 0 1 1\n", false);
+        assert!(
+            error.ends_with(":3: task 5 outside the 3 tasks"),
+            "{}",
+            error
+        );
+    }
+}
@@ -0,0 +1,351 @@
+use std::fmt;
+use std::fs;
+use std::io;
+
+use crate::schedule::ScheduledTask;
+use crate::utils::Utils;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Violation {
+    UnknownTask(i32),
+    UnknownWorker {
+        task: i32,
+        worker: i32,
+    },
+    MissingTask(i32),
+    DuplicateTask(i32),
+    NegativeStart {
+        task: i32,
+        start: i32,
+    },
+    WrongDuration {
+        task: i32,
+        expected: i32,
+        actual: i32,
+    },
+    Precedence {
+        pred: i32,
+        succ: i32,
+        pred_finish: i32,
+        succ_start: i32,
+    },
+    Overlap {
+        worker: i32,
+        first: i32,
+        second: i32,
+    },
+    Makespan {
+        reported: i32,
+        actual: i32,
+    },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::UnknownTask(task) => write!(f, "task {} is not in the graph", task),
+            Violation::UnknownWorker { task, worker } => {
+                write!(f, "task {} runs on unknown worker {}", task, worker)
+            }
+            Violation::MissingTask(task) => write!(f, "task {} is never scheduled", task),
+            Violation::DuplicateTask(task) => {
+                write!(f, "task {} is scheduled more than once", task)
+            }
+            Violation::NegativeStart { task, start } => {
+                write!(f, "task {} starts at negative time {}", task, start)
+            }
+            Violation::WrongDuration {
+                task,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "task {} runs for {} cycles but costs {}",
+                task, actual, expected
+            ),
+            Violation::Precedence {
+                pred,
+                succ,
+                pred_finish,
+                succ_start,
+            } => write!(
+                f,
+                "task {} starts at {} before its predecessor {} finishes at {}",
+                succ, succ_start, pred, pred_finish
+            ),
+            Violation::Overlap {
+                worker,
+                first,
+                second,
+            } => write!(
+                f,
+                "tasks {} and {} overlap on worker {}",
+                first, second, worker
+            ),
+            Violation::Makespan { reported, actual } => write!(
+                f,
+                "reported makespan {} but the last task finishes at {}",
+                reported, actual
+            ),
+        }
+    }
+}
+
+// Checks a complete schedule against the instance, an empty list means it is feasible
+pub(crate) fn validate(
+    utils: &Utils,
+    n_workers: i32,
+    schedule: &[ScheduledTask],
+    reported_makespan: i32,
+) -> Vec<Violation> {
+    let n = utils.n_tasks as usize;
+    let mut violations = Vec::new();
+    let mut placed: Vec<Option<ScheduledTask>> = vec![None; n];
+
+    for entry in schedule {
+        if entry.task < 0 || entry.task as usize >= n {
+            violations.push(Violation::UnknownTask(entry.task));
+            continue;
+        }
+        if entry.worker < 0 || entry.worker >= n_workers {
+            violations.push(Violation::UnknownWorker {
+                task: entry.task,
+                worker: entry.worker,
+            });
+        }
+        if entry.start < 0 {
+            violations.push(Violation::NegativeStart {
+                task: entry.task,
+                start: entry.start,
+            });
+        }
+        let expected = utils.costs_vec[entry.task as usize];
+        if entry.finish - entry.start != expected {
+            violations.push(Violation::WrongDuration {
+                task: entry.task,
+                expected,
+                actual: entry.finish - entry.start,
+            });
+        }
+        if placed[entry.task as usize].is_some() {
+            violations.push(Violation::DuplicateTask(entry.task));
+        } else {
+            placed[entry.task as usize] = Some(*entry);
+        }
+    }
+    for (task, entry) in placed.iter().enumerate() {
+        if entry.is_none() {
+            violations.push(Violation::MissingTask(task as i32));
+        }
+    }
+
+    for edge in utils.di_graph.edge_indices() {
+        let (source, target) = utils.di_graph.edge_endpoints(edge).unwrap();
+        if let (Some(pred), Some(succ)) = (placed[source.index()], placed[target.index()]) {
+            if succ.start < pred.finish {
+                violations.push(Violation::Precedence {
+                    pred: pred.task,
+                    succ: succ.task,
+                    pred_finish: pred.finish,
+                    succ_start: succ.start,
+                });
+            }
+        }
+    }
+
+    // zero cost tasks take no time on the worker and can never overlap
+    let mut by_worker: Vec<ScheduledTask> = placed
+        .iter()
+        .flatten()
+        .filter(|entry| entry.finish > entry.start)
+        .copied()
+        .collect();
+    by_worker.sort_by_key(|entry| (entry.worker, entry.start, entry.finish));
+    for pair in by_worker.windows(2) {
+        if pair[0].worker == pair[1].worker && pair[1].start < pair[0].finish {
+            violations.push(Violation::Overlap {
+                worker: pair[0].worker,
+                first: pair[0].task,
+                second: pair[1].task,
+            });
+        }
+    }
+
+    let actual = schedule.iter().map(|entry| entry.finish).max().unwrap_or(0);
+    if actual != reported_makespan {
+        violations.push(Violation::Makespan {
+            reported: reported_makespan,
+            actual,
+        });
+    }
+
+    violations
+}
+
+// Reads a schedule from a CSV file with task,worker,start,finish as the first columns.
+// A header line, comment lines starting with '#' and any extra columns are ignored.
+pub(crate) fn read_schedule_csv(path: &str) -> io::Result<Vec<ScheduledTask>> {
+    let contents = fs::read_to_string(path)?;
+    let mut schedule = Vec::new();
+    let mut header_allowed = true;
+    for (line_number, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split(',').map(|f| f.trim()).collect();
+        let values: Result<Vec<i32>, _> = fields.iter().take(4).map(|f| f.parse()).collect();
+        match values {
+            Ok(values) if values.len() == 4 => schedule.push(ScheduledTask {
+                task: values[0],
+                worker: values[1],
+                start: values[2],
+                finish: values[3],
+            }),
+            // the header
+            Err(_) if header_allowed => {}
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "{}:{}: expected task,worker,start,finish",
+                        path,
+                        line_number + 1
+                    ),
+                ))
+            }
+        }
+        header_allowed = false;
+    }
+    Ok(schedule)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils;
+
+    // 1 -> 3 is the critical path, 4 waits for 1 and 2
+    const GRAPH: &str = "4\n0 0 0\n1 3 1 0\n2 2 1 0\n3 4 1 1\n4 1 2 1 2\n5 0 2 3 4\n";
+
+    fn entry(task: i32, worker: i32, start: i32, finish: i32) -> ScheduledTask {
+        ScheduledTask {
+            task,
+            worker,
+            start,
+            finish,
+        }
+    }
+
+    // an optimal schedule on two workers
+    fn feasible() -> Vec<ScheduledTask> {
+        vec![
+            entry(0, 0, 0, 0),
+            entry(1, 0, 0, 3),
+            entry(2, 1, 0, 2),
+            entry(3, 0, 3, 7),
+            entry(4, 1, 3, 4),
+            entry(5, 0, 7, 7),
+        ]
+    }
+
+    #[test]
+    fn accepts_a_feasible_schedule() {
+        assert!(validate(&utils::from_stg(GRAPH), 2, &feasible(), 7).is_empty());
+    }
+
+    #[test]
+    fn reports_every_kind_of_violation() {
+        let utils = utils::from_stg(GRAPH);
+        let mut schedule = feasible();
+        // task 4 before task 1 finishes, and on top of task 3
+        schedule[4] = entry(4, 0, 2, 3);
+        // task 2 one unit too long on a worker that does not exist
+        schedule[2] = entry(2, 2, 0, 3);
+        schedule.push(entry(9, 0, 0, 1));
+        schedule.push(entry(1, 1, 0, 3));
+        schedule.retain(|e| e.task != 5);
+        let violations = validate(&utils, 2, &schedule, 8);
+        for expected in [
+            Violation::UnknownTask(9),
+            Violation::UnknownWorker { task: 2, worker: 2 },
+            Violation::MissingTask(5),
+            Violation::DuplicateTask(1),
+            Violation::WrongDuration {
+                task: 2,
+                expected: 2,
+                actual: 3,
+            },
+            Violation::Precedence {
+                pred: 1,
+                succ: 4,
+                pred_finish: 3,
+                succ_start: 2,
+            },
+            Violation::Overlap {
+                worker: 0,
+                first: 1,
+                second: 4,
+            },
+            Violation::Makespan {
+                reported: 8,
+                actual: 7,
+            },
+        ] {
+            assert!(
+                violations.contains(&expected),
+                "{} not in {:?}",
+                expected,
+                violations
+            );
+        }
+    }
+
+    #[test]
+    fn zero_cost_tasks_never_overlap() {
+        let mut schedule = feasible();
+        schedule[0] = entry(0, 0, 1, 1);
+        assert!(validate(&utils::from_stg(GRAPH), 2, &schedule, 7)
+            .iter()
+            .all(|v| !matches!(v, Violation::Overlap { .. })));
+    }
+
+    #[test]
+    fn negative_start() {
+        let mut schedule = feasible();
+        schedule[0] = entry(0, 0, -1, -1);
+        let violations = validate(&utils::from_stg(GRAPH), 2, &schedule, 7);
+        assert_eq!(
+            violations,
+            vec![Violation::NegativeStart { task: 0, start: -1 }]
+        );
+    }
+
+    #[test]
+    fn reads_csv_with_a_header_and_extra_columns() {
+        let path = std::env::temp_dir()
+            .join(format!(
+                "task_scheduling_validator_{}.csv",
+                std::process::id()
+            ))
+            .to_string_lossy()
+            .to_string();
+        fs::write(
+            &path,
+            "task,worker,start,finish,cost\n# comment\n1, 0, 0, 3, 3\n\n2,1,0,2,2\n",
+        )
+        .unwrap();
+        assert_eq!(
+            read_schedule_csv(&path).unwrap(),
+            vec![entry(1, 0, 0, 3), entry(2, 1, 0, 2)]
+        );
+        fs::write(
+            &path,
+            "task,worker,start,finish\n1,0,0,3\nheader,again,x,y\n",
+        )
+        .unwrap();
+        let error = read_schedule_csv(&path).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::pherohormones::{self, Pherohormones};
+use crate::pherohormones::Pherohormones;
 #[derive(Clone, Debug)]
 pub(crate) struct WorkerAnt {
     // the identifyer of the worker is the index of the array
@@ -8,14 +8,15 @@ pub(crate) struct WorkerAnt {
     pub task_history: Vec<(i32, i32, i32)>,
 }
 impl WorkerAnt {
-    pub fn new(n_tasks: i32) -> WorkerAnt {
+    pub fn new() -> WorkerAnt {
         WorkerAnt {
             // the values for the current task are -1 which means free and  any other value higher than -1 means busy
             current_task: -1, //free
             last_task: -1,
             // the value for free_at  means the cycle that the worker will be free or better iteration
             free_at: -1,
-            task_history: vec![(0, 0, 0); n_tasks as usize],
+            // (task, start, finish) of every task this worker ran, in start order
+            task_history: Vec::new(),
         }
     }
     pub fn start_task(
@@ -27,24 +28,23 @@ impl WorkerAnt {
         deposit_rate: f64,
         current_cycle: i32,
     ) {
-        self.current_task = chosen_task as i32;
+        self.current_task = chosen_task;
         // Set the time when the ant will be free again; adjust as necessary
         self.free_at = free_at;
         if self.last_task != -1 {
             pherohormones.deposit_pherohormones(last_task, chosen_task, deposit_rate);
         }
-        self.task_history[chosen_task as usize] = (chosen_task, current_cycle, -1);
+        self.task_history.push((chosen_task, current_cycle, -1));
 
         //pherohormones.deposit_pherohormones(self.last_task, chosen_task, deposit_rate);
     }
-    pub fn complete_task(&mut self, finished_task: i32, current_cycle: i32) {
+    // finish is the cycle the task really ended (its free_at), zero cost tasks are only noticed a cycle later
+    pub fn complete_task(&mut self, finished_task: i32, finish: i32) {
         self.current_task = -1;
         self.free_at = -1;
         self.last_task = finished_task;
-        self.task_history[finished_task as usize] = (
-            finished_task,
-            self.task_history[finished_task as usize].1,
-            current_cycle,
-        );
+        if let Some(entry) = self.task_history.last_mut() {
+            *entry = (finished_task, entry.1, finish);
+        }
     }
 }